@@ -0,0 +1,53 @@
+// Turn stacked `.tabs` sections into tabs.
+// Without js the tab list stays hidden and every panel is shown with its title.
+function selectTab(tabs, tab) {
+  for (const other of tabs.querySelectorAll("[role=tab]")) {
+    const selected = other === tab;
+    other.setAttribute("aria-selected", selected);
+    other.tabIndex = selected ? 0 : -1;
+    document.getElementById(other.getAttribute("aria-controls")).hidden =
+      !selected;
+  }
+}
+
+function setupTabs(tabs) {
+  const list = tabs.querySelector("[role=tablist]");
+  const buttons = Array.from(list.querySelectorAll("[role=tab]"));
+
+  for (const button of buttons) {
+    button.addEventListener("click", () => selectTab(tabs, button));
+    button.addEventListener("keydown", (event) => {
+      const i = buttons.indexOf(button);
+      let next;
+      switch (event.key) {
+        case "ArrowRight":
+          next = buttons[(i + 1) % buttons.length];
+          break;
+        case "ArrowLeft":
+          next = buttons[(i - 1 + buttons.length) % buttons.length];
+          break;
+        case "Home":
+          next = buttons[0];
+          break;
+        case "End":
+          next = buttons[buttons.length - 1];
+          break;
+        default:
+          return;
+      }
+      event.preventDefault();
+      selectTab(tabs, next);
+      next.focus();
+    });
+  }
+
+  list.hidden = false;
+  tabs.classList.add("js-tabs");
+  selectTab(tabs, buttons[0]);
+}
+
+document.addEventListener("DOMContentLoaded", () => {
+  for (const tabs of document.querySelectorAll(".tabs")) {
+    setupTabs(tabs);
+  }
+});
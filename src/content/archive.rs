@@ -20,15 +20,10 @@ pub fn post_archives(posts: &BTreeMap<PostRef, PostItem>) -> Vec<ArchiveItem> {
         })
         .collect();
 
-    let mut by_year: HashMap<i32, Vec<PostRef>> = HashMap::new();
+    let by_year = posts_by_year(&post_refs);
     let mut by_year_month: HashMap<(i32, u32), Vec<PostRef>> = HashMap::new();
 
     for post in post_refs.iter() {
-        by_year
-            .entry(post.order.created.year())
-            .or_default()
-            .push(post.clone());
-
         by_year_month
             .entry((post.order.created.year(), post.order.created.month()))
             .or_default()
@@ -76,6 +71,18 @@ pub fn post_archives(posts: &BTreeMap<PostRef, PostItem>) -> Vec<ArchiveItem> {
     res
 }
 
+/// Group published posts by the year they were created.
+pub fn posts_by_year(post_refs: &[PostRef]) -> HashMap<i32, Vec<PostRef>> {
+    let mut by_year: HashMap<i32, Vec<PostRef>> = HashMap::new();
+    for post in post_refs.iter() {
+        by_year
+            .entry(post.order.created.year())
+            .or_default()
+            .push(post.clone());
+    }
+    by_year
+}
+
 #[derive(Debug)]
 pub struct ArchiveItem {
     pub title: String,
@@ -0,0 +1,196 @@
+use chrono::NaiveDateTime;
+use eyre::{eyre, Result};
+use itemref_derive::ItemRef;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use tera::Context;
+use url::Url;
+
+use crate::content::tags::{Tag, TagPostContext, TagsMeta};
+use crate::context::{LoadContext, MarkupResources, RenderContext};
+use crate::item::TeraItem;
+use crate::markup::{find_markup_files, Html, Markup, MarkupLookup, ParseContext, RawMarkupFile};
+use crate::paths::{AbsPath, FilePath};
+use crate::site_url::SiteUrl;
+use crate::util;
+
+pub fn load_bookmarks(
+    dir: AbsPath,
+    context: &LoadContext,
+    resources: MarkupResources,
+) -> Result<BTreeMap<BookmarkRef, BookmarkItem>> {
+    find_markup_files(&context.opts.input_dir, &[dir])
+        .par_iter()
+        .map(|path| {
+            BookmarkItem::from_file(path, resources).map(|item| (item.bookmark_ref(), item))
+        })
+        .collect()
+}
+
+#[derive(ItemRef, Debug, Clone)]
+pub struct BookmarkRef {
+    pub id: String,
+    #[order]
+    pub created: NaiveDateTime,
+}
+
+#[derive(Debug)]
+pub struct BookmarkItem {
+    pub id: String,
+    pub title: String,
+    /// The external url the bookmark points to.
+    pub link: Url,
+    pub created: NaiveDateTime,
+    pub tags: Vec<Tag>,
+    pub path: AbsPath,
+    /// Where the bookmark is found on the site, inside the `/links` archive.
+    pub url: SiteUrl,
+    pub content: Html,
+    pub markup: Markup,
+    pub markup_lookup: Option<MarkupLookup>,
+    /// Include the bookmark in the main feed.
+    pub in_feed: bool,
+}
+
+impl BookmarkItem {
+    pub fn from_file(path: &FilePath, resources: MarkupResources) -> Result<Self> {
+        let markup = RawMarkupFile::from_file(path.abs_path())?;
+        Self::from_markup(markup, resources)
+    }
+
+    pub fn from_markup(
+        markup: RawMarkupFile<BookmarkMetadata>,
+        resources: MarkupResources,
+    ) -> Result<Self> {
+        let meta_line_count = markup.meta_line_count;
+        let id = markup
+            .path
+            .file_stem()
+            .ok_or_else(|| eyre!("Missing file stem: {}", markup.path))?
+            .to_string();
+        // Bookmarks are rendered together in the links archive, so keep their ids apart.
+        let markup = markup.parse(
+            ParseContext::new(meta_line_count)
+                .with_id_prefix(&id)
+                .with_repository(resources.repository)
+                .with_embed_cache(resources.embed_cache),
+        )?;
+
+        let meta = markup.markup_meta;
+        let link = Url::parse(&meta.link)
+            .map_err(|err| eyre!("Bad link `{}` in {}: {err}", meta.link, markup.path))?;
+        let created = util::parse_date_time(&meta.created)
+            .map_err(|err| eyre!("{err} in bookmark {}", markup.path))?;
+        let url = SiteUrl::parse(&format!("/links#{id}"))?;
+
+        Ok(Self {
+            id,
+            title: meta.title,
+            link,
+            created,
+            tags: meta.tags.map(Into::into).unwrap_or_default(),
+            path: markup.path,
+            url,
+            content: markup.html,
+            markup: markup.markup,
+            markup_lookup: markup.markup_lookup,
+            in_feed: meta.feed,
+        })
+    }
+
+    pub fn bookmark_ref(&self) -> BookmarkRef {
+        BookmarkRef {
+            id: self.id.clone(),
+            created: self.created,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct BookmarksArchiveItem {
+    pub url: SiteUrl,
+    pub bookmarks: Vec<BookmarkRef>,
+}
+
+impl BookmarksArchiveItem {
+    pub fn new(bookmarks: &BTreeMap<BookmarkRef, BookmarkItem>) -> Self {
+        Self {
+            url: SiteUrl::parse("/links").unwrap(),
+            bookmarks: bookmarks.keys().cloned().collect(),
+        }
+    }
+}
+
+impl TeraItem for BookmarksArchiveItem {
+    fn context(&self, ctx: &RenderContext) -> Context {
+        Context::from_serialize(BookmarksArchiveContext {
+            title: "Links",
+            bookmarks: self
+                .bookmarks
+                .iter()
+                .map(|bookmark_ref| {
+                    let bookmark = ctx
+                        .content
+                        .bookmarks
+                        .get(bookmark_ref)
+                        .expect("Should have bookmark");
+                    BookmarkContext::from(bookmark)
+                })
+                .collect(),
+        })
+        .unwrap()
+    }
+
+    fn template(&self) -> &str {
+        "links.html"
+    }
+
+    fn tera_url(&self) -> &SiteUrl {
+        &self.url
+    }
+
+    fn tera_source_file(&self) -> Option<&AbsPath> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct BookmarksArchiveContext<'a> {
+    title: &'static str,
+    bookmarks: Vec<BookmarkContext<'a>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct BookmarkContext<'a> {
+    id: &'a str,
+    title: Cow<'a, str>,
+    link: &'a str,
+    created: String,
+    content: &'a str,
+    tags: Vec<TagPostContext<'a>>,
+}
+
+impl<'a> From<&'a BookmarkItem> for BookmarkContext<'a> {
+    fn from(bookmark: &'a BookmarkItem) -> Self {
+        Self {
+            id: &bookmark.id,
+            title: html_escape::encode_text(&bookmark.title),
+            link: bookmark.link.as_str(),
+            created: bookmark.created.format("%FT%T%.fZ").to_string(),
+            content: &bookmark.content,
+            tags: bookmark.tags.iter().map(TagPostContext::from).collect(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct BookmarkMetadata {
+    title: String,
+    link: String,
+    created: String,
+    tags: Option<TagsMeta>,
+    #[serde(default)]
+    feed: bool,
+}
@@ -32,11 +32,18 @@ impl Item for SassItem {
 }
 
 #[derive(Debug)]
-pub struct JsItem;
+pub struct JsItem {
+    /// `main.js` connects to the dev server and should only be included when serving.
+    pub include_dev_js: bool,
+}
 
 impl Item for JsItem {
     fn render(&self, ctx: &RenderContext) -> Result<()> {
-        util::copy_file("js/main.js", ctx.output_dir.join("js/main.js").as_str())
+        util::copy_file("js/tabs.js", ctx.output_dir.join("js/tabs.js").as_str())?;
+        if self.include_dev_js {
+            util::copy_file("js/main.js", ctx.output_dir.join("js/main.js").as_str())?;
+        }
+        Ok(())
     }
 
     fn url(&self) -> &SiteUrl {
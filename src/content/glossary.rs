@@ -0,0 +1,66 @@
+use crate::markup::{term_id, Glossary};
+use crate::paths::AbsPath;
+use serde::Serialize;
+use tera::Context;
+
+use crate::{context::RenderContext, item::TeraItem, site_url::SiteUrl};
+
+/// The `/glossary` page that glossary terms in posts link to.
+#[derive(Debug)]
+pub struct GlossaryItem<'a> {
+    pub glossary: &'a Glossary,
+    pub url: SiteUrl,
+}
+
+impl<'a> GlossaryItem<'a> {
+    pub fn new(glossary: &'a Glossary) -> Self {
+        Self {
+            glossary,
+            url: SiteUrl::parse("/glossary").unwrap(),
+        }
+    }
+}
+
+impl TeraItem for GlossaryItem<'_> {
+    fn context(&self, _ctx: &RenderContext) -> Context {
+        Context::from_serialize(GlossaryContext {
+            title: "Glossary",
+            terms: self
+                .glossary
+                .terms
+                .iter()
+                .map(|(term, definition)| TermContext {
+                    id: term_id(term),
+                    term,
+                    definition,
+                })
+                .collect(),
+        })
+        .unwrap()
+    }
+
+    fn template(&self) -> &str {
+        "glossary.html"
+    }
+
+    fn tera_url(&self) -> &SiteUrl {
+        &self.url
+    }
+
+    fn tera_source_file(&self) -> Option<&AbsPath> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct GlossaryContext<'a> {
+    title: &'static str,
+    terms: Vec<TermContext<'a>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct TermContext<'a> {
+    id: String,
+    term: &'a str,
+    definition: &'a str,
+}
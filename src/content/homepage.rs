@@ -1,13 +1,16 @@
+use std::borrow::Cow;
 use std::collections::BTreeMap;
 
 use eyre::Result;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use tera::Context;
 
 use crate::content::posts::{PostItem, PostRef};
 use crate::content::projects::{GameRef, Project, ProjectContext, ProjectRef};
 use crate::content::{Game, GameContext};
 use crate::content::{SeriesContext, SeriesItem, SeriesRef};
+use crate::git::SiteRepository;
+use crate::markup::{Html, MarkupFile, MarkupLookup, ParseContext, RawMarkupFile};
 use crate::paths::AbsPath;
 use crate::{context::RenderContext, item::TeraItem, site_url::SiteUrl};
 
@@ -16,99 +19,119 @@ use super::posts::PostRefContext;
 #[derive(Debug)]
 pub struct HomepageItem {
     pub url: SiteUrl,
-    pub recent: Vec<PostRef>,
-    pub recommended: Vec<PostRef>,
-    pub series: Vec<SeriesRef>,
-    pub projects: Vec<ProjectRef>,
-    pub games: Vec<GameRef>,
+    pub title: String,
+    pub intro: Option<Html>,
+    pub markup_lookup: Option<MarkupLookup>,
+    pub path: Option<AbsPath>,
+    pub sections: Vec<HomepageSection>,
+    meta: HomepageMetadata,
+}
+
+#[derive(Debug)]
+pub enum HomepageSection {
+    Posts {
+        title: Option<String>,
+        url: Option<String>,
+        groups: Vec<(Option<String>, Vec<PostRef>)>,
+    },
+    Series {
+        title: String,
+        series: Vec<SeriesRef>,
+    },
+    Projects {
+        title: String,
+        projects: Vec<ProjectRef>,
+        games: Vec<GameRef>,
+    },
 }
 
 impl HomepageItem {
+    /// Creates the homepage, configured by an optional `homepage.dj` in `dir`.
+    /// Without it we fall back to the default layout.
     pub fn new(
+        dir: &AbsPath,
         posts: &BTreeMap<PostRef, PostItem>,
         series: &BTreeMap<SeriesRef, SeriesItem>,
         projects: &BTreeMap<ProjectRef, Project>,
         games: &BTreeMap<GameRef, Game>,
+        repository: &SiteRepository,
     ) -> Result<Self> {
         let url = SiteUrl::parse("/").expect("Should be able to create a url");
 
+        let path = dir.join("homepage.dj");
+        let (meta, intro, markup_lookup, path) = if path.exists() {
+            let raw_markup = RawMarkupFile::from_file(path)?;
+            let meta_line_count = raw_markup.meta_line_count;
+            let markup: MarkupFile<HomepageMetadata> =
+                raw_markup.parse(ParseContext::new(meta_line_count).with_repository(repository))?;
+            let intro = if markup.markup.content().trim().is_empty() {
+                None
+            } else {
+                Some(markup.html)
+            };
+            (
+                markup.markup_meta,
+                intro,
+                markup.markup_lookup,
+                Some(markup.path),
+            )
+        } else {
+            (HomepageMetadata::default(), None, None, None)
+        };
+
+        let sections = meta
+            .sections
+            .iter()
+            .map(|section| section.resolve(posts, series, projects, games))
+            .collect();
+
         Ok(Self {
             url,
-            recent: Self::filter_recent(posts),
-            recommended: Self::filter_recommended(posts),
-            series: Self::filter_series(series),
-            projects: Self::filter_projects(projects),
-            games: games.keys().map(Clone::clone).collect(),
+            title: meta.title.clone().unwrap_or_else(|| "Home".to_string()),
+            intro,
+            markup_lookup,
+            path,
+            sections,
+            meta,
         })
     }
 
     pub fn update_posts(&mut self, posts: &BTreeMap<PostRef, PostItem>) {
-        self.recent = Self::filter_recent(posts);
-        self.recommended = Self::filter_recommended(posts);
-    }
-
-    fn filter_recent(posts: &BTreeMap<PostRef, PostItem>) -> Vec<PostRef> {
-        posts
-            .iter()
-            .filter(|(_, post)| !post.is_draft)
-            .take(5)
-            .map(|(post_ref, _)| post_ref.clone())
-            .collect()
-    }
-
-    fn filter_recommended(posts: &BTreeMap<PostRef, PostItem>) -> Vec<PostRef> {
-        posts
-            .iter()
-            .filter(|(_, post)| post.recommended)
-            .map(|(post_ref, _)| post_ref.clone())
-            .collect()
+        for (section, meta) in self.sections.iter_mut().zip(self.meta.sections.iter()) {
+            if let (
+                HomepageSection::Posts { groups, .. },
+                SectionMetadata::Posts {
+                    groups: group_metas,
+                    ..
+                },
+            ) = (section, meta)
+            {
+                *groups = group_metas
+                    .iter()
+                    .map(|group| (group.label.clone(), group.filter(posts)))
+                    .collect();
+            }
+        }
     }
 
-    fn filter_series(series: &BTreeMap<SeriesRef, SeriesItem>) -> Vec<SeriesRef> {
-        series
-            .iter()
-            .filter(|(_, series)| series.homepage)
-            .map(|(series_ref, _)| series_ref.clone())
-            .collect()
-    }
-
-    fn filter_projects(projects: &BTreeMap<ProjectRef, Project>) -> Vec<ProjectRef> {
-        projects
-            .iter()
-            .filter(|(_, project)| project.homepage)
-            .map(|(project_ref, _)| project_ref.clone())
-            .collect()
+    pub fn find_lookup_by_path<'a>(&'a self, path: &AbsPath) -> Option<&'a MarkupLookup> {
+        if self.path.as_ref() == Some(path) {
+            self.markup_lookup.as_ref()
+        } else {
+            None
+        }
     }
 }
 
 impl TeraItem for HomepageItem {
     fn context(&self, ctx: &RenderContext) -> Context {
         Context::from_serialize(HomepageContext {
-            title: "Home",
-            recommended: self
-                .recommended
-                .iter()
-                .map(|x| PostRefContext::from_ref(x, ctx))
-                .collect(),
-            recent: self
-                .recent
+            title: html_escape::encode_text(&self.title),
+            intro: self.intro.as_ref().map(|x| x.0.as_str()),
+            sections: self
+                .sections
                 .iter()
-                .map(|x| PostRefContext::from_ref(x, ctx))
-                .collect(),
-            series: self
-                .series
-                .iter()
-                .map(|x| SeriesContext::from_ref(x, ctx))
-                .collect(),
-            projects: self
-                .projects
-                .iter()
-                .map(|x| ProjectContext::from_ref(x, ctx))
-                .collect(),
-            games: self
-                .games
-                .iter()
-                .map(|x| GameContext::from_ref(x, ctx))
+                .map(|section| SectionContext::new(section, ctx))
                 .collect(),
         })
         .unwrap()
@@ -123,16 +146,251 @@ impl TeraItem for HomepageItem {
     }
 
     fn tera_source_file(&self) -> Option<&AbsPath> {
-        None
+        self.path.as_ref()
     }
 }
 
 #[derive(Debug, Clone, Serialize)]
 struct HomepageContext<'a> {
-    title: &'static str,
-    recommended: Vec<PostRefContext<'a>>,
-    recent: Vec<PostRefContext<'a>>,
-    series: Vec<SeriesContext<'a>>,
-    projects: Vec<ProjectContext<'a>>,
-    games: Vec<GameContext<'a>>,
+    title: Cow<'a, str>,
+    intro: Option<&'a str>,
+    sections: Vec<SectionContext<'a>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum SectionContext<'a> {
+    Posts {
+        title: Option<Cow<'a, str>>,
+        url: Option<&'a str>,
+        groups: Vec<PostGroupContext<'a>>,
+    },
+    Series {
+        title: Cow<'a, str>,
+        series: Vec<SeriesContext<'a>>,
+    },
+    Projects {
+        title: Cow<'a, str>,
+        projects: Vec<ProjectContext<'a>>,
+        games: Vec<GameContext<'a>>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct PostGroupContext<'a> {
+    label: Option<Cow<'a, str>>,
+    posts: Vec<PostRefContext<'a>>,
+}
+
+impl<'a> SectionContext<'a> {
+    fn new(section: &'a HomepageSection, ctx: &'a RenderContext) -> Self {
+        match section {
+            HomepageSection::Posts { title, url, groups } => Self::Posts {
+                title: title.as_deref().map(html_escape::encode_text),
+                url: url.as_deref(),
+                groups: groups
+                    .iter()
+                    .map(|(label, posts)| PostGroupContext {
+                        label: label
+                            .as_deref()
+                            .map(html_escape::encode_double_quoted_attribute),
+                        posts: posts
+                            .iter()
+                            .map(|x| PostRefContext::from_ref(x, ctx))
+                            .collect(),
+                    })
+                    .collect(),
+            },
+            HomepageSection::Series { title, series } => Self::Series {
+                title: html_escape::encode_text(title),
+                series: series
+                    .iter()
+                    .map(|x| SeriesContext::from_ref(x, ctx))
+                    .collect(),
+            },
+            HomepageSection::Projects {
+                title,
+                projects,
+                games,
+            } => Self::Projects {
+                title: html_escape::encode_text(title),
+                projects: projects
+                    .iter()
+                    .map(|x| ProjectContext::from_ref(x, ctx))
+                    .collect(),
+                games: games
+                    .iter()
+                    .map(|x| GameContext::from_ref(x, ctx))
+                    .collect(),
+            },
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct HomepageMetadata {
+    title: Option<String>,
+    #[serde(default)]
+    sections: Vec<SectionMetadata>,
+}
+
+impl Default for HomepageMetadata {
+    fn default() -> Self {
+        Self {
+            title: None,
+            sections: vec![
+                SectionMetadata::Posts {
+                    title: Some("Posts".to_string()),
+                    url: Some("/archive".to_string()),
+                    groups: vec![
+                        PostGroupMetadata {
+                            label: Some("Recent".to_string()),
+                            count: Some(5),
+                            ..Default::default()
+                        },
+                        PostGroupMetadata {
+                            label: Some("Featured".to_string()),
+                            recommended: Some(true),
+                            ..Default::default()
+                        },
+                    ],
+                },
+                SectionMetadata::Series {
+                    title: None,
+                    count: None,
+                    all: false,
+                },
+                SectionMetadata::Projects {
+                    title: None,
+                    count: None,
+                    all: false,
+                    games: true,
+                },
+            ],
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum SectionMetadata {
+    Posts {
+        title: Option<String>,
+        url: Option<String>,
+        #[serde(default)]
+        groups: Vec<PostGroupMetadata>,
+    },
+    Series {
+        title: Option<String>,
+        count: Option<usize>,
+        /// Include all series, not only the ones marked with `homepage = true`.
+        #[serde(default)]
+        all: bool,
+    },
+    Projects {
+        title: Option<String>,
+        count: Option<usize>,
+        /// Include all projects, not only the ones marked with `homepage = true`.
+        #[serde(default)]
+        all: bool,
+        #[serde(default = "default_true")]
+        games: bool,
+    },
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl SectionMetadata {
+    fn resolve(
+        &self,
+        posts: &BTreeMap<PostRef, PostItem>,
+        series: &BTreeMap<SeriesRef, SeriesItem>,
+        projects: &BTreeMap<ProjectRef, Project>,
+        games: &BTreeMap<GameRef, Game>,
+    ) -> HomepageSection {
+        match self {
+            Self::Posts { title, url, groups } => HomepageSection::Posts {
+                title: title.clone(),
+                url: url.clone(),
+                groups: groups
+                    .iter()
+                    .map(|group| (group.label.clone(), group.filter(posts)))
+                    .collect(),
+            },
+            Self::Series { title, count, all } => HomepageSection::Series {
+                title: title.clone().unwrap_or_else(|| "Series".to_string()),
+                series: series
+                    .iter()
+                    .filter(|(_, series)| *all || series.homepage)
+                    .take(count.unwrap_or(usize::MAX))
+                    .map(|(series_ref, _)| series_ref.clone())
+                    .collect(),
+            },
+            Self::Projects {
+                title,
+                count,
+                all,
+                games: include_games,
+            } => HomepageSection::Projects {
+                title: title.clone().unwrap_or_else(|| "Projects".to_string()),
+                projects: projects
+                    .iter()
+                    .filter(|(_, project)| *all || project.homepage)
+                    .take(count.unwrap_or(usize::MAX))
+                    .map(|(project_ref, _)| project_ref.clone())
+                    .collect(),
+                games: if *include_games {
+                    games.keys().map(Clone::clone).collect()
+                } else {
+                    Vec::new()
+                },
+            },
+        }
+    }
+}
+
+/// A filtered list of posts, shown under a label.
+/// All filters must match for a post to be included.
+#[derive(Deserialize, Debug, Default)]
+struct PostGroupMetadata {
+    label: Option<String>,
+    count: Option<usize>,
+    /// Include posts that have any of these tags.
+    #[serde(default)]
+    tags: Vec<String>,
+    series: Option<String>,
+    recommended: Option<bool>,
+    favorite: Option<bool>,
+}
+
+impl PostGroupMetadata {
+    fn filter(&self, posts: &BTreeMap<PostRef, PostItem>) -> Vec<PostRef> {
+        posts
+            .iter()
+            .filter(|(_, post)| self.accept(post))
+            .take(self.count.unwrap_or(usize::MAX))
+            .map(|(post_ref, _)| post_ref.clone())
+            .collect()
+    }
+
+    fn accept(&self, post: &PostItem) -> bool {
+        if post.is_draft {
+            return false;
+        }
+        if self.recommended.is_some_and(|x| x != post.recommended) {
+            return false;
+        }
+        if self.favorite.is_some_and(|x| x != post.favorite) {
+            return false;
+        }
+        if self.series.is_some() && self.series != post.series_id {
+            return false;
+        }
+        if !self.tags.is_empty() && !post.tags.iter().any(|tag| self.tags.contains(&tag.id)) {
+            return false;
+        }
+        true
+    }
 }
@@ -1,8 +1,9 @@
 use crate::content::{
-    Game, PartialPostItem, PartialProject, PartialProjectsItem, PartialStandaloneItem, PostItem,
-    PostRef, SeriesItem, StandaloneItem, Tag,
+    Game, NoteItem, PartialPostItem, PartialProject, PartialProjectsItem, PartialStandaloneItem,
+    PostItem, SeriesItem, StandaloneItem, Tag, TaggedItems,
 };
 use crate::markup::markup_lookup::{Heading, LinkDef};
+use crate::markup::BibEntry;
 use crate::paths::FilePath;
 use crate::site::Site;
 use eyre::eyre;
@@ -27,6 +28,7 @@ pub enum ContentInfo {
     BrokenLink(BrokenLinkInfo),
     DivClass(DivClassInfo),
     Symbol(SymbolInfo),
+    Citation(CitationInfo),
 }
 
 impl TryFrom<&FilePath> for ContentInfo {
@@ -57,7 +59,7 @@ impl TryFrom<&FilePath> for ContentInfo {
 
 #[derive(Debug, Serialize, PartialEq, Eq, Clone)]
 pub struct DivClassInfo {
-    pub name: &'static str,
+    pub name: String,
 }
 
 #[derive(Debug, Serialize, PartialEq, Eq, Clone)]
@@ -71,15 +73,17 @@ pub struct TagInfo {
     pub name: String,
     pub url: String,
     pub posts: Vec<PostInfo>,
+    pub notes: Vec<NoteInfo>,
 }
 
 impl TagInfo {
-    pub fn from_tag(tag: &Tag, posts: &[PostRef], site: &Site) -> Self {
+    pub fn from_tag(tag: &Tag, items: &TaggedItems, site: &Site) -> Self {
         Self {
             id: tag.id.clone(),
             name: tag.name.to_string(),
             url: tag.url.href().to_string(),
-            posts: posts
+            posts: items
+                .posts
                 .iter()
                 .map(|post_ref| {
                     site.content
@@ -89,6 +93,34 @@ impl TagInfo {
                         .into()
                 })
                 .collect(),
+            notes: items
+                .notes
+                .iter()
+                .map(|note_ref| {
+                    site.content
+                        .notes
+                        .get(note_ref)
+                        .expect("Tag references non-existent note")
+                        .into()
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq, Clone)]
+pub struct NoteInfo {
+    pub path: String,
+    pub created: String,
+    pub url: String,
+}
+
+impl From<&NoteItem> for NoteInfo {
+    fn from(note: &NoteItem) -> Self {
+        NoteInfo {
+            path: note.path.to_string(),
+            url: note.url.href().to_string(),
+            created: note.created.format("%F").to_string(),
         }
     }
 }
@@ -293,6 +325,25 @@ impl BrokenLinkInfo {
     }
 }
 
+#[derive(Debug, Serialize, PartialEq, Eq, Clone)]
+pub struct CitationInfo {
+    pub key: String,
+    pub title: Option<String>,
+    pub authors: String,
+    pub year: Option<String>,
+}
+
+impl From<&BibEntry> for CitationInfo {
+    fn from(entry: &BibEntry) -> Self {
+        Self {
+            key: entry.key.clone(),
+            title: entry.title.clone(),
+            authors: entry.short_authors(),
+            year: entry.year.clone(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, PartialEq, Eq, Clone)]
 pub struct ImgInfo {
     pub url: String,
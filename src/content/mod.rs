@@ -1,28 +1,38 @@
 mod archive;
+mod bookmarks;
 mod external;
+mod glossary;
 mod homepage;
 mod info;
 mod nav_highlight;
+mod notes;
 mod posts;
 mod projects;
 mod series;
 mod series_archive;
+mod site_symbols;
 mod standalone;
 mod tags;
+mod year_review;
 
 pub use archive::{post_archives, ArchiveItem};
+pub use bookmarks::{load_bookmarks, BookmarkItem, BookmarkRef, BookmarksArchiveItem};
 pub use external::{JsItem, SassItem};
+pub use glossary::GlossaryItem;
 pub use homepage::HomepageItem;
 pub use info::{
-    BrokenLinkInfo, ConstantInfo, ContentInfo, DivClassInfo, HeadingContext, HeadingInfo, ImgInfo,
-    LinkDefInfo, PostInfo, SeriesInfo, StandaloneInfo, SymbolInfo, TagInfo,
+    BrokenLinkInfo, CitationInfo, ConstantInfo, ContentInfo, DivClassInfo, HeadingContext,
+    HeadingInfo, ImgInfo, LinkDefInfo, PostInfo, SeriesInfo, StandaloneInfo, SymbolInfo, TagInfo,
 };
 pub use nav_highlight::add_nav_highlight;
+pub use notes::{load_notes, notes_archives, NoteItem, NoteRef, NotesArchiveItem};
 pub use posts::{
     load_posts, set_post_prev_next, CountedWordsPostItem, PartialPostItem, PostItem, PostRef,
 };
 pub use projects::{Game, GameContext, PartialProject, PartialProjectsItem, ProjectsItem};
 pub use series::{load_series, SeriesContext, SeriesItem, SeriesRef};
 pub use series_archive::SeriesArchiveItem;
+pub use site_symbols::{has_site_symbols, render_site_symbols};
 pub use standalone::{load_standalones, PartialStandaloneItem, StandaloneItem};
-pub use tags::{tags_archives, Tag, TagListItem};
+pub use tags::{tags_archives, Tag, TagListItem, TaggedItems};
+pub use year_review::{year_reviews, YearReviewItem};
@@ -0,0 +1,282 @@
+use chrono::NaiveDateTime;
+use eyre::{eyre, Result};
+use itemref_derive::ItemRef;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashSet};
+use tera::Context;
+
+use crate::content::tags::{Tag, TagPostContext, TagsMeta};
+use crate::context::{LoadContext, MarkupResources, RenderContext};
+use crate::item::{Item, TeraItem};
+use crate::markup::{find_markup_files, Html, Markup, MarkupLookup, ParseContext, RawMarkupFile};
+use crate::paths::{AbsPath, FilePath, RelPath};
+use crate::site_url::SiteUrl;
+use crate::util;
+
+const NOTES_PER_PAGE: usize = 20;
+
+pub fn load_notes(
+    dir: AbsPath,
+    context: &LoadContext,
+    resources: MarkupResources,
+) -> Result<BTreeMap<NoteRef, NoteItem>> {
+    find_markup_files(&context.opts.input_dir, &[dir])
+        .par_iter()
+        .map(|path| NoteItem::from_file(path, resources).map(|note| (note.note_ref(), note)))
+        .collect()
+}
+
+/// Split the notes into stream pages, the first one at `/notes`.
+pub fn notes_archives(notes: &BTreeMap<NoteRef, NoteItem>) -> Vec<NotesArchiveItem> {
+    let refs: Vec<NoteRef> = notes.keys().cloned().collect();
+    let chunks: Vec<&[NoteRef]> = if refs.is_empty() {
+        vec![&[]]
+    } else {
+        refs.chunks(NOTES_PER_PAGE).collect()
+    };
+    let num_pages = chunks.len();
+
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(i, notes)| {
+            let page = i + 1;
+            NotesArchiveItem {
+                url: NotesArchiveItem::page_url(page),
+                notes: notes.to_vec(),
+                page,
+                prev: (page > 1).then(|| NotesArchiveItem::page_url(page - 1)),
+                next: (page < num_pages).then(|| NotesArchiveItem::page_url(page + 1)),
+            }
+        })
+        .collect()
+}
+
+#[derive(ItemRef, Debug, Clone)]
+#[item(NoteItem)]
+pub struct NoteRef {
+    pub id: String,
+    #[order]
+    pub created: NaiveDateTime,
+}
+
+#[derive(Debug)]
+pub struct NoteItem {
+    pub created: NaiveDateTime,
+    pub tags: Vec<Tag>,
+    pub path: AbsPath,
+    pub url: SiteUrl,
+    pub content: Html,
+    pub markup: Markup,
+    pub markup_lookup: Option<MarkupLookup>,
+    pub embedded_files: HashSet<RelPath>,
+}
+
+impl NoteItem {
+    pub fn from_file(path: &FilePath, resources: MarkupResources) -> Result<Self> {
+        let markup = RawMarkupFile::from_file(path.abs_path())?;
+        Self::from_markup(markup, resources)
+    }
+
+    pub fn from_markup(
+        markup: RawMarkupFile<NoteMetadata>,
+        resources: MarkupResources,
+    ) -> Result<Self> {
+        let meta_line_count = markup.meta_line_count;
+        let slug = markup
+            .path
+            .file_stem()
+            .ok_or_else(|| eyre!("Missing file stem: {}", markup.path))?
+            .to_string();
+        // Notes are rendered together in the stream, so keep their ids apart.
+        let markup = markup.parse(
+            ParseContext::new(meta_line_count)
+                .with_id_prefix(&slug)
+                .with_repository(resources.repository)
+                .with_embed_cache(resources.embed_cache),
+        )?;
+
+        let url = SiteUrl::parse(&format!("/notes/{slug}/"))?;
+        let created = util::parse_date_time(&markup.markup_meta.created)
+            .map_err(|err| eyre!("{err} in note {}", markup.path))?;
+
+        Ok(Self {
+            created,
+            tags: markup.markup_meta.tags.map(Into::into).unwrap_or_default(),
+            path: markup.path,
+            url,
+            content: markup.html,
+            markup: markup.markup,
+            markup_lookup: markup.markup_lookup,
+            embedded_files: markup.embedded_files,
+        })
+    }
+
+    pub fn note_ref(&self) -> NoteRef {
+        NoteRef {
+            id: self.id().to_string(),
+            created: self.created,
+        }
+    }
+
+    /// Notes are untitled, so we use the timestamp where a title is required.
+    pub fn title(&self) -> String {
+        format!("Note from {}", self.created.format("%B %e, %Y %H:%M"))
+    }
+}
+
+impl TeraItem for NoteItem {
+    fn context(&self, _ctx: &RenderContext) -> Context {
+        Context::from_serialize(NotePageContext {
+            title: self.title(),
+            note: NoteContext::from(self),
+            meta_keywords: self.tags.iter().map(|tag| tag.name.as_str()).collect(),
+        })
+        .unwrap()
+    }
+
+    fn template(&self) -> &str {
+        "note.html"
+    }
+
+    fn tera_url(&self) -> &SiteUrl {
+        &self.url
+    }
+
+    fn tera_source_file(&self) -> Option<&AbsPath> {
+        Some(&self.path)
+    }
+}
+
+#[derive(Debug)]
+pub struct NotesArchiveItem {
+    pub url: SiteUrl,
+    pub notes: Vec<NoteRef>,
+    pub page: usize,
+    pub prev: Option<SiteUrl>,
+    pub next: Option<SiteUrl>,
+}
+
+impl NotesArchiveItem {
+    fn page_url(page: usize) -> SiteUrl {
+        if page == 1 {
+            SiteUrl::parse("/notes").unwrap()
+        } else {
+            SiteUrl::parse(&format!("/notes/page/{page}")).unwrap()
+        }
+    }
+}
+
+impl TeraItem for NotesArchiveItem {
+    fn context(&self, ctx: &RenderContext) -> Context {
+        Context::from_serialize(NotesArchiveContext {
+            title: "Notes",
+            notes: self
+                .notes
+                .iter()
+                .map(|note_ref| {
+                    let note = ctx.content.notes.get(note_ref).expect("Should have note");
+                    NoteContext::from(note)
+                })
+                .collect(),
+            page: self.page,
+            prev: self.prev.as_ref().map(|x| x.href()),
+            next: self.next.as_ref().map(|x| x.href()),
+        })
+        .unwrap()
+    }
+
+    fn template(&self) -> &str {
+        "notes.html"
+    }
+
+    fn tera_url(&self) -> &SiteUrl {
+        &self.url
+    }
+
+    fn tera_source_file(&self) -> Option<&AbsPath> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct NotePageContext<'a> {
+    title: String,
+    note: NoteContext<'a>,
+    meta_keywords: Vec<&'a str>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct NotesArchiveContext<'a> {
+    title: &'static str,
+    notes: Vec<NoteContext<'a>>,
+    page: usize,
+    prev: Option<Cow<'a, str>>,
+    next: Option<Cow<'a, str>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct NoteContext<'a> {
+    url: Cow<'a, str>,
+    created: String,
+    content: &'a str,
+    tags: Vec<TagPostContext<'a>>,
+}
+
+impl<'a> From<&'a NoteItem> for NoteContext<'a> {
+    fn from(note: &'a NoteItem) -> Self {
+        Self {
+            url: note.url.href(),
+            created: note.created.format("%FT%T%.fZ").to_string(),
+            content: &note.content,
+            tags: note.tags.iter().map(TagPostContext::from).collect(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct NoteMetadata {
+    created: String,
+    tags: Option<TagsMeta>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::SiteRepository;
+    use crate::markup::{Bibliography, EmbedCache, Glossary, ProseLint, SpellChecker};
+    use crate::tests::{git_fixture, templates};
+    use camino::Utf8Path;
+
+    #[test]
+    fn test_note_includes_code_from_git() -> Result<()> {
+        let (_dir, repo) = git_fixture(&[("site/snippet.txt", "Included from git\n")])?;
+        let repository = SiteRepository::new(repo, Some(Utf8Path::new("site/")));
+
+        let content = r#"---toml
+created = "2024-03-01 10:00"
+---
+
+{git="HEAD:snippet.txt"}
+```
+```
+"#;
+        let note = NoteItem::from_markup(
+            RawMarkupFile::from_content(content.to_string(), "notes/a_note.dj".into())?,
+            MarkupResources {
+                bibliography: &Bibliography::default(),
+                glossary: &Glossary::default(),
+                spelling: &SpellChecker::default(),
+                prose_lint: &ProseLint::default(),
+                embed_cache: &EmbedCache::default(),
+                templates: templates(),
+                repository: &repository,
+            },
+        )?;
+        assert!(note.content.contains("Included from git"));
+
+        Ok(())
+    }
+}
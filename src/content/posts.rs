@@ -2,6 +2,7 @@ use camino::Utf8Path;
 use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
 use eyre::{eyre, Result};
 use itemref_derive::ItemRef;
+use itertools::Itertools;
 use lazy_static::lazy_static;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use regex::Regex;
@@ -14,18 +15,29 @@ use tera::Context;
 use tracing::error;
 
 use crate::content::series::SeriesRef;
+use crate::content::site_symbols::render_site_symbols;
 use crate::content::tags::{Tag, TagPostContext, TagsMeta};
-use crate::context::{LoadContext, RenderContext};
+use crate::context::{LoadContext, MarkupResources, RenderContext};
 use crate::git::{CommitContext, LatestCommitInfo};
 use crate::item::Item;
-use crate::markup::{self, Html, Markup, MarkupLookup, ParseContext, RawMarkupFile};
+use crate::markup::markup_lookup::{ChangeLog, ChangeLogItem};
+use crate::markup::{
+    self, heading_tree, CitationStyle, Html, LintRule, Markup, MarkupLookup, ParseContext,
+    RawMarkupFile, TocEntry,
+};
 use crate::paths::{AbsPath, FilePath, RelPath};
 use crate::{content::SeriesItem, item::TeraItem, site_url::SiteUrl, util};
 
-pub fn load_posts(dirs: &[AbsPath], context: &LoadContext) -> Result<BTreeMap<PostRef, PostItem>> {
+pub fn load_posts(
+    dirs: &[AbsPath],
+    context: &LoadContext,
+    resources: MarkupResources,
+) -> Result<BTreeMap<PostRef, PostItem>> {
     let mut posts = markup::find_markup_files(&context.opts.input_dir, dirs)
         .par_iter()
-        .map(|path| PostItem::from_file(path, context).map(|post| (post.post_ref(), post)))
+        .map(|path| {
+            PostItem::from_file(path, context, resources).map(|post| (post.post_ref(), post))
+        })
         .collect::<Result<BTreeMap<PostRef, PostItem>>>()?;
 
     set_post_prev_next(&mut posts);
@@ -93,34 +105,54 @@ pub struct PostItem {
     pub content: Html,
     pub markup: Markup,
     pub markup_lookup: Option<MarkupLookup>,
+    pub changelog: Option<ChangeLog>,
     pub embedded_files: HashSet<RelPath>,
     pub series_id: Option<String>,
     pub series: Option<SeriesRef>,
     pub is_draft: bool,
+    pub citation_style: CitationStyle,
 }
 
 impl PostItem {
-    pub fn from_file(path: &FilePath, context: &LoadContext) -> Result<Self> {
+    pub fn from_file(
+        path: &FilePath,
+        context: &LoadContext,
+        resources: MarkupResources,
+    ) -> Result<Self> {
         let abs_path = path.abs_path();
         let modified = util::last_modified(&abs_path)?;
         let markup = RawMarkupFile::from_file(abs_path)?;
         let latest_commit = context.get_commit(path).cloned();
-        Self::from_markup(markup, modified, latest_commit)
+        Self::from_markup(markup, modified, latest_commit, resources)
     }
 
     pub fn from_markup(
         markup: RawMarkupFile<PostMetadata>,
         modified: NaiveDateTime,
         latest_commit: Option<LatestCommitInfo>,
+        resources: MarkupResources,
     ) -> Result<Self> {
         let partial =
             PartialPostItem::from_markup(markup.path.clone(), &markup.markup_meta, modified)?;
 
         let meta_line_count = markup.meta_line_count;
-        let markup = markup.parse(ParseContext::new_post_context(
-            partial.is_draft,
-            meta_line_count,
-        ))?;
+        let citation_style = markup.markup_meta.citation_style;
+        let lint_rules = markup
+            .markup_meta
+            .prose_lint
+            .clone()
+            .unwrap_or_else(|| resources.prose_lint.default_rules.clone());
+        let markup = markup.parse(
+            ParseContext::new_post_context(partial.is_draft, meta_line_count)
+                .with_bibliography(resources.bibliography)
+                .with_glossary(resources.glossary)
+                .with_spelling(resources.spelling)
+                .with_prose_lint(resources.prose_lint, &lint_rules)
+                .with_templates(resources.templates)
+                .with_repository(resources.repository)
+                .with_embed_cache(resources.embed_cache)
+                .citation_style(citation_style),
+        )?;
 
         Ok(Self {
             title: partial.title,
@@ -134,6 +166,10 @@ impl PostItem {
             next: None,
             content: markup.html,
             markup: markup.markup,
+            changelog: markup
+                .markup_lookup
+                .as_ref()
+                .and_then(|lookup| lookup.changelog.clone()),
             markup_lookup: markup.markup_lookup,
             embedded_files: markup.embedded_files,
             series_id: partial.series_id,
@@ -141,6 +177,7 @@ impl PostItem {
             recommended: partial.recommended,
             favorite: partial.favorite,
             is_draft: partial.is_draft,
+            citation_style,
         })
     }
 
@@ -167,12 +204,26 @@ impl TeraItem for PostItem {
             }
         });
 
+        let headings = self
+            .markup_lookup
+            .as_ref()
+            .map(|lookup| heading_tree(lookup, 6))
+            .unwrap_or_default();
+        let toc = self.markup_lookup.as_ref().and_then(|lookup| {
+            let opts = lookup.toc?;
+            Some(TocContext {
+                numbered: opts.numbered,
+                sidebar: opts.sidebar,
+                entries: heading_tree(lookup, opts.depth),
+            })
+        });
+
         Context::from_serialize(PostContext {
             title: html_escape::encode_text(&self.title),
             url: self.url.href(),
             created: self.created.format("%FT%T%.fZ").to_string(),
             latest_commit: self.latest_commit.as_ref().map(Into::into),
-            content: &self.content,
+            content: render_site_symbols(&self.content, ctx.content),
             tags: self.tags.iter().map(TagPostContext::from).collect(),
             meta_keywords: self.tags.iter().map(|tag| tag.name.as_str()).collect(),
             series,
@@ -180,6 +231,20 @@ impl TeraItem for PostItem {
             prev: self.prev.as_ref().map(|x| PostRefContext::from_ref(x, ctx)),
             next: self.next.as_ref().map(|x| PostRefContext::from_ref(x, ctx)),
             is_draft: self.is_draft,
+            headings,
+            toc,
+            changelog: self
+                .changelog
+                .as_ref()
+                .map(|changelog| {
+                    changelog
+                        .items
+                        .iter()
+                        .sorted_by_key(|item| item.date)
+                        .map(Into::into)
+                        .collect()
+                })
+                .unwrap_or_default(),
         })
         .unwrap()
     }
@@ -287,22 +352,19 @@ pub struct CountedWordsPostItem {
 }
 
 impl CountedWordsPostItem {
-    pub fn from_file(path: AbsPath) -> Result<Self> {
-        let post_dir = PostDirMetadata::parse_post(&path)?;
-        let markup: RawMarkupFile<PostMetadata> = RawMarkupFile::from_file(path)?;
-
-        let words: Vec<_> = markup.markup.content().split_whitespace().collect();
-
-        let url = post_dir.to_url()?;
+    pub fn from_post(post: &PostItem) -> Self {
+        Self {
+            title: post.title.clone(),
+            tags: post.tags.clone(),
+            created: post.created.date(),
+            url: post.url.clone(),
+            series_id: post.series_id.clone(),
+            word_count: Self::count_words(post.markup.content()),
+        }
+    }
 
-        Ok(Self {
-            title: markup.markup_meta.title.clone(),
-            tags: markup.markup_meta.tags.clone().into(),
-            created: post_dir.date,
-            url,
-            series_id: markup.markup_meta.series.clone(),
-            word_count: words.len(),
-        })
+    fn count_words(content: &str) -> usize {
+        content.split_whitespace().count()
     }
 }
 
@@ -312,7 +374,7 @@ struct PostContext<'a> {
     url: Cow<'a, str>,
     created: String,
     latest_commit: Option<CommitContext>,
-    content: &'a str,
+    content: Cow<'a, str>,
     favorite: bool,
     tags: Vec<TagPostContext<'a>>,
     meta_keywords: Vec<&'a str>,
@@ -320,6 +382,34 @@ struct PostContext<'a> {
     prev: Option<PostRefContext<'a>>,
     next: Option<PostRefContext<'a>>,
     is_draft: bool,
+    /// All headings in the post, nested below their parent heading.
+    headings: Vec<TocEntry>,
+    toc: Option<TocContext>,
+    changelog: Vec<ChangeLogContext<'a>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ChangeLogContext<'a> {
+    date: String,
+    datetime: String,
+    description: &'a str,
+}
+
+impl<'a> From<&'a ChangeLogItem> for ChangeLogContext<'a> {
+    fn from(item: &'a ChangeLogItem) -> Self {
+        Self {
+            date: item.date.format("%B %e, %Y").to_string(),
+            datetime: item.date.format("%Y-%m-%dT00:00:00Z").to_string(),
+            description: &item.description,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct TocContext {
+    numbered: bool,
+    sidebar: bool,
+    entries: Vec<TocEntry>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -399,6 +489,10 @@ pub struct PostMetadata {
     pub series: Option<String>,
     pub recommended: Option<bool>,
     pub favorite: Option<bool>,
+    #[serde(default)]
+    pub citation_style: CitationStyle,
+    /// Prose lint rules to use instead of the defaults in `prose_lint.toml`.
+    pub prose_lint: Option<Vec<LintRule>>,
 }
 
 #[derive(Debug)]
@@ -477,6 +571,7 @@ mod tests {
     use std::path::PathBuf;
 
     use super::*;
+    use crate::markup::{Bibliography, EmbedCache, Glossary, ProseLint, SpellChecker};
     use crate::tests::*;
     use crate::{context::RenderContext, site::SiteContext};
     use git2::Oid;
@@ -500,6 +595,15 @@ mod tests {
                 id: Oid::from_str("f66a95823286a8d05fc4878fb40f7391545cdb91")?,
                 is_revision: true,
             }),
+            MarkupResources {
+                bibliography: &Bibliography::default(),
+                glossary: &Glossary::default(),
+                spelling: &SpellChecker::default(),
+                prose_lint: &ProseLint::default(),
+                embed_cache: &EmbedCache::default(),
+                templates: templates(),
+                repository: repository(),
+            },
         )?;
 
         assert_eq!(post.title, "Post & Title");
@@ -595,6 +699,7 @@ mod tests {
             parent_context: &Context::from_serialize(SiteContext::new(false, false)).unwrap(),
             output_dir: ".test_output".into(), // Not used
             tera: tests::templates(),
+            repository: tests::repository(),
             content: &test_site.site.content,
         })?;
         let document = Html::parse_document(&rendered);
@@ -8,6 +8,7 @@ use std::collections::BTreeMap;
 use tera::Context;
 
 use crate::context::{LoadContext, RenderContext};
+use crate::git::SiteRepository;
 use crate::item::TeraItem;
 use crate::markup::find_markup_files;
 use crate::markup::{Html, MarkupFile, MarkupLookup, ParseContext, RawMarkupFile};
@@ -30,8 +31,8 @@ impl ProjectsItem {
         let raw_markup = RawMarkupFile::from_file(dir.join("projects.dj"))?;
 
         let meta_line_count = raw_markup.meta_line_count;
-        let markup: MarkupFile<ProjectsMetadata> =
-            raw_markup.parse(ParseContext::new(meta_line_count))?;
+        let markup: MarkupFile<ProjectsMetadata> = raw_markup
+            .parse(ParseContext::new(meta_line_count).with_repository(context.repository))?;
 
         let title = markup.markup_meta.title.clone();
 
@@ -42,7 +43,10 @@ impl ProjectsItem {
         let projects = project_files
             .iter()
             .filter(|path| !is_game(path))
-            .map(|path| Project::from_file(path.abs_path()).map(|p| (p.project_ref(), p)))
+            .map(|path| {
+                Project::from_file(path.abs_path(), context.repository)
+                    .map(|p| (p.project_ref(), p))
+            })
             .collect::<Result<BTreeMap<ProjectRef, Project>>>()?;
 
         let games = project_files
@@ -159,14 +163,18 @@ pub struct Project {
 }
 
 impl Project {
-    pub fn from_file(path: AbsPath) -> Result<Self> {
+    pub fn from_file(path: AbsPath, repository: &SiteRepository) -> Result<Self> {
         let markup = RawMarkupFile::from_file(path)?;
-        Self::from_markup(markup)
+        Self::from_markup(markup, repository)
     }
 
-    pub fn from_markup(markup: RawMarkupFile<ProjectMetadata>) -> Result<Self> {
+    pub fn from_markup(
+        markup: RawMarkupFile<ProjectMetadata>,
+        repository: &SiteRepository,
+    ) -> Result<Self> {
         let meta_line_count = markup.meta_line_count;
-        let markup = markup.parse(ParseContext::new(meta_line_count))?;
+        let markup =
+            markup.parse(ParseContext::new(meta_line_count).with_repository(repository))?;
 
         Ok(Self {
             title: markup.markup_meta.title,
@@ -1,4 +1,4 @@
-use crate::context::LoadContext;
+use crate::context::{LoadContext, MarkupResources};
 use crate::item::Item;
 use crate::markup::{find_markup_files, Html, Markup, MarkupLookup, ParseContext, RawMarkupFile};
 use crate::paths::{AbsPath, FilePath};
@@ -19,6 +19,7 @@ use super::posts::{PostRef, PostRefContext};
 pub fn load_series(
     dir: AbsPath,
     context: &LoadContext,
+    resources: MarkupResources,
     posts: &mut BTreeMap<PostRef, PostItem>,
 ) -> Result<BTreeMap<SeriesRef, SeriesItem>> {
     let mut posts_in_series: HashMap<String, BTreeSet<Reverse<PostRef>>> = HashMap::new();
@@ -31,7 +32,7 @@ pub fn load_series(
 
     let mut series = find_markup_files(&context.opts.input_dir, &[dir])
         .par_iter_mut()
-        .map(|path| SeriesItem::from_file(path).map(|serie| (serie.id.clone(), serie)))
+        .map(|path| SeriesItem::from_file(path, resources).map(|serie| (serie.id.clone(), serie)))
         .collect::<Result<HashMap<_, _>>>()?;
 
     for (id, series_posts) in posts_in_series.into_iter() {
@@ -82,15 +83,22 @@ pub struct SeriesItem {
 }
 
 impl SeriesItem {
-    pub fn from_file(path: &FilePath) -> Result<Self> {
+    pub fn from_file(path: &FilePath, resources: MarkupResources) -> Result<Self> {
         let abs_path = path.abs_path();
         let markup = RawMarkupFile::from_file(abs_path)?;
-        Self::from_markup(markup)
+        Self::from_markup(markup, resources)
     }
 
-    pub fn from_markup(markup: RawMarkupFile<SeriesMetadata>) -> Result<Self> {
+    pub fn from_markup(
+        markup: RawMarkupFile<SeriesMetadata>,
+        resources: MarkupResources,
+    ) -> Result<Self> {
         let meta_line_count = markup.meta_line_count;
-        let markup = markup.parse(ParseContext::new(meta_line_count))?;
+        let markup = markup.parse(
+            ParseContext::new(meta_line_count)
+                .with_repository(resources.repository)
+                .with_embed_cache(resources.embed_cache),
+        )?;
         let SeriesDirMetadata { id } = SeriesDirMetadata::from_path(&markup.path)?;
 
         let url =
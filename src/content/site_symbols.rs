@@ -0,0 +1,151 @@
+use chrono::NaiveDate;
+use eyre::{eyre, Result};
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use tracing::error;
+
+use crate::content::{CountedWordsPostItem, PostItem, Tag};
+use crate::markup::{PostStatsGraph, SymbolTransform};
+use crate::site::SiteContent;
+
+const DEFAULT_RECENT_POSTS: usize = 5;
+
+lazy_static! {
+    static ref SITE_SYMBOL: Regex = Regex::new(r"<!-- SITE_SYMBOL ([\w-]+) (\{.*?\}) -->").unwrap();
+}
+
+/// If the html has site aware symbols, and should be re-rendered when posts,
+/// tags or series change.
+pub fn has_site_symbols(html: &str) -> bool {
+    html.contains("<!-- SITE_SYMBOL ")
+}
+
+/// Replace the placeholders left by site aware symbol transforms,
+/// such as `:recent-posts:`, with content from the loaded site.
+pub fn render_site_symbols<'a>(html: &'a str, content: &SiteContent) -> Cow<'a, str> {
+    if !has_site_symbols(html) {
+        return Cow::Borrowed(html);
+    }
+
+    SITE_SYMBOL.replace_all(html, |caps: &Captures| {
+        match render_symbol(&caps[1], &caps[2], content) {
+            Ok(res) => res,
+            Err(err) => {
+                error!("site symbol error: {err}");
+                String::new()
+            }
+        }
+    })
+}
+
+fn render_symbol(symbol: &str, attrs: &str, content: &SiteContent) -> Result<String> {
+    let attrs: HashMap<String, String> = serde_json::from_str(attrs)?;
+
+    match SymbolTransform::parse(symbol) {
+        Some(SymbolTransform::RecentPosts) => {
+            let count = match attrs.get("count") {
+                Some(count) => count.parse()?,
+                None => DEFAULT_RECENT_POSTS,
+            };
+            Ok(post_list(
+                "recent-posts",
+                published_posts(content).take(count),
+            ))
+        }
+        Some(SymbolTransform::FavoritePosts) => Ok(post_list(
+            "favorite-posts",
+            published_posts(content).filter(|post| post.favorite),
+        )),
+        Some(SymbolTransform::TagCloud) => Ok(tag_cloud(content)),
+        Some(SymbolTransform::SeriesList) => Ok(series_list(content)),
+        Some(SymbolTransform::PostStatsGraph) => post_stats_graph(&attrs, content),
+        _ => Err(eyre!("Unknown site symbol `{symbol}`")),
+    }
+}
+
+/// Posts that aren't drafts, newest first.
+fn published_posts(content: &SiteContent) -> impl Iterator<Item = &PostItem> {
+    content.posts.values().filter(|post| !post.is_draft)
+}
+
+fn post_list<'a>(class: &str, posts: impl Iterator<Item = &'a PostItem>) -> String {
+    let mut res = format!(r#"<ul class="{class}">"#);
+    for post in posts {
+        res.push_str(&format!(
+            r#"<li><a href="{}">{}</a> <time datetime="{}">{}</time></li>"#,
+            post.url.href(),
+            html_escape::encode_text(&post.title),
+            post.created.format("%FT%T%.fZ"),
+            post.created.format("%F"),
+        ));
+    }
+    res.push_str("</ul>");
+    res
+}
+
+fn post_stats_graph(attrs: &HashMap<String, String>, content: &SiteContent) -> Result<String> {
+    let before_date = match attrs.get("before_date") {
+        Some(date) => Some(NaiveDate::parse_from_str(date, "%Y-%m-%d")?),
+        None => None,
+    };
+
+    let posts = published_posts(content)
+        .filter(|post| match before_date {
+            Some(date) => post.created.date() <= date,
+            None => true,
+        })
+        .map(CountedWordsPostItem::from_post)
+        .collect();
+
+    PostStatsGraph {
+        posts,
+        caption: attrs.get("caption").cloned(),
+    }
+    .generate()
+}
+
+fn tag_cloud(content: &SiteContent) -> String {
+    let mut counts: BTreeMap<&Tag, usize> = BTreeMap::new();
+    for post in published_posts(content) {
+        for tag in &post.tags {
+            *counts.entry(tag).or_default() += 1;
+        }
+    }
+    let max = counts.values().copied().max().unwrap_or(1);
+
+    let mut res = r#"<ul class="tag-cloud">"#.to_string();
+    for (tag, count) in counts {
+        res.push_str(&format!(
+            r#"<li class="weight-{}"><a href="{}">{}</a> <span class="count">{count}</span></li>"#,
+            1 + (count * 4) / max,
+            tag.url.href(),
+            html_escape::encode_text(&tag.name),
+        ));
+    }
+    res.push_str("</ul>");
+    res
+}
+
+fn series_list(content: &SiteContent) -> String {
+    let mut res = r#"<ul class="series-list">"#.to_string();
+    for series in content.series.values() {
+        let count = series
+            .posts
+            .iter()
+            .filter(|post| !post.0.order.is_draft)
+            .count();
+        if count == 0 {
+            continue;
+        }
+        res.push_str(&format!(
+            r#"<li><a href="{}">{}</a> <span class="count">{count} {}</span></li>"#,
+            series.url.href(),
+            html_escape::encode_text(&series.title),
+            if count == 1 { "post" } else { "posts" },
+        ));
+    }
+    res.push_str("</ul>");
+    res
+}
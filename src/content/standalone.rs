@@ -3,7 +3,8 @@ use serde::{Deserialize, Serialize};
 use std::{borrow::Cow, collections::HashSet};
 use tera::Context;
 
-use crate::context::LoadContext;
+use crate::content::render_site_symbols;
+use crate::context::{LoadContext, MarkupResources};
 use crate::git::{CommitContext, LatestCommitInfo};
 use crate::markup::{find_markup_files, Html, MarkupLookup, ParseContext, RawMarkupFile};
 use crate::{
@@ -13,10 +14,14 @@ use crate::{
     site_url::SiteUrl,
 };
 
-pub fn load_standalones(dir: AbsPath, context: &LoadContext) -> Result<HashSet<StandaloneItem>> {
+pub fn load_standalones(
+    dir: AbsPath,
+    context: &LoadContext,
+    resources: MarkupResources,
+) -> Result<HashSet<StandaloneItem>> {
     let mut res = HashSet::new();
     for path in find_markup_files(&context.opts.input_dir, &[dir]).into_iter() {
-        let item = StandaloneItem::from_file(&path, context)?;
+        let item = StandaloneItem::from_file(&path, context, resources)?;
         if !item.is_draft || context.opts.include_drafts {
             res.insert(item);
         }
@@ -50,19 +55,29 @@ impl std::hash::Hash for StandaloneItem {
 }
 
 impl StandaloneItem {
-    pub fn from_file(path: &FilePath, context: &LoadContext) -> Result<Self> {
+    pub fn from_file(
+        path: &FilePath,
+        context: &LoadContext,
+        resources: MarkupResources,
+    ) -> Result<Self> {
         let abs_path = path.abs_path();
         let markup = RawMarkupFile::from_file(abs_path)?;
         let latest_commit = context.get_commit(path).cloned();
-        Self::from_markup(markup, latest_commit)
+        Self::from_markup(markup, latest_commit, resources)
     }
 
     pub fn from_markup(
         markup: RawMarkupFile<StandaloneMetadata>,
         latest_commit: Option<LatestCommitInfo>,
+        resources: MarkupResources,
     ) -> Result<Self> {
         let meta_line_count = markup.meta_line_count;
-        let markup = markup.parse(ParseContext::new(meta_line_count))?;
+        let markup = markup.parse(
+            ParseContext::new(meta_line_count)
+                .with_templates(resources.templates)
+                .with_repository(resources.repository)
+                .with_embed_cache(resources.embed_cache),
+        )?;
         let slug = markup
             .path
             .file_stem()
@@ -84,11 +99,11 @@ impl StandaloneItem {
 }
 
 impl TeraItem for StandaloneItem {
-    fn context(&self, _ctx: &RenderContext) -> Context {
+    fn context(&self, ctx: &RenderContext) -> Context {
         Context::from_serialize(StandaloneContext {
             title: html_escape::encode_text(&self.title),
             latest_commit: self.latest_commit.as_ref().map(Into::into),
-            content: &self.content.0,
+            content: render_site_symbols(&self.content.0, ctx.content),
         })
         .unwrap()
     }
@@ -110,7 +125,7 @@ impl TeraItem for StandaloneItem {
 struct StandaloneContext<'a> {
     title: Cow<'a, str>,
     latest_commit: Option<CommitContext>,
-    content: &'a str,
+    content: Cow<'a, str>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -1,6 +1,6 @@
 use crate::{
-    content::ArchiveItem, content::PostRef, context::RenderContext, item::TeraItem, paths::AbsPath,
-    site_url::SiteUrl, util,
+    content::ArchiveItem, content::NoteRef, content::PostRef, context::RenderContext,
+    item::TeraItem, paths::AbsPath, site_url::SiteUrl, util,
 };
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
@@ -9,17 +9,26 @@ use std::cmp::Ordering;
 use std::collections::HashMap;
 use tera::Context;
 
-pub fn tags_archives(tags: &HashMap<Tag, Vec<PostRef>>) -> Vec<ArchiveItem> {
+pub fn tags_archives(tags: &HashMap<Tag, TaggedItems>) -> Vec<ArchiveItem> {
     tags.iter()
-        .map(|(tag, posts)| ArchiveItem {
+        .filter(|(_, items)| !items.posts.is_empty())
+        .map(|(tag, items)| ArchiveItem {
             title: tag.name.clone(),
             url: tag.url.clone(),
-            posts: posts.to_vec(),
+            posts: items.posts.to_vec(),
             tag_filter: Some(tag.name.clone()),
         })
         .collect()
 }
 
+/// Everything with a tag.
+#[derive(Debug, Default, Clone)]
+pub struct TaggedItems {
+    pub posts: Vec<PostRef>,
+    /// Notes are kept out of the tag archives.
+    pub notes: Vec<NoteRef>,
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct Tag {
     pub id: String,
@@ -92,12 +101,12 @@ impl From<TagsMeta> for Vec<Tag> {
 
 #[derive(Debug)]
 pub struct TagListItem<'a> {
-    pub tags: &'a HashMap<Tag, Vec<PostRef>>,
+    pub tags: &'a HashMap<Tag, TaggedItems>,
     pub url: SiteUrl,
 }
 
 impl<'a> TagListItem<'a> {
-    pub fn new(tags: &'a HashMap<Tag, Vec<PostRef>>) -> Self {
+    pub fn new(tags: &'a HashMap<Tag, TaggedItems>) -> Self {
         Self {
             tags,
             url: SiteUrl::parse("/blog/tags").unwrap(),
@@ -110,10 +119,11 @@ impl TeraItem for TagListItem<'_> {
         let mut tags: Vec<TagContext<'_>> = self
             .tags
             .iter()
-            .map(|(tag, posts)| TagContext {
+            .filter(|(_, items)| !items.posts.is_empty())
+            .map(|(tag, items)| TagContext {
                 name: &tag.name,
                 href: tag.url.href(),
-                num_posts: posts.len(),
+                num_posts: items.posts.len(),
             })
             .collect();
         tags.sort();
@@ -0,0 +1,220 @@
+use chrono::{Datelike, NaiveDate};
+use eyre::Result;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use tera::Context;
+
+use super::archive::posts_by_year;
+use super::posts::{PostRef, PostRefContext};
+use crate::content::{CountedWordsPostItem, PostItem, SeriesItem, SeriesRef, Tag};
+use crate::context::RenderContext;
+use crate::item::TeraItem;
+use crate::markup::PostsGraph;
+use crate::paths::AbsPath;
+use crate::site_url::SiteUrl;
+
+const TOP_TAGS_COUNT: usize = 5;
+
+/// Create the reviews of all years, or only of `years` if given.
+pub fn year_reviews(
+    posts: &BTreeMap<PostRef, PostItem>,
+    series: &BTreeMap<SeriesRef, SeriesItem>,
+    years: Option<&[i32]>,
+) -> Result<Vec<YearReviewItem>> {
+    let post_refs: Vec<PostRef> = posts
+        .iter()
+        .filter(|(_, post)| !post.is_draft)
+        .map(|(post_ref, _)| post_ref.clone())
+        .collect();
+
+    posts_by_year(&post_refs)
+        .into_iter()
+        .filter(|(year, _)| years.map_or(true, |years| years.contains(year)))
+        .map(|(year, post_refs)| YearReviewItem::new(year, post_refs, posts, series))
+        .collect()
+}
+
+#[derive(Debug)]
+pub struct YearReviewItem {
+    pub year: i32,
+    pub url: SiteUrl,
+    pub posts: Vec<PostRef>,
+    pub favorites: Vec<PostRef>,
+    pub total_words: usize,
+    pub top_tags: Vec<(Tag, usize)>,
+    pub series_started: Vec<SeriesRef>,
+    pub series_completed: Vec<SeriesRef>,
+    pub graph: String,
+}
+
+impl YearReviewItem {
+    fn new(
+        year: i32,
+        post_refs: Vec<PostRef>,
+        posts: &BTreeMap<PostRef, PostItem>,
+        series: &BTreeMap<SeriesRef, SeriesItem>,
+    ) -> Result<Self> {
+        let year_posts: Vec<&PostItem> = post_refs
+            .iter()
+            .map(|post_ref| posts.get(post_ref).expect("Should have post"))
+            .collect();
+
+        let counted: Vec<CountedWordsPostItem> = year_posts
+            .iter()
+            .map(|post| CountedWordsPostItem::from_post(post))
+            .collect();
+        let total_words = counted.iter().map(|post| post.word_count).sum();
+
+        let mut tag_counts: HashMap<&Tag, usize> = HashMap::new();
+        for post in year_posts.iter() {
+            for tag in post.tags.iter() {
+                *tag_counts.entry(tag).or_default() += 1;
+            }
+        }
+        let mut top_tags: Vec<(Tag, usize)> = tag_counts
+            .into_iter()
+            .map(|(tag, count)| (tag.clone(), count))
+            .collect();
+        top_tags.sort_by(|(a_tag, a_count), (b_tag, b_count)| {
+            b_count.cmp(a_count).then(a_tag.cmp(b_tag))
+        });
+        top_tags.truncate(TOP_TAGS_COUNT);
+
+        let favorites = post_refs
+            .iter()
+            .filter(|post_ref| posts.get(post_ref).is_some_and(|post| post.favorite))
+            .cloned()
+            .collect();
+
+        let mut series_started = Vec::new();
+        let mut series_completed = Vec::new();
+        for (series_ref, item) in series.iter() {
+            let mut published = item
+                .posts
+                .iter()
+                .map(|post_ref| &post_ref.0)
+                .filter(|post_ref| !post_ref.order.is_draft);
+            let first = published.next();
+            let last = published.last().or(first);
+
+            if first.is_some_and(|post_ref| post_ref.order.created.year() == year) {
+                series_started.push(series_ref.clone());
+            }
+            if item.completed && last.is_some_and(|post_ref| post_ref.order.created.year() == year)
+            {
+                series_completed.push(series_ref.clone());
+            }
+        }
+
+        let graph = PostsGraph {
+            posts: counted,
+            date_range: (
+                NaiveDate::from_ymd_opt(year, 1, 1).unwrap(),
+                NaiveDate::from_ymd_opt(year, 12, 31).unwrap(),
+            ),
+            caption: None,
+        }
+        .generate()?;
+
+        Ok(Self {
+            year,
+            url: SiteUrl::parse(&format!("/blog/{year}/review")).unwrap(),
+            posts: post_refs,
+            favorites,
+            total_words,
+            top_tags,
+            series_started,
+            series_completed,
+            graph,
+        })
+    }
+}
+
+impl TeraItem for YearReviewItem {
+    fn context(&self, ctx: &RenderContext) -> Context {
+        Context::from_serialize(YearReviewContext {
+            title: format!("{} in review", self.year),
+            year: self.year,
+            posts: post_contexts(&self.posts, ctx),
+            favorites: post_contexts(&self.favorites, ctx),
+            total_words: self.total_words,
+            top_tags: self
+                .top_tags
+                .iter()
+                .map(|(tag, count)| TagCountContext {
+                    name: &tag.name,
+                    href: tag.url.href(),
+                    count: *count,
+                })
+                .collect(),
+            series_started: series_contexts(&self.series_started, ctx),
+            series_completed: series_contexts(&self.series_completed, ctx),
+            graph: &self.graph,
+        })
+        .unwrap()
+    }
+
+    fn template(&self) -> &str {
+        "year_review.html"
+    }
+
+    fn tera_url(&self) -> &SiteUrl {
+        &self.url
+    }
+
+    fn tera_source_file(&self) -> Option<&AbsPath> {
+        None
+    }
+}
+
+fn post_contexts<'a>(refs: &[PostRef], ctx: &'a RenderContext) -> Vec<PostRefContext<'a>> {
+    refs.iter()
+        .map(|post_ref| PostRefContext::from_ref(post_ref, ctx))
+        .collect()
+}
+
+fn series_contexts<'a>(refs: &[SeriesRef], ctx: &'a RenderContext) -> Vec<SeriesRefContext<'a>> {
+    refs.iter()
+        .map(|series_ref| SeriesRefContext::from_ref(series_ref, ctx))
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct YearReviewContext<'a> {
+    title: String,
+    year: i32,
+    posts: Vec<PostRefContext<'a>>,
+    favorites: Vec<PostRefContext<'a>>,
+    total_words: usize,
+    top_tags: Vec<TagCountContext<'a>>,
+    series_started: Vec<SeriesRefContext<'a>>,
+    series_completed: Vec<SeriesRefContext<'a>>,
+    graph: &'a str,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct TagCountContext<'a> {
+    name: &'a str,
+    href: Cow<'a, str>,
+    count: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct SeriesRefContext<'a> {
+    title: Cow<'a, str>,
+    url: Cow<'a, str>,
+}
+
+impl<'a> SeriesRefContext<'a> {
+    fn from_ref(series_ref: &SeriesRef, ctx: &'a RenderContext) -> Self {
+        let series = ctx
+            .content
+            .get_series(series_ref)
+            .expect("Should have series");
+        Self {
+            title: html_escape::encode_text(&series.title),
+            url: series.url.href(),
+        }
+    }
+}
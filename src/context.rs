@@ -1,5 +1,6 @@
+use crate::markup::{Bibliography, EmbedCache, Glossary, ProseLint, SpellChecker};
 use crate::site::{SiteContent, SiteOptions};
-use crate::{git::LatestCommitInfo, git::LatestCommits, paths::FilePath};
+use crate::{git::LatestCommitInfo, git::LatestCommits, git::SiteRepository, paths::FilePath};
 
 use camino::Utf8Path;
 use tera::{Context, Tera};
@@ -7,6 +8,8 @@ use tera::{Context, Tera};
 pub struct LoadContext<'a> {
     pub opts: &'a SiteOptions,
     pub latest_commits: &'a LatestCommits,
+    pub templates: &'a Tera,
+    pub repository: &'a SiteRepository,
 }
 
 impl<'a> LoadContext<'a> {
@@ -15,9 +18,22 @@ impl<'a> LoadContext<'a> {
     }
 }
 
+/// Site wide resources used when parsing markup.
+#[derive(Clone, Copy)]
+pub struct MarkupResources<'a> {
+    pub bibliography: &'a Bibliography,
+    pub glossary: &'a Glossary,
+    pub spelling: &'a SpellChecker,
+    pub prose_lint: &'a ProseLint,
+    pub embed_cache: &'a EmbedCache,
+    pub templates: &'a Tera,
+    pub repository: &'a SiteRepository,
+}
+
 pub struct RenderContext<'a> {
     pub output_dir: &'a Utf8Path,
     pub parent_context: &'a Context,
     pub content: &'a SiteContent,
     pub tera: &'a Tera,
+    pub repository: &'a SiteRepository,
 }
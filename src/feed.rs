@@ -3,13 +3,14 @@ use atom_syndication::Entry;
 use atom_syndication::Feed;
 use atom_syndication::Link;
 use atom_syndication::Person;
+use atom_syndication::Text;
 use chrono::DateTime;
 use chrono::FixedOffset;
 use chrono::NaiveDateTime;
 use eyre::{eyre, Result};
 use tracing::debug;
 
-use crate::content::PostItem;
+use crate::content::{BookmarkItem, NoteItem, PostItem};
 use crate::context::RenderContext;
 use crate::item::Item;
 use crate::markup::ParseContext;
@@ -21,39 +22,153 @@ use lazy_static::lazy_static;
 
 lazy_static! {
     static ref FEED_URL: SiteUrl = SiteUrl::parse("feed.xml").unwrap();
+    static ref NOTES_FEED_URL: SiteUrl = SiteUrl::parse("notes/feed.xml").unwrap();
 }
 
 #[derive(Debug)]
 pub struct SiteFeed;
 
-impl From<&PostItem> for Entry {
-    fn from(post: &PostItem) -> Self {
-        Self {
-            title: post.title.clone().into(),
-            id: entity_id(&post.url),
-            updated: fixed_date_time(post.modified),
-            published: Some(fixed_date_time(post.created)),
-            links: vec![Link {
+#[derive(Debug)]
+pub struct NotesFeed;
+
+fn post_entry(post: &PostItem, ctx: &RenderContext) -> Entry {
+    // Posts with a changelog are only updated by significant revisions in the changelog,
+    // not by every small fix.
+    let latest_change = post
+        .changelog
+        .as_ref()
+        .and_then(|changelog| changelog.latest());
+    let updated = match latest_change {
+        Some(change) => change.date.and_hms_opt(0, 0, 0).unwrap().max(post.created),
+        None => post.modified,
+    };
+    let summary = latest_change
+        .map(|change| util::html_text(&change.description).trim().to_string())
+        .filter(|description| !description.is_empty())
+        .map(|description| Text::plain(format!("Updated: {description}")));
+
+    Entry {
+        title: post.title.clone().into(),
+        id: entity_id(&post.url),
+        updated: fixed_date_time(updated),
+        summary,
+        published: Some(fixed_date_time(post.created)),
+        links: vec![Link {
+            href: BASE_SITE_URL
+                .join(post.url.href().as_ref())
+                .expect("Should be able to join url")
+                .to_string(),
+            rel: "alternate".to_string(),
+            ..Default::default()
+        }],
+        content: Some(Content {
+            base: Some(BASE_SITE_URL.to_string()),
+            content_type: Some("html".to_string()),
+            value: Some(
+                post.markup
+                    .parse_feed(
+                        ParseContext::default()
+                            .with_bibliography(&ctx.content.bibliography)
+                            .with_glossary(&ctx.content.glossary)
+                            .with_templates(ctx.tera)
+                            .with_repository(ctx.repository)
+                            .with_embed_cache(&ctx.content.embed_cache)
+                            .citation_style(post.citation_style),
+                    )
+                    .expect("Should be able to generate feed markup")
+                    .0,
+            ),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+fn note_entry(note: &NoteItem, ctx: &RenderContext) -> Entry {
+    Entry {
+        title: note.title().into(),
+        id: entity_id(&note.url),
+        updated: fixed_date_time(note.created),
+        published: Some(fixed_date_time(note.created)),
+        links: vec![Link {
+            href: BASE_SITE_URL
+                .join(note.url.href().as_ref())
+                .expect("Should be able to join url")
+                .to_string(),
+            rel: "alternate".to_string(),
+            ..Default::default()
+        }],
+        content: Some(Content {
+            base: Some(BASE_SITE_URL.to_string()),
+            content_type: Some("html".to_string()),
+            value: Some(
+                note.markup
+                    .parse_feed(
+                        ParseContext::default()
+                            .with_templates(ctx.tera)
+                            .with_repository(ctx.repository)
+                            .with_embed_cache(&ctx.content.embed_cache),
+                    )
+                    .expect("Should be able to generate feed markup")
+                    .0,
+            ),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+fn bookmark_entry(bookmark: &BookmarkItem, ctx: &RenderContext) -> Entry {
+    Entry {
+        title: bookmark.title.clone().into(),
+        // The url points to a fragment in the archive, which doesn't make for a good id.
+        id: entity_id(
+            &SiteUrl::parse(&format!("/links/{}", bookmark.id))
+                .expect("Should be able to create a url"),
+        ),
+        updated: fixed_date_time(bookmark.created),
+        published: Some(fixed_date_time(bookmark.created)),
+        links: vec![
+            Link {
                 href: BASE_SITE_URL
-                    .join(post.url.href().as_ref())
+                    .join(bookmark.url.href().as_ref())
                     .expect("Should be able to join url")
                     .to_string(),
                 rel: "alternate".to_string(),
                 ..Default::default()
-            }],
-            content: Some(Content {
-                base: Some(BASE_SITE_URL.to_string()),
-                content_type: Some("html".to_string()),
-                value: Some(
-                    post.markup
-                        .parse_feed(ParseContext::default())
-                        .expect("Should be able to generate feed markup")
-                        .0,
-                ),
+            },
+            Link {
+                href: bookmark.link.to_string(),
+                rel: "related".to_string(),
                 ..Default::default()
-            }),
+            },
+        ],
+        content: Some(Content {
+            base: Some(BASE_SITE_URL.to_string()),
+            content_type: Some("html".to_string()),
+            value: Some(
+                bookmark
+                    .markup
+                    .parse_feed(
+                        ParseContext::default()
+                            .with_templates(ctx.tera)
+                            .with_repository(ctx.repository)
+                            .with_embed_cache(&ctx.content.embed_cache),
+                    )
+                    .expect("Should be able to generate feed markup")
+                    .0,
+            ),
             ..Default::default()
-        }
+        }),
+        ..Default::default()
+    }
+}
+
+fn author() -> Person {
+    Person {
+        name: "Jonas Hietala".to_string(),
+        email: Some("mail@jonashietala.se".to_string()),
+        uri: Some(BASE_SITE_URL.to_string()),
     }
 }
 
@@ -81,6 +196,26 @@ impl Item for SiteFeed {
 
         let file = util::create_file(&output_file)?;
 
+        let mut entries: Vec<Entry> = ctx
+            .content
+            .posts
+            .values()
+            .map(|post| post_entry(post, ctx))
+            .chain(
+                ctx.content
+                    .bookmarks
+                    .values()
+                    .filter(|bookmark| bookmark.in_feed)
+                    .map(|bookmark| bookmark_entry(bookmark, ctx)),
+            )
+            .collect();
+        // Bookmarks are interleaved with posts, newest first.
+        entries.sort_by(|a, b| {
+            b.published
+                .unwrap_or(b.updated)
+                .cmp(&a.published.unwrap_or(a.updated))
+        });
+
         let feed = Feed {
             title: "Jonas Hietala".into(),
             id: "http://www.jonashietala.se/feed.xml".to_string(),
@@ -96,20 +231,24 @@ impl Item for SiteFeed {
                     ..Default::default()
                 },
             ],
-            authors: vec![Person {
-                name: "Jonas Hietala".to_string(),
-                email: Some("mail@jonashietala.se".to_string()),
-                uri: Some(BASE_SITE_URL.to_string()),
-            }],
+            authors: vec![author()],
             base: Some(BASE_SITE_URL.to_string()),
             updated: ctx
                 .content
                 .posts
                 .values()
-                .max_by_key(|post| post.modified)
-                .map(|post| fixed_date_time(post.modified))
+                .map(|post| post.modified)
+                .chain(
+                    ctx.content
+                        .bookmarks
+                        .values()
+                        .filter(|bookmark| bookmark.in_feed)
+                        .map(|bookmark| bookmark.created),
+                )
+                .max()
+                .map(fixed_date_time)
                 .unwrap(),
-            entries: ctx.content.posts.values().map(Entry::from).collect(),
+            entries,
             ..Default::default()
         };
 
@@ -128,6 +267,62 @@ impl Item for SiteFeed {
     }
 }
 
+impl Item for NotesFeed {
+    fn render(&self, ctx: &RenderContext) -> Result<()> {
+        let output_file = ctx.output_dir.join("notes/feed.xml");
+        debug!("Writing {output_file}");
+
+        let file = util::create_file(&output_file)?;
+
+        let feed = Feed {
+            title: "Jonas Hietala: Notes".into(),
+            id: "http://www.jonashietala.se/notes/feed.xml".to_string(),
+            links: vec![
+                Link {
+                    href: BASE_SITE_URL.join("notes/feed.xml").unwrap().to_string(),
+                    rel: "self".to_string(),
+                    ..Default::default()
+                },
+                Link {
+                    href: BASE_SITE_URL.join("notes").unwrap().to_string(),
+                    rel: "alternate".to_string(),
+                    ..Default::default()
+                },
+            ],
+            authors: vec![author()],
+            base: Some(BASE_SITE_URL.to_string()),
+            updated: ctx
+                .content
+                .notes
+                .values()
+                .map(|note| note.created)
+                .max()
+                .map(fixed_date_time)
+                .unwrap_or_else(|| fixed_date_time(NaiveDateTime::default())),
+            entries: ctx
+                .content
+                .notes
+                .values()
+                .map(|note| note_entry(note, ctx))
+                .collect(),
+            ..Default::default()
+        };
+
+        feed.write_to(file)
+            .map_err(|err| eyre!("Error writing file {output_file}: {err}"))?;
+
+        Ok(())
+    }
+
+    fn url(&self) -> &SiteUrl {
+        &NOTES_FEED_URL
+    }
+
+    fn source_file(&self) -> Option<&AbsPath> {
+        None
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -188,12 +383,82 @@ mod tests {
                 "http://jonashietala.se/blog/2022/01/31/test_post/index.html",
                 "http://jonashietala.se/blog/2022/02/01/feb_post/index.html",
                 "http://jonashietala.se/blog/2022/02/02/feb_post2/index.html",
-                "http://jonashietala.se/blog/2022/02/02/feb_post_dupe/index.html"
+                "http://jonashietala.se/blog/2022/02/02/feb_post_dupe/index.html",
+                "http://jonashietala.se/links/djot_syntax/index.html",
             ]
         );
 
         assert!(!feed.contains("table-of-content"));
 
+        // Entries are sorted by date, newest first, with bookmarks mixed in.
+        let xpath = Factory::new()
+            .build("/a:feed/a:entry/a:id")
+            .unwrap()
+            .unwrap();
+        let ordered = match xpath.evaluate(&context(), document.root())? {
+            Value::Nodeset(nodes) => nodes.document_order(),
+            _ => panic!("Expected entry ids"),
+        };
+        assert_eq!(
+            ordered.first().map(|node| node.string_value()),
+            Some("http://jonashietala.se/links/djot_syntax/index.html".to_string())
+        );
+        assert_eq!(
+            ordered.last().map(|node| node.string_value()),
+            Some("http://jonashietala.se/blog/2022/01/31/test_post/index.html".to_string())
+        );
+
+        let related = collect_strings(&document, "/a:feed/a:entry/a:link[@rel='related']/@href")?;
+        assert_eq!(
+            related,
+            vec!["https://htmlpreview.github.io/?https://github.com/jgm/djot/blob/master/doc/syntax.html"]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_feed_changelog() -> Result<()> {
+        let mut test_site = TestSiteBuilder {
+            include_drafts: false,
+        }
+        .build()?;
+
+        test_site.create_file(
+            "posts/2023-01-31-revised.dj",
+            r#"---toml
+title = "Revised"
+tags = ["One"]
+---
+
+Content
+
+::: changelog
+: 2023-02-05
+
+  Fixed a typo.
+
+: 2023-03-10
+
+  Rewrote the *conclusion*.
+:::
+"#,
+        )?;
+
+        let feed = test_site.output_content("feed.xml")?;
+        let package = parser::parse(&feed)?;
+        let document = package.as_document();
+
+        let entry = "/a:feed/a:entry[a:title='Revised']";
+        assert_eq!(
+            collect_string(&document, &format!("{entry}/a:updated"))?,
+            "2023-03-10T00:00:00+00:00"
+        );
+        assert_eq!(
+            collect_string(&document, &format!("{entry}/a:summary"))?,
+            "Updated: Rewrote the conclusion."
+        );
+
         Ok(())
     }
 
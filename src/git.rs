@@ -1,11 +1,14 @@
 use crate::{markup::MarkupType, paths::FilePath};
 use camino::{Utf8Path, Utf8PathBuf};
 use chrono::{DateTime, NaiveDateTime};
-use eyre::Result;
+use eyre::{eyre, Result};
 use git2::{Oid, Repository};
 use lazy_static::lazy_static;
 use serde::Serialize;
 use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
 
 lazy_static! {
     pub static ref IGNORED_COMMITS: HashSet<&'static str> = [
@@ -137,6 +140,77 @@ impl LatestCommits {
     }
 }
 
+/// Read a file as it was in a specific revision,
+/// returns the content and the short id of the commit.
+fn read_file_at_revision(
+    repo: &Repository,
+    rev: &str,
+    path: &Utf8Path,
+) -> Result<(String, String)> {
+    let commit = repo
+        .revparse_single(rev)
+        .and_then(|obj| obj.peel_to_commit())
+        .map_err(|err| eyre!("Couldn't find commit `{rev}`: {}", err.message()))?;
+    let entry = commit
+        .tree()?
+        .get_path(path.as_std_path())
+        .map_err(|_| eyre!("`{path}` doesn't exist in commit `{rev}`"))?;
+    let blob = entry.to_object(repo)?.peel_to_blob()?;
+    let content = String::from_utf8(blob.content().to_vec())?;
+    let short_id = commit.id().to_string().chars().take(7).collect();
+    Ok((content, short_id))
+}
+
+/// The repository the site is in.
+///
+/// Content is loaded in parallel, so the repository is kept behind a lock.
+pub struct SiteRepository {
+    repo: Mutex<Repository>,
+    /// Where the site is inside the repository.
+    path_offset: Option<Utf8PathBuf>,
+}
+
+impl SiteRepository {
+    pub fn open(path: impl AsRef<Path>, path_offset: Option<&Utf8Path>) -> Result<Self> {
+        Ok(Self::new(Repository::open(path)?, path_offset))
+    }
+
+    pub fn new(repo: Repository, path_offset: Option<&Utf8Path>) -> Self {
+        Self {
+            repo: Mutex::new(repo),
+            path_offset: path_offset.map(|offset| offset.to_owned()),
+        }
+    }
+
+    pub fn latest_commits(&self) -> Result<LatestCommits> {
+        LatestCommits::new(&self.repo()?, self.path_offset.as_deref())
+    }
+
+    /// Read a file relative to the site as it was in a specific revision,
+    /// returns the content and the short id of the commit.
+    pub fn read_file_at_revision(&self, rev: &str, path: &Utf8Path) -> Result<(String, String)> {
+        let path = match &self.path_offset {
+            Some(offset) => offset.join(path),
+            None => path.to_owned(),
+        };
+        read_file_at_revision(&self.repo()?, rev, &path)
+    }
+
+    fn repo(&self) -> Result<MutexGuard<'_, Repository>> {
+        self.repo
+            .lock()
+            .map_err(|_| eyre!("Git repository lock poisoned"))
+    }
+}
+
+impl fmt::Debug for SiteRepository {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SiteRepository")
+            .field("path_offset", &self.path_offset)
+            .finish()
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct CommitContext {
     id: String,
@@ -162,6 +236,7 @@ mod tests {
     use chrono::{NaiveDate, NaiveTime};
 
     use super::*;
+    use crate::tests::git_fixture;
 
     #[test]
     fn test_git_info() -> Result<()> {
@@ -181,4 +256,27 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_read_file_at_revision() -> Result<()> {
+        let (_dir, repo) = git_fixture(&[("site/notes.txt", "First line\nSecond line\n")])?;
+        let head = repo.head()?.peel_to_commit()?.id().to_string();
+        let repo = SiteRepository::new(repo, Some(Utf8Path::new("site/")));
+
+        let (content, short_id) = repo.read_file_at_revision("HEAD", Utf8Path::new("notes.txt"))?;
+        assert_eq!(content, "First line\nSecond line\n");
+        assert_eq!(short_id, head[..7]);
+
+        assert!(repo
+            .read_file_at_revision("HEAD", Utf8Path::new("site/notes.txt"))
+            .is_err());
+        assert!(repo
+            .read_file_at_revision("HEAD", Utf8Path::new("does/not/exist"))
+            .is_err());
+        assert!(repo
+            .read_file_at_revision("0000000000000000", Utf8Path::new("notes.txt"))
+            .is_err());
+
+        Ok(())
+    }
 }
@@ -20,7 +20,7 @@ use crate::site_url::{HrefUrl, ImgUrl};
 use camino::Utf8PathBuf;
 use clap::{Parser, Subcommand};
 use colored::Colorize;
-use eyre::Result;
+use eyre::{eyre, Result};
 use futures::future::join_all;
 use lazy_static::lazy_static;
 use paths::AbsPath;
@@ -66,17 +66,25 @@ enum Commands {
     DumpTheme { file: Utf8PathBuf },
     /// Check external links
     CheckExternalLinks,
+    /// Render drafts to unguessable urls and sync them for review
+    PreviewDrafts,
+    /// Report unused glossary entries and terms missing from the glossary
+    CheckGlossary,
+    /// Report accessibility issues in posts, such as images without alt text
+    Check,
 }
 
 lazy_static! {
     static ref CURRENT_DIR: AbsPath = AbsPath::current_dir().unwrap();
     static ref OUTPUT_DIR: AbsPath = CURRENT_DIR.join(".output");
     static ref FILE_DIR: AbsPath = CURRENT_DIR.join("files");
+    static ref PREVIEW_DIR: AbsPath = CURRENT_DIR.join(".preview");
 }
 
 static SITE_BUCKET: &str = "www.jonashietala.se";
 static FILE_BUCKET: &str = "jonashietala-files";
 static REGION: Region = Region::EuWest1;
+static PREVIEW_PREFIX: &str = "preview/";
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -114,6 +122,8 @@ async fn main() -> Result<()> {
             upload::sync(SyncOpts {
                 dir: &OUTPUT_DIR,
                 bucket: site_bucket()?,
+                remote_prefix: "",
+                keep_remote: Some(PREVIEW_PREFIX),
                 delete: true,
                 print_urls: false,
             })
@@ -123,6 +133,8 @@ async fn main() -> Result<()> {
             upload::sync(SyncOpts {
                 dir: &FILE_DIR,
                 bucket: file_bucket()?,
+                remote_prefix: "",
+                keep_remote: None,
                 delete: false,
                 print_urls: true,
             })
@@ -140,6 +152,15 @@ async fn main() -> Result<()> {
         Commands::CheckExternalLinks => {
             check_external_links().await?;
         }
+        Commands::PreviewDrafts => {
+            preview_drafts().await?;
+        }
+        Commands::CheckGlossary => {
+            check_glossary()?;
+        }
+        Commands::Check => {
+            check()?;
+        }
     }
 
     Ok(())
@@ -157,7 +178,7 @@ fn file_bucket() -> Result<Bucket> {
     Ok(bucket)
 }
 
-fn build() -> Result<()> {
+fn build() -> Result<Site> {
     let site = Site::load_content(SiteOptions {
         output_dir: OUTPUT_DIR.clone(),
         input_dir: CURRENT_DIR.clone(),
@@ -170,11 +191,44 @@ fn build() -> Result<()> {
 
     site.render_all()?;
 
+    Ok(site)
+}
+
+async fn preview_drafts() -> Result<()> {
+    let secret = std::env::var("PREVIEW_SECRET")
+        .map_err(|_| eyre!("`PREVIEW_SECRET` must be set to generate draft preview urls"))?;
+
+    let mut site = Site::load_content(SiteOptions {
+        output_dir: PREVIEW_DIR.clone(),
+        input_dir: CURRENT_DIR.clone(),
+        clear_output_dir: true,
+        include_drafts: true,
+        generate_feed: false,
+        include_js: false,
+        git_path_offset: None,
+    })?;
+
+    let previews = site.render_draft_previews(&secret)?;
+
+    upload::sync(SyncOpts {
+        dir: &PREVIEW_DIR,
+        bucket: site_bucket()?,
+        remote_prefix: PREVIEW_PREFIX,
+        keep_remote: None,
+        delete: true,
+        print_urls: false,
+    })
+    .await?;
+
+    for (path, url) in previews {
+        println!("{} {}", path.as_str().green(), url.url);
+    }
+
     Ok(())
 }
 
 async fn check_external_links() -> Result<()> {
-    build()?;
+    let site = build()?;
     let files = parse_html_files(&OUTPUT_DIR)?;
 
     let mut links = HashSet::new();
@@ -194,6 +248,10 @@ async fn check_external_links() -> Result<()> {
         }
     }
 
+    for bookmark in site.content.bookmarks.values() {
+        links.insert(&bookmark.link);
+    }
+
     let client = Client::new();
     let mut requests = Vec::new();
     for link in links {
@@ -205,6 +263,59 @@ async fn check_external_links() -> Result<()> {
     Ok(())
 }
 
+fn check_glossary() -> Result<()> {
+    let site = Site::load_content(SiteOptions {
+        output_dir: OUTPUT_DIR.clone(),
+        input_dir: CURRENT_DIR.clone(),
+        clear_output_dir: false,
+        include_drafts: true,
+        generate_feed: false,
+        include_js: false,
+        git_path_offset: None,
+    })?;
+
+    let (unused, undefined) = site.content.glossary_usage();
+    for term in unused {
+        println!("{} `{term}`", "Unused".yellow());
+    }
+    for (term, path) in undefined {
+        println!("{} `{term}` in {path}", "Undefined".red());
+    }
+
+    Ok(())
+}
+
+fn check() -> Result<()> {
+    let site = Site::load_content(SiteOptions {
+        output_dir: OUTPUT_DIR.clone(),
+        input_dir: CURRENT_DIR.clone(),
+        clear_output_dir: false,
+        include_drafts: true,
+        generate_feed: false,
+        include_js: false,
+        git_path_offset: None,
+    })?;
+
+    for post in site.content.posts.values() {
+        let lookup = match &post.markup_lookup {
+            Some(lookup) => lookup,
+            None => continue,
+        };
+        for diagnostic in server::diagnostics::accessibility_diagnostics(lookup) {
+            println!(
+                "{} {}:{}:{} {}",
+                "Warning".yellow(),
+                post.path,
+                diagnostic.lnum + 1,
+                diagnostic.col + 1,
+                diagnostic.message
+            );
+        }
+    }
+
+    Ok(())
+}
+
 async fn check_external_link(client: &Client, url: &Url) {
     match client.get(url.as_str()).send().await {
         Ok(response) => {
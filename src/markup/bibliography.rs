@@ -0,0 +1,225 @@
+use biblatex::{ChunksExt, Person};
+use eyre::{eyre, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+
+use crate::paths::AbsPath;
+
+/// References parsed from a BibTeX file, used to resolve `[@key]` citations.
+#[derive(Debug, Default)]
+pub struct Bibliography {
+    pub entries: BTreeMap<String, BibEntry>,
+}
+
+impl Bibliography {
+    /// Load the bibliography if it exists, otherwise return an empty one.
+    pub fn load(path: &AbsPath) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)?;
+        Self::parse(&content).map_err(|err| eyre!("Failed to parse {path}: {err}"))
+    }
+
+    pub fn parse(s: &str) -> Result<Self> {
+        let bib = biblatex::Bibliography::parse(s).map_err(|err| eyre!("{err}"))?;
+        let entries = bib
+            .iter()
+            .map(|entry| (entry.key.clone(), BibEntry::from_entry(entry)))
+            .collect();
+        Ok(Self { entries })
+    }
+
+    pub fn get(&self, key: &str) -> Option<&BibEntry> {
+        self.entries.get(key)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BibEntry {
+    pub key: String,
+    pub kind: String,
+    pub authors: Vec<Author>,
+    pub title: Option<String>,
+    pub year: Option<String>,
+    /// Where it was published, the journal, conference or publisher.
+    pub venue: Option<String>,
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Author {
+    pub first: Option<String>,
+    pub last: String,
+}
+
+impl BibEntry {
+    fn from_entry(entry: &biblatex::Entry) -> Self {
+        let field = |name: &str| entry.get(name).map(|chunks| chunks.format_verbatim());
+        let authors = match entry.author() {
+            Ok(authors) => authors,
+            Err(_) => entry
+                .editors()
+                .map(|editors| editors.into_iter().flat_map(|(people, _)| people).collect())
+                .unwrap_or_default(),
+        };
+        let venue = ["journal", "booktitle", "publisher", "howpublished"]
+            .iter()
+            .find_map(|name| field(name));
+
+        Self {
+            key: entry.key.clone(),
+            kind: entry.entry_type.to_string().to_lowercase(),
+            authors: authors.iter().map(Author::from_person).collect(),
+            title: field("title"),
+            year: field("year"),
+            venue,
+            url: field("url"),
+        }
+    }
+
+    /// Short author label for in text citations, such as "Knuth" or "Kernighan et al.".
+    pub fn short_authors(&self) -> String {
+        match self.authors.as_slice() {
+            [] => self.key.clone(),
+            [a] => a.last.clone(),
+            [a, b] => format!("{} and {}", a.last, b.last),
+            [a, ..] => format!("{} et al.", a.last),
+        }
+    }
+
+    pub fn short_year(&self) -> &str {
+        self.year.as_deref().unwrap_or("n.d.")
+    }
+
+    /// The full reference, as shown in the list of references.
+    pub fn reference_html(&self) -> String {
+        let mut res = String::new();
+
+        let authors: Vec<String> = self.authors.iter().map(Author::full_name).collect();
+        if !authors.is_empty() {
+            res.push_str(&html_escape::encode_text(&join_authors(&authors)));
+            res.push(' ');
+        }
+        res.push_str(&format!(
+            "({}). ",
+            html_escape::encode_text(self.short_year())
+        ));
+        if let Some(title) = &self.title {
+            res.push_str(&format!(
+                "<cite>{}</cite>. ",
+                html_escape::encode_text(title)
+            ));
+        }
+        if let Some(venue) = &self.venue {
+            res.push_str(&format!("{}. ", html_escape::encode_text(venue)));
+        }
+        if let Some(url) = &self.url {
+            res.push_str(&format!(
+                r#"<a href="{}">{}</a>"#,
+                html_escape::encode_double_quoted_attribute(url),
+                html_escape::encode_text(url)
+            ));
+        }
+
+        res.trim_end().to_string()
+    }
+}
+
+impl Author {
+    fn from_person(person: &Person) -> Self {
+        let last = if person.prefix.is_empty() {
+            person.name.clone()
+        } else {
+            format!("{} {}", person.prefix, person.name)
+        };
+        Self {
+            first: Some(person.given_name.clone()).filter(|x| !x.is_empty()),
+            last,
+        }
+    }
+
+    fn full_name(&self) -> String {
+        match &self.first {
+            Some(first) => format!("{first} {}", self.last),
+            None => self.last.clone(),
+        }
+    }
+}
+
+fn join_authors(authors: &[String]) -> String {
+    match authors {
+        [] => String::new(),
+        [a] => a.clone(),
+        [init @ .., last] => format!("{} and {last}", init.join(", ")),
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CitationStyle {
+    /// Citations are numbered in the order they're first cited, like `[1]`.
+    #[default]
+    Numbered,
+    /// Citations show author and year, like `(Knuth, 1984)`.
+    AuthorYear,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bibliography() -> Result<()> {
+        let bib = Bibliography::parse(
+            r#"
+@comment{ This is ignored }
+
+@book{knuth1984,
+  author = {Knuth, Donald E.},
+  title = {The {TeX}book},
+  publisher = "Addison-Wesley",
+  year = 1984,
+}
+
+@article{kp1978,
+  author = {Brian W. Kernighan and Plauger, P. J. and Ritchie, Dennis},
+  title = "Software " # {Tools},
+  year = {1978}
+}
+"#,
+        )?;
+
+        assert_eq!(bib.entries.len(), 2);
+
+        let knuth = bib.get("knuth1984").unwrap();
+        assert_eq!(knuth.kind, "book");
+        assert_eq!(knuth.title.as_deref(), Some("The TeXbook"));
+        assert_eq!(knuth.year.as_deref(), Some("1984"));
+        assert_eq!(knuth.venue.as_deref(), Some("Addison-Wesley"));
+        assert_eq!(knuth.short_authors(), "Knuth");
+        assert_eq!(
+            knuth.reference_html(),
+            "Donald E. Knuth (1984). <cite>The TeXbook</cite>. Addison-Wesley."
+        );
+
+        let kp = bib.get("kp1978").unwrap();
+        assert_eq!(kp.title.as_deref(), Some("Software Tools"));
+        assert_eq!(kp.short_authors(), "Kernighan et al.");
+        assert_eq!(
+            kp.authors[1],
+            Author {
+                first: Some("P. J.".into()),
+                last: "Plauger".into()
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_bibliography_error() {
+        assert!(Bibliography::parse("@book{key, title = {Unterminated").is_err());
+    }
+}
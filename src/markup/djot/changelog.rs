@@ -1,6 +1,7 @@
+use crate::markup::markup_lookup::{ChangeLog, ChangeLogItem};
 use chrono::NaiveDate;
 use eyre::{eyre, Result};
-use jotdown::{Attributes, Container, Event};
+use jotdown::{html::Renderer, Attributes, Container, Event, Render};
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::borrow::Cow;
@@ -22,10 +23,10 @@ lazy_static! {
         Regex::new(r#"(?s)<!-- CHANGELOG_START -->(.+)<!-- CHANGELOG_END -->"#).unwrap();
 }
 
-// TODO
-// 1. Should store these and output them last in post
-// 2. Need to format them in a nice way.
-pub fn convert_changelog<'a, I>(mut content: I) -> Result<Vec<Event<'a>>>
+/// Convert a description list of dates and descriptions to a changelog section,
+/// that's later moved to the end of the post by `move_changelog`.
+/// Also returns the parsed entries, so they can be used outside of the post content.
+pub fn convert_changelog<'a, I>(mut content: I) -> Result<(Vec<Event<'a>>, ChangeLog)>
 where
     I: Iterator<Item = Event<'a>>,
 {
@@ -35,12 +36,14 @@ where
     ));
 
     let mut res = Vec::new();
+    let mut items = Vec::new();
 
     let html = Container::RawBlock { format: "html" };
 
     res.push(Event::Start(html.clone(), Attributes::new()));
     res.push(Event::Str(
-        r#"<!-- CHANGELOG_START --><section class="changelog"><hr /><ul class="items">"#.into(),
+        r#"<!-- CHANGELOG_START --><section class="changelog" id="changelog"><hr /><ul class="items">"#
+            .into(),
     ));
     res.push(Event::End(html.clone()));
 
@@ -93,13 +96,22 @@ where
         ));
         res.push(Event::End(html.clone()));
 
+        let mut description = Vec::new();
         for token in content.by_ref() {
             match token {
                 Event::End(Container::DescriptionDetails) => break,
-                x => res.push(x),
+                x => description.push(x),
             }
         }
 
+        let mut description_html = String::new();
+        Renderer::default().push(description.iter().cloned(), &mut description_html)?;
+        items.push(ChangeLogItem {
+            date: when,
+            description: description_html,
+        });
+        res.append(&mut description);
+
         // Close `description` and `item`
         res.push(Event::Start(html.clone(), Attributes::new()));
         res.push(Event::Str("</div></li>".into()));
@@ -111,7 +123,7 @@ where
     res.push(Event::Str("</ul></section><!-- CHANGELOG_END -->".into()));
     res.push(Event::End(html.clone()));
 
-    Ok(res)
+    Ok((res, ChangeLog { items }))
 }
 
 #[cfg(test)]
@@ -0,0 +1,221 @@
+use eyre::{eyre, Result};
+use jotdown::{Attributes, Container, Event};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::ops::Range;
+use std::rc::Rc;
+use tracing::warn;
+
+use crate::markup::djot::code_include::site_path;
+use crate::markup::graphs::{Chart, ChartData, ChartType};
+use crate::markup::markup_lookup::{Element, MarkupLookup};
+use crate::paths::RelPath;
+
+/// Render `chart` code blocks to svg, with the data as csv or toml in the block:
+///
+///     {type=bar caption="Hash rate"}
+///     ```chart
+///     Miner, Hash rate
+///     CPU, 1
+///     GPU, 10
+///     ```
+///
+/// Or in a file with `data`, relative to the site root:
+///
+///     {type=line data="plots/hash_rate.csv"}
+///     ```chart
+///     ```
+///
+/// Charts we fail to render are left as is and registered as errors in the lookup.
+pub struct Charts<'a, 'b, I: Iterator<Item = (Event<'a>, Range<usize>)>> {
+    parent: I,
+    event_queue: Vec<(Event<'a>, Range<usize>)>,
+    embedded_files: &'b RefCell<HashSet<RelPath>>,
+    lookup: Option<Rc<RefCell<MarkupLookup>>>,
+}
+
+impl<'a, 'b, I: Iterator<Item = (Event<'a>, Range<usize>)>> Charts<'a, 'b, I> {
+    pub fn new(
+        parent: I,
+        embedded_files: &'b RefCell<HashSet<RelPath>>,
+        lookup: Option<Rc<RefCell<MarkupLookup>>>,
+    ) -> Self {
+        Self {
+            parent,
+            event_queue: vec![],
+            embedded_files,
+            lookup,
+        }
+    }
+
+    fn chart(&self, attrs: &Attributes, body: &str) -> Result<String> {
+        let chart_type = match attrs.get("type") {
+            Some(x) => {
+                let x = x.to_string();
+                ChartType::parse(&x).ok_or_else(|| eyre!("Unknown chart type `{x}`"))?
+            }
+            None => ChartType::Line,
+        };
+
+        let mut data = match attrs.get("data") {
+            Some(path) => {
+                let path = path.to_string();
+                let path = site_path(path.strip_prefix('/').unwrap_or(&path))?;
+                self.embedded_files
+                    .borrow_mut()
+                    .insert(RelPath(path.clone()));
+                ChartData::load(&path)?
+            }
+            None => ChartData::parse(body)?,
+        };
+        if let Some(label) = attrs.get("x-label") {
+            data.x_label = Some(label.to_string());
+        }
+        if let Some(label) = attrs.get("y-label") {
+            data.y_label = Some(label.to_string());
+        }
+
+        Chart {
+            chart_type,
+            data,
+            caption: attrs.get("caption").map(|x| x.to_string()),
+        }
+        .generate()
+    }
+}
+
+impl<'a, 'b, I: Iterator<Item = (Event<'a>, Range<usize>)>> Iterator for Charts<'a, 'b, I> {
+    type Item = (Event<'a>, Range<usize>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(event) = self.event_queue.pop() {
+            return Some(event);
+        }
+
+        let (language, attrs, start_range) = match self.parent.next()? {
+            (Event::Start(Container::CodeBlock { language }, attrs), range)
+                if language == "chart" =>
+            {
+                (language, attrs, range)
+            }
+            other => return Some(other),
+        };
+
+        let mut inner = Vec::new();
+        let mut body = String::new();
+        let end = loop {
+            match self.parent.next().expect("Code block should be closed") {
+                end @ (Event::End(Container::CodeBlock { .. }), _) => break end,
+                (Event::Str(s), range) => {
+                    body.push_str(&s);
+                    inner.push((Event::Str(s), range));
+                }
+                other => inner.push(other),
+            }
+        };
+
+        let range = start_range.start..end.1.end;
+        match self.chart(&attrs, &body) {
+            Ok(svg) => {
+                let html = Container::RawBlock { format: "html" };
+                self.event_queue
+                    .push((Event::End(html.clone()), range.end..range.end));
+                self.event_queue
+                    .push((Event::Str(svg.into()), range.clone()));
+                Some((
+                    Event::Start(html, Attributes::new()),
+                    range.start..range.start,
+                ))
+            }
+            Err(err) => {
+                warn!("Failed to render chart: {err}");
+                if let Some(lookup) = &self.lookup {
+                    lookup.borrow_mut().insert_element(
+                        Element::ChartError {
+                            message: err.to_string(),
+                        },
+                        range,
+                    );
+                }
+                self.event_queue.push(end);
+                for event in inner.into_iter().rev() {
+                    self.event_queue.push(event);
+                }
+                Some((
+                    Event::Start(Container::CodeBlock { language }, attrs),
+                    start_range,
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markup::djot::drop_offset::DropOffset;
+    use jotdown::{html, Parser, Render};
+
+    fn convert(s: &str) -> Result<(String, MarkupLookup)> {
+        let lookup = Rc::new(RefCell::new(MarkupLookup::new(s, 0)));
+        let embedded_files = RefCell::new(HashSet::new());
+        let parser = Parser::new(s).into_offset_iter();
+        let transformed = Charts::new(parser, &embedded_files, Some(lookup.clone()));
+        let transformed = DropOffset::new(transformed);
+        let mut body = String::new();
+        html::Renderer::default().push(transformed, &mut body)?;
+        let lookup = Rc::try_unwrap(lookup).unwrap().into_inner();
+        Ok((body, lookup))
+    }
+
+    #[test]
+    fn test_chart_block() -> Result<()> {
+        let s = r#"
+{type=scatter y-label="Words"}
+```chart
+Year, Posts
+2022, 20
+2023, 34
+```
+"#;
+        let (html, _) = convert(s)?;
+        assert!(html.contains(r#"<figure class="plot-wrapper">"#));
+        assert!(html.contains(r#"class="chart chart-scatter""#));
+        assert!(html.contains(r#"class="label-title">Words</text>"#));
+        Ok(())
+    }
+
+    #[test]
+    fn test_chart_error() -> Result<()> {
+        let s = r#"
+{type=pie}
+```chart
+Year, Posts
+2022, 20
+```
+"#;
+        let (html, lookup) = convert(s)?;
+        assert!(html.contains(r#"<pre><code class="language-chart">"#));
+        assert!(lookup.char_pos_to_element.iter().any(|(_, e)| e.element
+            == Element::ChartError {
+                message: "Unknown chart type `pie`".into()
+            }));
+        Ok(())
+    }
+
+    #[test]
+    fn test_chart_data_outside_site() -> Result<()> {
+        let s = r#"
+{data="../secrets.csv"}
+```chart
+```
+"#;
+        let (html, lookup) = convert(s)?;
+        assert!(html.contains(r#"<pre><code class="language-chart">"#));
+        assert!(lookup.char_pos_to_element.iter().any(|(_, e)| e.element
+            == Element::ChartError {
+                message: "Can't include `../secrets.csv` outside of the site".into()
+            }));
+        Ok(())
+    }
+}
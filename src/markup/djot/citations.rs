@@ -0,0 +1,356 @@
+use crate::markup::markup_lookup::{Element, MarkupLookup};
+use crate::markup::{BibEntry, CitationStyle, ParseContext};
+use jotdown::{Attributes, Container, Event};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::iter::Peekable;
+use std::ops::Range;
+use std::rc::Rc;
+
+/// Resolve `[@key]` and `[@key1; @key2]` citations against the bibliography
+/// and append a list of references at the end of the document.
+pub struct Citations<'a, I: Iterator<Item = (Event<'a>, Range<usize>)>> {
+    parent: Peekable<I>,
+    context: ParseContext<'a>,
+    lookup: Option<Rc<RefCell<MarkupLookup>>>,
+    event_queue: Vec<(Event<'a>, Range<usize>)>,
+    /// Cited entries, in the order they were first cited.
+    cited: Vec<&'a BibEntry>,
+    /// End of the last seen event, where the references are placed.
+    end: usize,
+    /// Depth of containers where citations aren't resolved, such as code.
+    skip_depth: usize,
+    done: bool,
+}
+
+impl<'a, I: Iterator<Item = (Event<'a>, Range<usize>)>> Citations<'a, I> {
+    pub fn new(
+        parent: I,
+        context: ParseContext<'a>,
+        lookup: Option<Rc<RefCell<MarkupLookup>>>,
+    ) -> Self {
+        Self {
+            parent: parent.peekable(),
+            context,
+            lookup,
+            event_queue: vec![],
+            cited: vec![],
+            end: 0,
+            skip_depth: 0,
+            done: false,
+        }
+    }
+
+    fn cite(&mut self, keys: &str, range: Range<usize>) -> String {
+        let mut parts = Vec::new();
+
+        for key in keys
+            .split(';')
+            .map(|key| key.trim().trim_start_matches('@'))
+        {
+            let entry = match self.context.bibliography.and_then(|bib| bib.get(key)) {
+                Some(entry) => entry,
+                None => {
+                    self.context.log_unknown_citation(key);
+                    if let Some(lookup) = &self.lookup {
+                        lookup.borrow_mut().insert_element(
+                            Element::UnknownCitation {
+                                key: key.to_string(),
+                            },
+                            range.clone(),
+                        );
+                    }
+                    parts.push(format!("?{}", html_escape::encode_text(key)));
+                    continue;
+                }
+            };
+
+            let num = match self.cited.iter().position(|x| x.key == entry.key) {
+                Some(i) => i + 1,
+                None => {
+                    self.cited.push(entry);
+                    self.cited.len()
+                }
+            };
+
+            let label = match self.context.citation_style {
+                CitationStyle::Numbered => num.to_string(),
+                CitationStyle::AuthorYear => format!(
+                    "{}, {}",
+                    html_escape::encode_text(&entry.short_authors()),
+                    html_escape::encode_text(entry.short_year())
+                ),
+            };
+            parts.push(format!(
+                r##"<a href="#ref-{}" role="doc-biblioref">{label}</a>"##,
+                html_escape::encode_double_quoted_attribute(&entry.key)
+            ));
+        }
+
+        match self.context.citation_style {
+            CitationStyle::Numbered => {
+                format!(r#"<span class="citation">[{}]</span>"#, parts.join(", "))
+            }
+            CitationStyle::AuthorYear => {
+                format!(r#"<span class="citation">({})</span>"#, parts.join("; "))
+            }
+        }
+    }
+
+    fn references(&self) -> Option<String> {
+        if self.cited.is_empty() {
+            return None;
+        }
+
+        let (list, entries) = match self.context.citation_style {
+            CitationStyle::Numbered => ("ol", self.cited.clone()),
+            CitationStyle::AuthorYear => {
+                let mut entries = self.cited.clone();
+                entries
+                    .sort_by_key(|entry| (entry.short_authors(), entry.short_year().to_string()));
+                ("ul", entries)
+            }
+        };
+
+        let mut res = format!(
+            r#"<!-- REFERENCES_START --><section class="references" role="doc-bibliography"><hr /><{list} class="items">"#
+        );
+        for entry in entries {
+            res.push_str(&format!(
+                r#"<li id="ref-{}">{}</li>"#,
+                html_escape::encode_double_quoted_attribute(&entry.key),
+                entry.reference_html()
+            ));
+        }
+        res.push_str(&format!("</{list}></section><!-- REFERENCES_END -->"));
+        Some(res)
+    }
+}
+
+impl<'a, I: Iterator<Item = (Event<'a>, Range<usize>)>> Iterator for Citations<'a, I> {
+    type Item = (Event<'a>, Range<usize>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(event) = self.event_queue.pop() {
+            return Some(event);
+        }
+
+        let (mut text, mut range) = match self.parent.next() {
+            Some((Event::Str(s), range)) if self.skip_depth == 0 => (s, range),
+            Some(other) => {
+                match &other.0 {
+                    Event::Start(container, _) if is_skipped(container) => self.skip_depth += 1,
+                    Event::End(container) if is_skipped(container) => {
+                        self.skip_depth = self.skip_depth.saturating_sub(1)
+                    }
+                    _ => {}
+                }
+                self.end = other.1.end;
+                return Some(other);
+            }
+            None => {
+                if self.done {
+                    return None;
+                }
+                self.done = true;
+                let end = self.end;
+                let html = Container::RawBlock { format: "html" };
+                let references = self.references()?;
+                self.event_queue.push((Event::End(html.clone()), end..end));
+                self.event_queue
+                    .push((Event::Str(references.into()), end..end));
+                return Some((Event::Start(html, Attributes::new()), end..end));
+            }
+        };
+
+        // Brackets may split the text into several events, so merge them.
+        while let Some((Event::Str(_), _)) = self.parent.peek() {
+            if let Some((Event::Str(s), next_range)) = self.parent.next() {
+                text.to_mut().push_str(&s);
+                range.end = next_range.end;
+            }
+        }
+
+        if !CITATION.is_match(&text) {
+            self.end = range.end;
+            return Some((Event::Str(text), range));
+        }
+
+        let mut events = Vec::new();
+        let mut last = 0;
+        for caps in CITATION.captures_iter(&text) {
+            let m = caps.get(0).unwrap();
+            if m.start() > last {
+                events.push((
+                    Event::Str(text[last..m.start()].to_string().into()),
+                    range.clone(),
+                ));
+            }
+
+            // Positions are only exact if the text hasn't been escaped or transformed,
+            // but it's good enough to point a diagnostic at the right place.
+            let cite_range =
+                (range.start + m.start()).min(range.end)..(range.start + m.end()).min(range.end);
+            let html = Container::RawInline { format: "html" };
+            let cite = self.cite(&caps[1], cite_range.clone());
+            events.push((
+                Event::Start(html.clone(), Attributes::new()),
+                cite_range.clone(),
+            ));
+            events.push((Event::Str(cite.into()), cite_range.clone()));
+            events.push((Event::End(html), cite_range));
+
+            last = m.end();
+        }
+        if last < text.len() {
+            events.push((Event::Str(text[last..].to_string().into()), range.clone()));
+        }
+
+        self.end = range.end;
+        for event in events.into_iter().rev() {
+            self.event_queue.push(event);
+        }
+        self.event_queue.pop()
+    }
+}
+
+fn is_skipped(container: &Container) -> bool {
+    matches!(
+        container,
+        Container::Verbatim
+            | Container::CodeBlock { .. }
+            | Container::RawBlock { .. }
+            | Container::RawInline { .. }
+            | Container::Math { .. }
+    )
+}
+
+lazy_static! {
+    static ref CITATION: Regex = Regex::new(r"\[(@[\w:.\-]+(?:\s*;\s*@[\w:.\-]+)*)\]").unwrap();
+    static ref REFERENCES: Regex =
+        Regex::new(r#"(?s)<!-- REFERENCES_START -->(.+)<!-- REFERENCES_END -->"#).unwrap();
+}
+
+/// Move the references to the end, after footnotes.
+pub fn move_references(body: &str) -> Cow<'_, str> {
+    if let Some(caps) = REFERENCES.captures(body) {
+        let mut res = body.to_string();
+        res.replace_range(caps.get(0).unwrap().range(), "");
+        res.push_str(&caps[1]);
+        res.push('\n');
+        Cow::Owned(res)
+    } else {
+        Cow::Borrowed(body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markup::djot::drop_offset::DropOffset;
+    use crate::markup::Bibliography;
+    use eyre::Result;
+    use jotdown::{html, Parser, Render};
+
+    fn bibliography() -> Result<Bibliography> {
+        Bibliography::parse(
+            r#"
+@book{knuth1984,
+  author = {Knuth, Donald E.},
+  title = {The TeXbook},
+  year = 1984,
+}
+@book{kr1988,
+  author = {Kernighan, Brian W. and Ritchie, Dennis M.},
+  title = {The C Programming Language},
+  year = 1988,
+}
+"#,
+        )
+    }
+
+    fn convert(
+        s: &str,
+        bib: &Bibliography,
+        style: CitationStyle,
+    ) -> Result<(String, MarkupLookup)> {
+        let lookup = Rc::new(RefCell::new(MarkupLookup::new(s, 0)));
+        let context = ParseContext::default()
+            .with_bibliography(bib)
+            .citation_style(style);
+        let parser = Parser::new(s).into_offset_iter();
+        let transformed = Citations::new(parser, context, Some(lookup.clone()));
+        let transformed = DropOffset::new(transformed);
+        let mut body = String::new();
+        html::Renderer::default().push(transformed, &mut body)?;
+        let body = move_references(&body).to_string();
+        let lookup = Rc::try_unwrap(lookup).unwrap().into_inner();
+        Ok((body, lookup))
+    }
+
+    #[test]
+    fn test_numbered_citations() -> Result<()> {
+        let bib = bibliography()?;
+        let (html, lookup) = convert(
+            "See [@kr1988] and [@knuth1984; @kr1988].",
+            &bib,
+            CitationStyle::Numbered,
+        )?;
+        assert!(html.starts_with(
+            r##"<p>See <span class="citation">[<a href="#ref-kr1988" role="doc-biblioref">1</a>]</span> and <span class="citation">[<a href="#ref-knuth1984" role="doc-biblioref">2</a>, <a href="#ref-kr1988" role="doc-biblioref">1</a>]</span>.</p>"##
+        ));
+        let kr = html.find(r#"<li id="ref-kr1988">"#).unwrap();
+        let knuth = html.find(r#"<li id="ref-knuth1984">"#).unwrap();
+        assert!(kr < knuth);
+        assert!(html.contains(r#"<section class="references" role="doc-bibliography">"#));
+        assert!(lookup.char_pos_to_element.iter().next().is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_author_year_citations() -> Result<()> {
+        let bib = bibliography()?;
+        let (html, _) = convert("See [@kr1988].", &bib, CitationStyle::AuthorYear)?;
+        assert!(html.contains(
+            r##"<span class="citation">(<a href="#ref-kr1988" role="doc-biblioref">Kernighan and Ritchie, 1988</a>)</span>"##
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_citations_in_code() -> Result<()> {
+        let bib = bibliography()?;
+        let (html, lookup) = convert(
+            "Not `[@kr1988]` or $`[@kr1988]`\n\n```\n[@missing]\n```\n",
+            &bib,
+            CitationStyle::Numbered,
+        )?;
+        assert!(html.contains("<code>[@kr1988]</code>"));
+        assert!(html.contains("[@missing]\n</code></pre>"));
+        assert!(!html.contains("citation"));
+        assert!(lookup.char_pos_to_element.iter().next().is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_citation() -> Result<()> {
+        let bib = bibliography()?;
+        let (html, lookup) = convert("See [@missing].", &bib, CitationStyle::Numbered)?;
+        assert!(html.contains(r#"<span class="citation">[?missing]</span>"#));
+        assert!(!html.contains("references"));
+        let elements: Vec<_> = lookup
+            .char_pos_to_element
+            .iter()
+            .map(|(_, e)| &e.element)
+            .collect();
+        assert_eq!(
+            elements,
+            [&Element::UnknownCitation {
+                key: "missing".into()
+            }]
+        );
+        Ok(())
+    }
+}
@@ -1,22 +1,45 @@
+use crate::markup::prefixed_id;
 use crate::markup::syntax_highlight::*;
 use itertools::{Itertools, MultiPeek};
-use jotdown::{Attributes, Container, Event};
+use jotdown::{Attributes, Container, Event, ListKind};
 
-pub struct CodeBlockSyntaxHighlight<'a, I: Iterator<Item = Event<'a>>> {
+pub struct CodeBlockSyntaxHighlight<'a, 'b, I: Iterator<Item = Event<'a>>> {
     parent: I,
     event_queue: Vec<Event<'a>>,
+    /// Number of code blocks with callouts, used to create unique ids.
+    callout_blocks: usize,
+    /// Prefix for the callout ids, as several items may be rendered on the same page.
+    id_prefix: Option<&'b str>,
+    /// An ordered list directly after a code block with callouts
+    /// describes the callouts.
+    callout_list: Option<CalloutList>,
 }
 
-impl<'a, I: Iterator<Item = Event<'a>>> CodeBlockSyntaxHighlight<'a, I> {
+struct CalloutList {
+    id: String,
+    depth: usize,
+    items: usize,
+    badge_pending: bool,
+}
+
+impl<'a, 'b, I: Iterator<Item = Event<'a>>> CodeBlockSyntaxHighlight<'a, 'b, I> {
     pub fn new(parent: I) -> Self {
         Self {
             parent,
             event_queue: vec![],
+            callout_blocks: 0,
+            id_prefix: None,
+            callout_list: None,
         }
     }
+
+    pub fn with_id_prefix(mut self, id_prefix: Option<&'b str>) -> Self {
+        self.id_prefix = id_prefix;
+        self
+    }
 }
 
-impl<'a, I: Iterator<Item = Event<'a>>> Iterator for CodeBlockSyntaxHighlight<'a, I> {
+impl<'a, 'b, I: Iterator<Item = Event<'a>>> Iterator for CodeBlockSyntaxHighlight<'a, 'b, I> {
     type Item = Event<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -24,13 +47,84 @@ impl<'a, I: Iterator<Item = Event<'a>>> Iterator for CodeBlockSyntaxHighlight<'a
             return Some(event);
         }
 
-        let (lang, attrs) = match self.parent.next()? {
+        let event = self.parent.next()?;
+
+        let event = if let Some(list) = self.callout_list.as_mut() {
+            match event {
+                Event::Start(
+                    container @ Container::List {
+                        kind: ListKind::Ordered { .. },
+                        ..
+                    },
+                    mut attrs,
+                ) if list.depth == 0 => {
+                    list.depth = 1;
+                    attrs.insert("class", "callouts".into());
+                    return Some(Event::Start(container, attrs));
+                }
+                Event::Start(container @ Container::List { .. }, attrs) if list.depth > 0 => {
+                    list.depth += 1;
+                    return Some(Event::Start(container, attrs));
+                }
+                Event::End(container @ Container::List { .. }) if list.depth > 0 => {
+                    list.depth -= 1;
+                    if list.depth == 0 {
+                        self.callout_list = None;
+                    }
+                    return Some(Event::End(container));
+                }
+                Event::Start(Container::ListItem, attrs) if list.depth == 1 => {
+                    list.items += 1;
+                    list.badge_pending = true;
+                    return Some(Event::Start(Container::ListItem, attrs));
+                }
+                // Link the item to the callout in the code block.
+                Event::Start(Container::Paragraph, attrs) if list.badge_pending => {
+                    list.badge_pending = false;
+                    let html = Container::RawInline { format: "html" };
+                    self.event_queue.push(Event::End(html.clone()));
+                    self.event_queue.push(Event::Str(
+                        format!(
+                            r##"<a class="callout" href="#{}-{n}">{n}</a> "##,
+                            list.id,
+                            n = list.items
+                        )
+                        .into(),
+                    ));
+                    self.event_queue.push(Event::Start(html, Attributes::new()));
+                    return Some(Event::Start(Container::Paragraph, attrs));
+                }
+                event if list.depth > 0 => return Some(event),
+                // The list is separated from the code block by a blank line.
+                Event::Blankline => return Some(Event::Blankline),
+                event => {
+                    self.callout_list = None;
+                    event
+                }
+            }
+        } else {
+            event
+        };
+
+        let (lang, attrs) = match event {
             Event::Start(Container::CodeBlock { language }, attrs) => (language, attrs),
             other => return Some(other),
         };
 
         let lang = parse_code_spec(lang);
-        let path = attrs.get("path").map(|x| x.to_string());
+        // `diff-rust` highlights the code as rust while marking added and removed lines.
+        let (lang, diff) = match lang.as_deref().and_then(|x| x.strip_prefix("diff-")) {
+            Some(inner) => (Some(inner.to_string()), true),
+            None => (
+                lang,
+                attrs.get("diff").is_some_and(|x| x.to_string() == "true"),
+            ),
+        };
+        // Show the path of included files unless overridden.
+        let path = attrs
+            .get("path")
+            .or_else(|| attrs.get("include"))
+            .map(|x| x.to_string());
         let highlight_lines = attrs.get("hl").map(|x| {
             parse_line_highlight_spec(x.to_string().as_str())
                 .expect("Error parsing `hl` code block attribute")
@@ -47,6 +141,20 @@ impl<'a, I: Iterator<Item = Event<'a>>> Iterator for CodeBlockSyntaxHighlight<'a
             code.push_str(&text);
         }
 
+        let callout_id = if has_callouts(&code) {
+            self.callout_blocks += 1;
+            let id = prefixed_id(self.id_prefix, &format!("callout-{}", self.callout_blocks));
+            self.callout_list = Some(CalloutList {
+                id: id.clone(),
+                depth: 0,
+                items: 0,
+                badge_pending: false,
+            });
+            Some(id)
+        } else {
+            None
+        };
+
         let mut res = String::new();
 
         Code::Block {
@@ -55,6 +163,8 @@ impl<'a, I: Iterator<Item = Event<'a>>> Iterator for CodeBlockSyntaxHighlight<'a
             path: path.as_deref(),
             linenum_start,
             highlight_lines,
+            diff,
+            callout_id: callout_id.as_deref(),
         }
         .push(&mut res);
 
@@ -175,8 +285,12 @@ mod tests {
     use jotdown::{html, Parser, Render};
 
     fn convert(s: &str) -> Result<String> {
+        convert_with_prefix(s, None)
+    }
+
+    fn convert_with_prefix(s: &str, id_prefix: Option<&str>) -> Result<String> {
         let parser = Parser::new(s);
-        let transformed = CodeBlockSyntaxHighlight::new(parser);
+        let transformed = CodeBlockSyntaxHighlight::new(parser).with_id_prefix(id_prefix);
         let transformed = InlineCodeSyntaxHighlight::new(transformed);
         let mut body = String::new();
         html::Renderer::default().push(transformed, &mut body)?;
@@ -195,6 +309,85 @@ let x = 2;
         Ok(())
     }
 
+    #[test]
+    fn test_highlight_diff_code_block() -> Result<()> {
+        let s = r"
+```diff-rust
+-let x = 2;
++let x = 3;
+```";
+        let res = convert(s)?;
+        assert!(res.contains(r#"<div class="descr" data-descr="rust"></div>"#));
+        assert!(res.contains(r#"<code class="highlight rust">"#));
+        assert!(res.contains(r#"<div class="line diff-remove">"#));
+        assert!(res.contains(r#"<div class="line diff-add">"#));
+        assert!(!res.contains("+let"));
+
+        let s = r#"
+{diff=true}
+```
++added
+```"#;
+        assert!(convert(s)?.contains(r#"<div class="line diff-add">added"#));
+        Ok(())
+    }
+
+    #[test]
+    fn test_code_callouts() -> Result<()> {
+        let s = r#"
+{linenum=1 hl=2}
+```rust
+let x = 2; // <1>
+let y = x; // <2>
+```
+
+1. The first
+2. The second
+"#;
+        let res = convert(s)?;
+        assert!(res.contains(r#"<div class="line" data-linenum="1">"#));
+        assert!(res.contains(r#"<div class="line hl" data-linenum="2">"#));
+        assert!(res.contains(r#"<span class="callout" id="callout-1-1">1</span>"#));
+        assert!(res.contains(r#"<span class="callout" id="callout-1-2">2</span>"#));
+        assert!(!res.contains("&lt;1&gt;"));
+        assert!(res.contains(r#"<ol class="callouts">"#));
+        assert!(res.contains(r##"<a class="callout" href="#callout-1-2">2</a> The second"##));
+        Ok(())
+    }
+
+    #[test]
+    fn test_prefixed_code_callouts() -> Result<()> {
+        let s = r#"
+```rust
+let x = 2; // <1>
+```
+
+1. The first
+"#;
+        let res = convert_with_prefix(s, Some("my_note"))?;
+        assert!(res.contains(r#"<span class="callout" id="my_note-callout-1-1">1</span>"#));
+        assert!(res.contains(r##"<a class="callout" href="#my_note-callout-1-1">1</a> The first"##));
+        Ok(())
+    }
+
+    #[test]
+    fn test_code_callouts_after_paragraph() -> Result<()> {
+        let s = r#"
+```rust
+let x = 2; // <1>
+```
+
+Not a callout list:
+
+1. The first
+"#;
+        let res = convert(s)?;
+        assert!(res.contains(r#"<span class="callout" id="callout-1-1">1</span>"#));
+        assert!(!res.contains(r#"<ol class="callouts">"#));
+        assert!(!res.contains(r#"<a class="callout""#));
+        Ok(())
+    }
+
     #[test]
     fn test_highlight_empty_code_block() -> Result<()> {
         let s = r"
@@ -0,0 +1,350 @@
+use camino::{Utf8Component, Utf8Path, Utf8PathBuf};
+use eyre::{eyre, Result};
+use jotdown::{Attributes, Container, Event};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fs;
+use std::ops::Range;
+use std::rc::Rc;
+use tracing::warn;
+
+use crate::git::SiteRepository;
+use crate::markup::markup_lookup::{Element, MarkupLookup};
+use crate::paths::RelPath;
+
+/// Replace the content of code blocks with an `include` attribute with
+/// the content of a file in the repository, optionally limited with `lines`:
+///
+///     {include="src/site.rs" lines="10-40"}
+///     ```rust
+///     ```
+///
+/// Use `git` instead of `include` to read the file from a specific commit:
+///
+///     {git="abc123:src/upload.rs" lines="1-30"}
+///     ```rust
+///     ```
+pub struct CodeIncludes<'a, 'b, I: Iterator<Item = (Event<'a>, Range<usize>)>> {
+    parent: I,
+    event_queue: Vec<(Event<'a>, Range<usize>)>,
+    repository: Option<&'b SiteRepository>,
+    embedded_files: &'b RefCell<HashSet<RelPath>>,
+    lookup: Option<Rc<RefCell<MarkupLookup>>>,
+}
+
+impl<'a, 'b, I: Iterator<Item = (Event<'a>, Range<usize>)>> CodeIncludes<'a, 'b, I> {
+    pub fn new(
+        parent: I,
+        repository: Option<&'b SiteRepository>,
+        embedded_files: &'b RefCell<HashSet<RelPath>>,
+        lookup: Option<Rc<RefCell<MarkupLookup>>>,
+    ) -> Self {
+        Self {
+            parent,
+            event_queue: vec![],
+            repository,
+            embedded_files,
+            lookup,
+        }
+    }
+}
+
+impl<'a, 'b, I: Iterator<Item = (Event<'a>, Range<usize>)>> Iterator for CodeIncludes<'a, 'b, I> {
+    type Item = (Event<'a>, Range<usize>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(event) = self.event_queue.pop() {
+            return Some(event);
+        }
+
+        let (start, mut attrs, start_range) = match self.parent.next()? {
+            (Event::Start(Container::CodeBlock { language }, attrs), range) => {
+                (Container::CodeBlock { language }, attrs, range)
+            }
+            other => return Some(other),
+        };
+
+        let source = if let Some(include) = attrs.get("include") {
+            Source::File(include.to_string())
+        } else if let Some(git) = attrs.get("git") {
+            Source::Git(git.to_string())
+        } else {
+            return Some((Event::Start(start, attrs), start_range));
+        };
+
+        let mut inner = Vec::new();
+        let end = loop {
+            match self.parent.next().expect("Code block should be closed") {
+                end @ (Event::End(Container::CodeBlock { .. }), _) => break end,
+                other => inner.push(other),
+            }
+        };
+
+        // Files in a commit never change, so only track files in the working tree.
+        if let Source::File(path) = &source {
+            if let Ok(path) = site_path(path) {
+                self.embedded_files.borrow_mut().insert(RelPath(path));
+            }
+        }
+
+        let lines = attrs.get("lines").map(|x| x.to_string());
+        match source.read(self.repository, lines.as_deref()) {
+            Ok((code, short_id)) => {
+                // Show which commit the code is from.
+                if let Some(short_id) = short_id {
+                    if attrs.get("path").is_none() {
+                        attrs.insert("path", format!("{} @ {short_id}", source.path()).into());
+                    }
+                }
+                let range = start_range.end..end.1.start;
+                self.event_queue.push(end);
+                self.event_queue.push((Event::Str(code.into()), range));
+            }
+            Err(err) => {
+                warn!("Couldn't include `{}` in code block: {err}", source.path());
+                if let Some(lookup) = &self.lookup {
+                    lookup.borrow_mut().insert_element(
+                        Element::CodeIncludeError {
+                            message: err.to_string(),
+                        },
+                        start_range.start..end.1.end,
+                    );
+                }
+                self.event_queue.push(end);
+                for event in inner.into_iter().rev() {
+                    self.event_queue.push(event);
+                }
+            }
+        }
+
+        Some((Event::Start(start, attrs), start_range))
+    }
+}
+
+enum Source {
+    /// A file in the working tree.
+    File(String),
+    /// A file in a commit, given as `rev:path`.
+    Git(String),
+}
+
+impl Source {
+    fn path(&self) -> &str {
+        match self {
+            Self::File(path) => path,
+            Self::Git(spec) => spec.split_once(':').map_or(spec.as_str(), |(_, path)| path),
+        }
+    }
+
+    /// Read the content, together with the short commit id for git sources.
+    fn read(
+        &self,
+        repository: Option<&SiteRepository>,
+        lines: Option<&str>,
+    ) -> Result<(String, Option<String>)> {
+        let (content, short_id) = match self {
+            Self::File(path) => {
+                let content = fs::read_to_string(site_path(path)?)
+                    .map_err(|err| eyre!("Couldn't read `{path}`: {err}"))?;
+                (content, None)
+            }
+            Self::Git(spec) => {
+                let (rev, path) = spec
+                    .split_once(':')
+                    .ok_or_else(|| eyre!("Expected `rev:path` but got `{spec}`"))?;
+                let repository =
+                    repository.ok_or_else(|| eyre!("No git repository to read `{spec}` from"))?;
+                let (content, short_id) =
+                    repository.read_file_at_revision(rev, Utf8Path::new(path))?;
+                (content, Some(short_id))
+            }
+        };
+
+        Ok((select_lines(content, self.path(), lines)?, short_id))
+    }
+}
+
+/// A path relative to the site root, that can't be used to read files outside of it.
+pub fn site_path(path: &str) -> Result<Utf8PathBuf> {
+    let res = Utf8PathBuf::from(path);
+    let outside = res
+        .components()
+        .any(|component| !matches!(component, Utf8Component::Normal(_) | Utf8Component::CurDir));
+    if res.has_root() || outside {
+        return Err(eyre!("Can't include `{path}` outside of the site"));
+    }
+    Ok(res)
+}
+
+fn select_lines(content: String, path: &str, lines: Option<&str>) -> Result<String> {
+    let spec = match lines {
+        Some(spec) => spec,
+        None => return Ok(content),
+    };
+
+    let (start, end) = parse_line_range(spec)?;
+    let count = content.lines().count();
+    let end = end.unwrap_or(count);
+    if start > count || end > count {
+        return Err(eyre!(
+            "Lines `{spec}` out of range, `{path}` only has {count} lines"
+        ));
+    }
+
+    let mut res = content
+        .lines()
+        .skip(start - 1)
+        .take(end + 1 - start)
+        .collect::<Vec<_>>()
+        .join("\n");
+    res.push('\n');
+    Ok(res)
+}
+
+/// Parse `10-40`, `10-` or `10` into a 1-indexed inclusive line range.
+fn parse_line_range(spec: &str) -> Result<(usize, Option<usize>)> {
+    let invalid = || eyre!("Invalid `lines` spec: `{spec}`");
+
+    let (start, end) = match spec.split_once('-') {
+        Some((start, "")) => (start.trim().parse().map_err(|_| invalid())?, None),
+        Some((start, end)) => (
+            start.trim().parse().map_err(|_| invalid())?,
+            Some(end.trim().parse().map_err(|_| invalid())?),
+        ),
+        None => {
+            let line = spec.trim().parse().map_err(|_| invalid())?;
+            (line, Some(line))
+        }
+    };
+
+    if start == 0 || end.is_some_and(|end| end < start) {
+        return Err(invalid());
+    }
+    Ok((start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markup::djot::drop_offset::DropOffset;
+    use crate::tests::git_fixture;
+    use jotdown::{html, Parser, Render};
+
+    fn convert(s: &str) -> Result<(String, HashSet<RelPath>, MarkupLookup)> {
+        convert_with_repository(s, None)
+    }
+
+    fn convert_with_repository(
+        s: &str,
+        repository: Option<&SiteRepository>,
+    ) -> Result<(String, HashSet<RelPath>, MarkupLookup)> {
+        let lookup = Rc::new(RefCell::new(MarkupLookup::new(s, 0)));
+        let embedded_files = RefCell::new(HashSet::new());
+        let parser = Parser::new(s).into_offset_iter();
+        let transformed =
+            CodeIncludes::new(parser, repository, &embedded_files, Some(lookup.clone()));
+        let transformed = DropOffset::new(transformed);
+        let mut body = String::new();
+        html::Renderer::default().push(transformed, &mut body)?;
+        let lookup = Rc::try_unwrap(lookup).unwrap().into_inner();
+        Ok((body, embedded_files.into_inner(), lookup))
+    }
+
+    #[test]
+    fn test_parse_line_range() -> Result<()> {
+        assert_eq!(parse_line_range("10-40")?, (10, Some(40)));
+        assert_eq!(parse_line_range("3")?, (3, Some(3)));
+        assert_eq!(parse_line_range("7-")?, (7, None));
+        assert!(parse_line_range("0-2").is_err());
+        assert!(parse_line_range("5-2").is_err());
+        assert!(parse_line_range("x").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_include_lines() -> Result<()> {
+        let s = r#"{include="test-site/bibliography.bib" lines="1-2"}
+```
+```"#;
+        let (html, embedded_files, lookup) = convert(s)?;
+        let expected: String = fs::read_to_string("test-site/bibliography.bib")?
+            .lines()
+            .take(2)
+            .map(|line| format!("{}\n", html_escape::encode_text(line)))
+            .collect();
+        assert_eq!(html, format!("<pre><code>{expected}</code></pre>\n"));
+        assert!(embedded_files.contains(&RelPath("test-site/bibliography.bib".into())));
+        assert!(lookup.char_pos_to_element.iter().next().is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_include_git() -> Result<()> {
+        let (_dir, repo) = git_fixture(&[("site/Cargo.toml", "[package]\nname = \"site\"\n")])?;
+        let repository = SiteRepository::new(repo, Some(Utf8Path::new("site/")));
+
+        let s = r#"{git="HEAD:Cargo.toml" lines="1"}
+```toml
+```"#;
+        let (html, embedded_files, lookup) = convert_with_repository(s, Some(&repository))?;
+        assert!(html.ends_with("<code class=\"language-toml\">[package]\n</code></pre>\n"));
+        assert!(embedded_files.is_empty());
+        assert!(lookup.char_pos_to_element.iter().next().is_none());
+
+        let s = r#"{git="0000000:Cargo.toml"}
+```
+```"#;
+        let (_, _, lookup) = convert_with_repository(s, Some(&repository))?;
+        assert_eq!(lookup.char_pos_to_element.iter().count(), 1);
+
+        // Without a repository there's nothing to read from.
+        let s = r#"{git="HEAD:Cargo.toml"}
+```
+```"#;
+        let (_, _, lookup) = convert(s)?;
+        assert_eq!(lookup.char_pos_to_element.iter().count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_include_errors() -> Result<()> {
+        let s = r#"{include="test-site/bibliography.bib" lines="1000-1001"}
+```
+```
+
+{include="does/not/exist.rs"}
+```
+```"#;
+        let (_, _, lookup) = convert(s)?;
+        let errors: Vec<_> = lookup
+            .char_pos_to_element
+            .iter()
+            .map(|(_, e)| &e.element)
+            .collect();
+        assert!(matches!(
+            errors[..],
+            [
+                Element::CodeIncludeError { .. },
+                Element::CodeIncludeError { .. }
+            ]
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_include_outside_site() -> Result<()> {
+        for path in ["../secret.txt", "/etc/passwd", "src/../../secret.txt"] {
+            let s = format!("{{include=\"{path}\"}}\n```\n```");
+            let (html, embedded_files, lookup) = convert(&s)?;
+            assert_eq!(html, "<pre><code></code></pre>\n");
+            assert!(embedded_files.is_empty());
+            let errors: Vec<_> = lookup
+                .char_pos_to_element
+                .iter()
+                .map(|(_, e)| &e.element)
+                .collect();
+            assert!(matches!(errors[..], [Element::CodeIncludeError { .. }]));
+        }
+        Ok(())
+    }
+}
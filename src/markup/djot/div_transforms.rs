@@ -1,26 +1,62 @@
 use crate::markup::djot::changelog::convert_changelog;
+use crate::markup::markup_lookup::MarkupLookup;
 use eyre::{eyre, Result};
-use jotdown::{Attributes, Container, Event, LinkType, SpanLinkType};
+use jotdown::{html::Renderer, Attributes, Container, Event, LinkType, Render, SpanLinkType};
 use lazy_static::lazy_static;
 use regex::Regex;
 use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use tera::Tera;
 use tracing::error;
 
-pub struct DivTransforms<'a, I: Iterator<Item = Event<'a>>> {
+/// Divs without a builtin transform are rendered with a site template from here,
+/// `::: foo` uses `templates/divs/foo.html` if it exists.
+const DIV_TEMPLATES_DIR: &str = "divs";
+
+pub struct DivTransforms<'a, 'b, I: Iterator<Item = Event<'a>>> {
     parent: I,
     event_queue: Vec<Event<'a>>,
+    /// Number of transformed divs, used to create unique ids.
+    count: usize,
+    in_feed: bool,
+    lookup: Option<Rc<RefCell<MarkupLookup>>>,
+    templates: Option<&'b Tera>,
 }
 
-impl<'a, I: Iterator<Item = Event<'a>>> DivTransforms<'a, I> {
+impl<'a, 'b, I: Iterator<Item = Event<'a>>> DivTransforms<'a, 'b, I> {
     pub fn new(parent: I) -> Self {
         Self {
             parent,
             event_queue: vec![],
+            count: 0,
+            in_feed: false,
+            lookup: None,
+            templates: None,
         }
     }
+
+    /// Render divs without a builtin transform with the site's div templates.
+    pub fn with_templates(mut self, templates: Option<&'b Tera>) -> Self {
+        self.templates = templates;
+        self
+    }
+
+    /// Register parsed content, such as the changelog, in the lookup.
+    pub fn with_lookup(mut self, lookup: Rc<RefCell<MarkupLookup>>) -> Self {
+        self.lookup = Some(lookup);
+        self
+    }
+
+    /// The feed can't rely on css or js, so some transforms are simplified.
+    pub fn in_feed(mut self, in_feed: bool) -> Self {
+        self.in_feed = in_feed;
+        self
+    }
 }
 
-impl<'a, I: Iterator<Item = Event<'a>>> Iterator for DivTransforms<'a, I> {
+impl<'a, 'b, I: Iterator<Item = Event<'a>>> Iterator for DivTransforms<'a, 'b, I> {
     type Item = Event<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -29,10 +65,12 @@ impl<'a, I: Iterator<Item = Event<'a>>> Iterator for DivTransforms<'a, I> {
         }
 
         let (transformer, class, attrs) = match self.parent.next()? {
-            Event::Start(Container::Div { class }, attrs) => match DivTransform::parse(class) {
-                Some(h) => (h, class, attrs),
-                _ => return Some(Event::Start(Container::Div { class }, attrs)),
-            },
+            Event::Start(Container::Div { class }, attrs) => {
+                match DivTransform::parse(class, self.templates) {
+                    Some(h) => (h, class, attrs),
+                    _ => return Some(Event::Start(Container::Div { class }, attrs)),
+                }
+            }
             other => return Some(other),
         };
 
@@ -44,7 +82,18 @@ impl<'a, I: Iterator<Item = Event<'a>>> Iterator for DivTransforms<'a, I> {
             }
         }
 
-        for x in transformer.transform(events, &attrs).into_iter().rev() {
+        self.count += 1;
+        let context = TransformContext {
+            index: self.count,
+            in_feed: self.in_feed,
+            lookup: self.lookup.as_deref(),
+            templates: self.templates,
+        };
+        for x in transformer
+            .transform(events, &attrs, context)
+            .into_iter()
+            .rev()
+        {
             self.event_queue.push(x);
         }
         self.event_queue.pop()
@@ -63,10 +112,20 @@ pub enum DivTransform {
     Gallery,
     Timeline,
     Changelog,
+    Tabs,
+    Template(String),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TransformContext<'l> {
+    index: usize,
+    in_feed: bool,
+    lookup: Option<&'l RefCell<MarkupLookup>>,
+    templates: Option<&'l Tera>,
 }
 
 impl DivTransform {
-    pub fn parse(id: &str) -> Option<Self> {
+    pub fn parse(id: &str, templates: Option<&Tera>) -> Option<Self> {
         match id.to_lowercase().as_str() {
             "note" => Some(Self::Note),
             "tip" => Some(Self::Tip),
@@ -78,11 +137,30 @@ impl DivTransform {
             "gallery" => Some(Self::Gallery),
             "timeline" => Some(Self::Timeline),
             "changelog" => Some(Self::Changelog),
-            _ => None,
+            "tabs" => Some(Self::Tabs),
+            x => templates
+                .filter(|templates| has_div_template(templates, x))
+                .map(|_| Self::Template(x.to_string())),
         }
     }
 
-    pub fn as_str(&self) -> &'static str {
+    /// All user defined div templates, sorted by name.
+    pub fn templates(templates: &Tera) -> Vec<Self> {
+        let mut res: Vec<Self> = templates
+            .get_template_names()
+            .filter_map(|name| {
+                name.strip_prefix(DIV_TEMPLATES_DIR)?
+                    .strip_prefix('/')?
+                    .strip_suffix(".html")
+            })
+            .filter_map(|name| Self::parse(name, Some(templates)))
+            .filter(|transform| matches!(transform, Self::Template(_)))
+            .collect();
+        res.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        res
+    }
+
+    pub fn as_str(&self) -> &str {
         match self {
             DivTransform::Flex => "flex",
             DivTransform::Figure => "figure",
@@ -94,6 +172,8 @@ impl DivTransform {
             DivTransform::Important => "important",
             DivTransform::Update => "update",
             DivTransform::Changelog => "changelog",
+            DivTransform::Tabs => "tabs",
+            DivTransform::Template(name) => name,
         }
     }
 
@@ -101,6 +181,7 @@ impl DivTransform {
         self,
         content: Vec<Event<'a>>,
         attrs: &Attributes,
+        context: TransformContext<'_>,
     ) -> Result<Vec<Event<'a>>> {
         match self {
             Self::Note => wrap_content(content.into_iter(), "aside", Some("note")),
@@ -127,12 +208,31 @@ impl DivTransform {
             Self::Figure => wrap_images(content.into_iter(), "figure", None, false),
             Self::Gallery => wrap_images(content.into_iter(), "figure", Some("gallery"), true),
             Self::Timeline => convert_timeline(content.into_iter(), attrs),
-            Self::Changelog => convert_changelog(content.into_iter()),
+            Self::Changelog => {
+                let (res, changelog) = convert_changelog(content.into_iter())?;
+                if let Some(lookup) = context.lookup {
+                    lookup.borrow_mut().changelog = Some(changelog);
+                }
+                Ok(res)
+            }
+            Self::Tabs => {
+                let id = match attrs.get("id") {
+                    Some(id) => id.to_string(),
+                    None => format!("tabs-{}", context.index),
+                };
+                convert_tabs(content.into_iter(), &id, context)
+            }
+            Self::Template(name) => convert_template(&name, content, attrs, context),
         }
     }
 
-    fn transform<'a>(self, content: Vec<Event<'a>>, attrs: &Attributes) -> Vec<Event<'a>> {
-        match self.try_transform(content, attrs) {
+    fn transform<'a>(
+        self,
+        content: Vec<Event<'a>>,
+        attrs: &Attributes,
+        context: TransformContext<'_>,
+    ) -> Vec<Event<'a>> {
+        match self.try_transform(content, attrs, context) {
             Ok(res) => res,
             Err(err) => {
                 error!("div transform error: {err}");
@@ -366,9 +466,220 @@ where
     Ok(res)
 }
 
+fn div_template_name(name: &str) -> String {
+    format!("{DIV_TEMPLATES_DIR}/{name}.html")
+}
+
+fn has_div_template(templates: &Tera, name: &str) -> bool {
+    let template = div_template_name(name);
+    templates.get_template_names().any(|x| x == template)
+}
+
+/// Render the div through its template, with the converted inner html as `content`
+/// and the div attributes as `attrs`.
+fn convert_template<'a>(
+    name: &str,
+    content: Vec<Event<'a>>,
+    attrs: &Attributes,
+    context: TransformContext<'_>,
+) -> Result<Vec<Event<'a>>> {
+    let templates = context
+        .templates
+        .ok_or_else(|| eyre!("Missing div template `{name}`"))?;
+    let template = div_template_name(name);
+
+    let mut inner = String::new();
+    Renderer::default().push(content.into_iter(), &mut inner)?;
+
+    let attrs: BTreeMap<String, String> = attrs
+        .unique_pairs()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+
+    let mut tera_context = tera::Context::new();
+    tera_context.insert("content", &inner);
+    tera_context.insert("attrs", &attrs);
+    tera_context.insert("in_feed", &context.in_feed);
+    let rendered = templates
+        .render(&template, &tera_context)
+        .map_err(|err| eyre!("Failed to render {template}: {err:?}"))?;
+
+    let html = Container::RawBlock { format: "html" };
+    Ok(vec![
+        Event::Start(html.clone(), Attributes::new()),
+        Event::Str(rendered.into()),
+        Event::End(html),
+    ])
+}
+
+struct Tab<'a> {
+    label: String,
+    content: Vec<Event<'a>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SplitTabsOn {
+    Heading(u16),
+    Div,
+}
+
+/// Split the content into tabs, either on headings or on nested divs,
+/// depending on which comes first.
+/// Only headings with the same level as the first one start a new tab.
+/// Anything before the first tab is placed before the tabs.
+///
+/// Headings used as tab labels aren't rendered, so they're removed from the lookup
+/// to keep them out of the table of content.
+fn split_tabs<'a, I>(
+    mut content: I,
+    lookup: Option<&RefCell<MarkupLookup>>,
+) -> (Vec<Event<'a>>, Vec<Tab<'a>>)
+where
+    I: Iterator<Item = Event<'a>>,
+{
+    let mut preface = Vec::new();
+    let mut tabs: Vec<Tab<'a>> = Vec::new();
+    let mut split_on = None;
+    let mut depth = 0;
+
+    while let Some(event) = content.next() {
+        if depth == 0 {
+            match event {
+                Event::Start(Container::Heading { level, ref id, .. }, _)
+                    if *split_on.get_or_insert(SplitTabsOn::Heading(level))
+                        == SplitTabsOn::Heading(level) =>
+                {
+                    if let Some(lookup) = lookup {
+                        lookup.borrow_mut().remove_heading(id);
+                    }
+                    let mut label = String::new();
+                    for e in content.by_ref() {
+                        match e {
+                            Event::End(Container::Heading { .. }) => break,
+                            Event::Str(s) => label.push_str(&s),
+                            _ => {}
+                        }
+                    }
+                    tabs.push(Tab {
+                        label,
+                        content: Vec::new(),
+                    });
+                    continue;
+                }
+                Event::Start(Container::Div { class }, ref attrs)
+                    if *split_on.get_or_insert(SplitTabsOn::Div) == SplitTabsOn::Div =>
+                {
+                    let label = match attrs.get("label") {
+                        Some(label) => label.to_string(),
+                        None => class.to_string(),
+                    };
+                    let mut tab_content = Vec::new();
+                    let mut div_depth = 0;
+                    for e in content.by_ref() {
+                        match e {
+                            Event::Start(Container::Div { .. }, _) => div_depth += 1,
+                            Event::End(Container::Div { .. }) if div_depth == 0 => break,
+                            Event::End(Container::Div { .. }) => div_depth -= 1,
+                            _ => {}
+                        }
+                        tab_content.push(e);
+                    }
+                    tabs.push(Tab {
+                        label,
+                        content: tab_content,
+                    });
+                    continue;
+                }
+                _ => {}
+            }
+        }
+
+        match &event {
+            Event::Start(container, _) if container.is_block() => depth += 1,
+            Event::End(container) if container.is_block() => depth -= 1,
+            _ => {}
+        }
+
+        match tabs.last_mut() {
+            Some(tab) => tab.content.push(event),
+            None => preface.push(event),
+        }
+    }
+
+    (preface, tabs)
+}
+
+fn convert_tabs<'a, I>(
+    content: I,
+    id: &str,
+    context: TransformContext<'_>,
+) -> Result<Vec<Event<'a>>>
+where
+    I: Iterator<Item = Event<'a>>,
+{
+    let (preface, tabs) = split_tabs(content, context.lookup);
+    if tabs.is_empty() {
+        return Err(eyre!("No headings or divs to split into tabs in `{id}`"));
+    }
+
+    let html = Container::RawBlock { format: "html" };
+    let raw = |s: String| {
+        [
+            Event::Start(html.clone(), Attributes::new()),
+            Event::Str(s.into()),
+            Event::End(html.clone()),
+        ]
+    };
+
+    let mut res = preface;
+
+    // The feed has no css or js, so we show the tabs one after another.
+    if context.in_feed {
+        for tab in tabs {
+            res.extend(raw(format!(
+                "<section><p><strong>{}</strong></p>",
+                html_escape::encode_text(&tab.label)
+            )));
+            res.extend(tab.content);
+            res.extend(raw("</section>".to_string()));
+        }
+        return Ok(res);
+    }
+
+    // The tab list is shown by js, without it the panels are stacked
+    // and the titles are used instead.
+    let mut tab_list =
+        format!(r#"<div class="tabs" id="{id}"><div class="tab-list" role="tablist" hidden>"#);
+    for (i, tab) in tabs.iter().enumerate() {
+        let selected = i == 0;
+        tab_list.push_str(&format!(
+            r#"<button type="button" role="tab" id="{id}-tab-{i}" aria-controls="{id}-panel-{i}" aria-selected="{selected}" tabindex="{}">{}</button>"#,
+            if selected { 0 } else { -1 },
+            html_escape::encode_text(&tab.label)
+        ));
+    }
+    tab_list.push_str("</div>");
+    res.extend(raw(tab_list));
+
+    for (i, tab) in tabs.into_iter().enumerate() {
+        res.extend(raw(format!(
+            r#"<section class="tab-panel" role="tabpanel" id="{id}-panel-{i}" aria-labelledby="{id}-tab-{i}"><div class="tab-title">{}</div>"#,
+            html_escape::encode_text(&tab.label)
+        )));
+        res.extend(tab.content);
+        res.extend(raw("</section>".to_string()));
+    }
+
+    res.extend(raw("</div>".to_string()));
+
+    Ok(res)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::markup::ParseContext;
+    use chrono::NaiveDate;
     use eyre::Result;
     use jotdown::{html, Parser, Render};
 
@@ -380,6 +691,14 @@ mod tests {
         Ok(body)
     }
 
+    fn convert_feed(s: &str) -> Result<String> {
+        let parser = Parser::new(s);
+        let transformed = DivTransforms::new(parser).in_feed(true);
+        let mut body = String::new();
+        html::Renderer::default().push(transformed, &mut body)?;
+        Ok(body)
+    }
+
     #[test]
     fn test_parse_note() -> Result<()> {
         let s = "::: note
@@ -616,8 +935,189 @@ Text
 
 After
 "#;
-        let s = convert(s)?;
-        assert!(s.contains(r#"<section class="changelog">"#));
+        let lookup = Rc::new(RefCell::new(MarkupLookup::new(s, 0)));
+        let transformed = DivTransforms::new(Parser::new(s)).with_lookup(lookup.clone());
+        let mut body = String::new();
+        html::Renderer::default().push(transformed, &mut body)?;
+        assert!(body.contains(r#"<section class="changelog" id="changelog">"#));
+
+        let changelog = lookup.borrow().changelog.clone().unwrap();
+        assert_eq!(changelog.items.len(), 3);
+        assert_eq!(
+            changelog.items[0].date,
+            NaiveDate::from_ymd_opt(2021, 9, 5).unwrap()
+        );
+        assert_eq!(changelog.items[0].description.trim(), "<p>First</p>");
+        assert_eq!(
+            changelog.items[2].description.trim(),
+            r#"<p><a href="/abc">Third</a></p>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_tabs_headings() -> Result<()> {
+        let s = r#"
+::: tabs
+# Rust
+
+```rust
+let x = 2;
+```
+
+# Lua
+
+Some *lua*
+:::"#;
+        let html = convert(s)?;
+        assert!(html.contains(
+            r#"<div class="tabs" id="tabs-1"><div class="tab-list" role="tablist" hidden><button type="button" role="tab" id="tabs-1-tab-0" aria-controls="tabs-1-panel-0" aria-selected="true" tabindex="0">Rust</button><button type="button" role="tab" id="tabs-1-tab-1" aria-controls="tabs-1-panel-1" aria-selected="false" tabindex="-1">Lua</button></div>"#
+        ));
+        assert!(html.contains(
+            r#"<section class="tab-panel" role="tabpanel" id="tabs-1-panel-1" aria-labelledby="tabs-1-tab-1"><div class="tab-title">Lua</div>
+<p>Some <strong>lua</strong></p>
+</section>"#
+        ));
+        assert!(!html.contains("<h1>"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tabs_divs() -> Result<()> {
+        let s = r#"
+:::: tabs
+::: fish
+Fish
+:::
+
+{label="Lua code"}
+::: lua
+Lua
+:::
+::::"#;
+        let html = convert(s)?;
+        assert!(html.contains(r#"aria-selected="true" tabindex="0">fish</button>"#));
+        assert!(html.contains(r#"aria-selected="false" tabindex="-1">Lua code</button>"#));
+        assert!(html.contains("<p>Lua</p>"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tabs_nested() -> Result<()> {
+        let s = r#"
+:::: tabs
+# Rust
+
+## Details
+
+::: note
+Inner
+:::
+
+# Lua
+
+Lua
+::::"#;
+        let res = crate::markup::djot::djot_to_html(s, ParseContext::default())?;
+        let html = res.html.0;
+        assert!(html.contains(r#"tabindex="0">Rust</button>"#));
+        assert!(html.contains(r#"tabindex="-1">Lua</button>"#));
+        assert!(!html.contains(">Details</button>"));
+        assert!(html.contains("Details</a></h3>"));
+        assert!(html.contains("<p>Inner</p>"));
+
+        let lookup = res.lookup.unwrap();
+        let headings: Vec<_> = lookup
+            .headings
+            .values()
+            .flatten()
+            .map(|h| h.heading.content.as_str())
+            .collect();
+        assert_eq!(headings, vec!["Details"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tabs_nested_divs() -> Result<()> {
+        let s = r#"
+::::: tabs
+:::: fish
+::: note
+Fish
+:::
+
+After
+::::
+
+::: lua
+Lua
+:::
+:::::"#;
+        let html = convert(s)?;
+        assert!(html.contains(r#"tabindex="0">fish</button>"#));
+        assert!(html.contains(r#"tabindex="-1">lua</button>"#));
+        assert!(html.contains("<p>After</p>\n</section>"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tabs_feed() -> Result<()> {
+        let s = r#"
+::: tabs
+# Rust
+
+First
+
+# Lua
+
+Second
+:::"#;
+        assert_eq!(
+            convert_feed(s)?,
+            r#"
+<section><p><strong>Rust</strong></p>
+<p>First</p>
+</section>
+<section><p><strong>Lua</strong></p>
+<p>Second</p>
+</section>
+"#
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_div_template() -> Result<()> {
+        let s = r#"
+{summary="More"}
+::: details
+Hidden _text_
+:::"#;
+        let templates = crate::tests::templates();
+        let transformed = DivTransforms::new(Parser::new(s)).with_templates(Some(templates));
+        let mut html = String::new();
+        html::Renderer::default().push(transformed, &mut html)?;
+        assert!(html
+            .contains("<details><summary>More</summary><p>Hidden <em>text</em></p>\n</details>"));
+        // Without templates the div is left as is.
+        assert!(convert(s)?.contains(r#"<div class="details">"#));
+
+        assert_eq!(
+            DivTransform::parse("details", Some(templates)),
+            Some(DivTransform::Template("details".into()))
+        );
+        assert_eq!(
+            DivTransform::templates(templates),
+            vec![DivTransform::Template("details".into())]
+        );
+        assert_eq!(DivTransform::parse("../site", Some(templates)), None);
+        assert_eq!(DivTransform::parse("site", Some(templates)), None);
+
         Ok(())
     }
 }
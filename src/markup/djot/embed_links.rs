@@ -1,24 +1,27 @@
-use crate::markup::embed_youtube::embed_yt;
+use crate::markup::embeds::{embed_link, EmbedCache};
 use jotdown::{Attributes, Container, Event};
 use std::iter::Peekable;
+use tracing::warn;
 
-pub struct EmbedYoutube<'a, I: Iterator<Item = Event<'a>>> {
+pub struct EmbedLinks<'a, 'b, I: Iterator<Item = Event<'a>>> {
     parent: Peekable<I>,
     event_queue: Vec<Event<'a>>,
     embed_thumbnail: bool,
+    cache: Option<&'b EmbedCache>,
 }
 
-impl<'a, I: Iterator<Item = Event<'a>>> EmbedYoutube<'a, I> {
-    pub fn new(parent: I, embed_thumbnail: bool) -> Self {
+impl<'a, 'b, I: Iterator<Item = Event<'a>>> EmbedLinks<'a, 'b, I> {
+    pub fn new(parent: I, embed_thumbnail: bool, cache: Option<&'b EmbedCache>) -> Self {
         Self {
             parent: parent.peekable(),
             event_queue: vec![],
             embed_thumbnail,
+            cache,
         }
     }
 }
 
-impl<'a, I: Iterator<Item = Event<'a>>> Iterator for EmbedYoutube<'a, I> {
+impl<'a, 'b, I: Iterator<Item = Event<'a>>> Iterator for EmbedLinks<'a, 'b, I> {
     type Item = Event<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -36,17 +39,19 @@ impl<'a, I: Iterator<Item = Event<'a>>> Iterator for EmbedYoutube<'a, I> {
             _ => return Some(start),
         };
 
-        let embedded = match embed_yt(text.as_ref(), self.embed_thumbnail)
-            .expect("Failed to process youtube link")
-        {
-            Some(x) => x,
-            None => return Some(start),
+        let embedded = match embed_link(text.as_ref(), self.embed_thumbnail, self.cache) {
+            Ok(Some(x)) => x,
+            Ok(None) => return Some(start),
+            Err(err) => {
+                warn!("Failed to embed link `{text}`: {err}");
+                return Some(start);
+            }
         };
 
-        // Consume the text and ending paragraph, which we should replace with the embedded video.
+        // Consume the text and ending paragraph, which we should replace with the embed.
         self.parent.next(); // Skip the Text
         if self.parent.next() != Some(Event::End(Container::Paragraph)) {
-            panic!("Bare Youtube link not ending with a paragraph");
+            panic!("Bare embedded link not ending with a paragraph");
         }
 
         let html = Container::RawBlock { format: "html" };
@@ -63,8 +68,16 @@ mod tests {
     use jotdown::{html, Parser, Render};
 
     fn convert(s: &str, embed_thumbnail: bool) -> Result<String> {
+        convert_with_cache(s, embed_thumbnail, None)
+    }
+
+    fn convert_with_cache(
+        s: &str,
+        embed_thumbnail: bool,
+        cache: Option<&EmbedCache>,
+    ) -> Result<String> {
         let parser = Parser::new(s);
-        let transformed = EmbedYoutube::new(parser, embed_thumbnail);
+        let transformed = EmbedLinks::new(parser, embed_thumbnail, cache);
         let mut body = String::new();
         html::Renderer::default().push(transformed, &mut body)?;
         Ok(body)
@@ -99,4 +112,24 @@ After";
 
         Ok(())
     }
+
+    #[test]
+    fn test_embed_cached_link() -> Result<()> {
+        let cache = EmbedCache::parse(
+            r#"
+["https://vimeo.com/76979871"]
+title = "The New Vimeo Player"
+"#,
+        )?;
+        let s = "https://vimeo.com/76979871";
+
+        let res = convert_with_cache(s, true, Some(&cache))?;
+        assert!(res.contains(r#"<iframe src="https://player.vimeo.com/video/76979871""#));
+
+        // Without the cache we don't know anything about the link, so leave it be.
+        let res = convert(s, true)?;
+        assert_eq!(res.trim_end(), "<p>https://vimeo.com/76979871</p>");
+
+        Ok(())
+    }
 }
@@ -1,5 +1,6 @@
 use camino::Utf8PathBuf;
 use jotdown::{Attributes, Container, Event, SpanLinkType};
+use std::cell::RefCell;
 use std::collections::HashSet;
 use std::fs;
 use tracing::warn;
@@ -9,11 +10,11 @@ use crate::paths::RelPath;
 pub struct EmbedSvg<'a, I: Iterator<Item = Event<'a>>> {
     parent: I,
     event_queue: Vec<Event<'a>>,
-    embedded_files: &'a mut HashSet<RelPath>,
+    embedded_files: &'a RefCell<HashSet<RelPath>>,
 }
 
 impl<'a, I: Iterator<Item = Event<'a>>> EmbedSvg<'a, I> {
-    pub fn new(parent: I, embedded_files: &'a mut HashSet<RelPath>) -> Self {
+    pub fn new(parent: I, embedded_files: &'a RefCell<HashSet<RelPath>>) -> Self {
         Self {
             parent,
             event_queue: vec![],
@@ -53,6 +54,7 @@ impl<'a, I: Iterator<Item = Event<'a>>> Iterator for EmbedSvg<'a, I> {
             .unwrap_or_else(|| src.to_string());
 
         self.embedded_files
+            .borrow_mut()
             .insert(RelPath(Utf8PathBuf::from(rel_src.to_string())));
 
         let path = Utf8PathBuf::from(rel_src);
@@ -0,0 +1,295 @@
+use crate::markup::markup_lookup::{Element, MarkupLookup};
+use crate::markup::{term_id, Glossary, ParseContext};
+use jotdown::{Attributes, Container, Event};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::ops::Range;
+use std::rc::Rc;
+
+/// Expand the first occurrence of each glossary term in a post to an `<abbr>`
+/// with the definition, linking to the term on the `/glossary` page.
+///
+/// Terms can also be marked explicitly with `[term]{.term}`, which are always expanded
+/// and are reported if they're missing from the glossary.
+pub struct GlossaryTerms<'a, I: Iterator<Item = (Event<'a>, Range<usize>)>> {
+    parent: I,
+    context: ParseContext<'a>,
+    lookup: Option<Rc<RefCell<MarkupLookup>>>,
+    event_queue: Vec<(Event<'a>, Range<usize>)>,
+    /// Terms that have already been expanded.
+    expanded: HashSet<&'a str>,
+    /// Depth of containers where terms shouldn't be expanded, such as links and headings.
+    skip_depth: usize,
+}
+
+impl<'a, I: Iterator<Item = (Event<'a>, Range<usize>)>> GlossaryTerms<'a, I> {
+    pub fn new(
+        parent: I,
+        context: ParseContext<'a>,
+        lookup: Option<Rc<RefCell<MarkupLookup>>>,
+    ) -> Self {
+        Self {
+            parent,
+            context,
+            lookup,
+            event_queue: vec![],
+            expanded: HashSet::new(),
+            skip_depth: 0,
+        }
+    }
+
+    fn register(&self, element: Element, range: Range<usize>) {
+        if let Some(lookup) = &self.lookup {
+            lookup.borrow_mut().insert_element(element, range);
+        }
+    }
+
+    fn push_html(
+        &mut self,
+        html: String,
+        range: Range<usize>,
+    ) -> Option<(Event<'a>, Range<usize>)> {
+        let container = Container::RawInline { format: "html" };
+        self.event_queue
+            .push((Event::End(container.clone()), range.clone()));
+        self.event_queue
+            .push((Event::Str(html.into()), range.clone()));
+        Some((Event::Start(container, Attributes::new()), range))
+    }
+
+    fn explicit_term(
+        &mut self,
+        glossary: &'a Glossary,
+        attrs: Attributes<'a>,
+        start: Range<usize>,
+    ) -> Option<(Event<'a>, Range<usize>)> {
+        let mut inner = Vec::new();
+        let mut text = String::new();
+        let mut depth = 0;
+        let end = loop {
+            match self.parent.next().expect("Span should be closed") {
+                (Event::End(Container::Span), end) if depth == 0 => break end,
+                (event, range) => {
+                    match &event {
+                        Event::Start(Container::Span, _) => depth += 1,
+                        Event::End(Container::Span) => depth -= 1,
+                        Event::Str(s) => text.push_str(s),
+                        _ => {}
+                    }
+                    inner.push((event, range));
+                }
+            }
+        };
+        let range = start.start..end.end;
+
+        match glossary.terms.get_key_value(text.as_str()) {
+            Some((term, definition)) => {
+                self.expanded.insert(term.as_str());
+                self.register(
+                    Element::GlossaryTerm {
+                        term: term.clone(),
+                        definition: definition.clone(),
+                    },
+                    range.clone(),
+                );
+                self.push_html(term_html(&text, term, definition), range)
+            }
+            None => {
+                self.context.log_undefined_term(&text);
+                self.register(Element::UndefinedTerm { term: text }, range);
+                self.event_queue.push((Event::End(Container::Span), end));
+                for event in inner.into_iter().rev() {
+                    self.event_queue.push(event);
+                }
+                Some((Event::Start(Container::Span, attrs), start))
+            }
+        }
+    }
+
+    fn implicit_terms(
+        &mut self,
+        glossary: &'a Glossary,
+        text: Cow<'a, str>,
+        range: Range<usize>,
+    ) -> Option<(Event<'a>, Range<usize>)> {
+        let mut events = Vec::new();
+        let mut last = 0;
+        for m in glossary.find_terms(&text) {
+            let (term, definition) = glossary
+                .terms
+                .get_key_value(m.as_str())
+                .expect("Matched terms should be in the glossary");
+
+            // Positions are only exact if the text hasn't been escaped or transformed,
+            // but it's good enough for hover info.
+            let term_range =
+                (range.start + m.start()).min(range.end)..(range.start + m.end()).min(range.end);
+            self.register(
+                Element::GlossaryTerm {
+                    term: term.clone(),
+                    definition: definition.clone(),
+                },
+                term_range.clone(),
+            );
+
+            if !self.expanded.insert(term.as_str()) {
+                continue;
+            }
+
+            if m.start() > last {
+                events.push((
+                    Event::Str(text[last..m.start()].to_string().into()),
+                    range.clone(),
+                ));
+            }
+            let html = Container::RawInline { format: "html" };
+            events.push((
+                Event::Start(html.clone(), Attributes::new()),
+                term_range.clone(),
+            ));
+            events.push((
+                Event::Str(term_html(m.as_str(), term, definition).into()),
+                term_range.clone(),
+            ));
+            events.push((Event::End(html), term_range));
+
+            last = m.end();
+        }
+
+        if events.is_empty() {
+            return Some((Event::Str(text), range));
+        }
+        if last < text.len() {
+            events.push((Event::Str(text[last..].to_string().into()), range));
+        }
+
+        for event in events.into_iter().rev() {
+            self.event_queue.push(event);
+        }
+        self.event_queue.pop()
+    }
+}
+
+impl<'a, I: Iterator<Item = (Event<'a>, Range<usize>)>> Iterator for GlossaryTerms<'a, I> {
+    type Item = (Event<'a>, Range<usize>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(event) = self.event_queue.pop() {
+            return Some(event);
+        }
+
+        let glossary = match self.context.glossary {
+            Some(glossary) => glossary,
+            None => return self.parent.next(),
+        };
+
+        let (event, range) = self.parent.next()?;
+        match event {
+            Event::Start(Container::Span, attrs) if is_term_span(&attrs) => {
+                self.explicit_term(glossary, attrs, range)
+            }
+            Event::Start(container, attrs) => {
+                if skip_terms(&container) {
+                    self.skip_depth += 1;
+                }
+                Some((Event::Start(container, attrs), range))
+            }
+            Event::End(container) => {
+                if skip_terms(&container) {
+                    self.skip_depth -= 1;
+                }
+                Some((Event::End(container), range))
+            }
+            Event::Str(text) if self.skip_depth == 0 => self.implicit_terms(glossary, text, range),
+            other => Some((other, range)),
+        }
+    }
+}
+
+fn is_term_span(attrs: &Attributes) -> bool {
+    attrs
+        .get("class")
+        .map(|class| class.to_string().split_whitespace().any(|x| x == "term"))
+        .unwrap_or(false)
+}
+
+/// Containers where implicit terms are left alone.
+fn skip_terms(container: &Container) -> bool {
+    matches!(
+        container,
+        Container::Heading { .. }
+            | Container::Link(..)
+            | Container::Image(..)
+            | Container::Verbatim
+            | Container::CodeBlock { .. }
+            | Container::RawBlock { .. }
+            | Container::RawInline { .. }
+            | Container::Math { .. }
+    )
+}
+
+fn term_html(text: &str, term: &str, definition: &str) -> String {
+    format!(
+        r#"<a class="glossary-term" href="/glossary#{}"><abbr title="{}">{}</abbr></a>"#,
+        html_escape::encode_double_quoted_attribute(&term_id(term)),
+        html_escape::encode_double_quoted_attribute(definition),
+        html_escape::encode_text(text)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markup::djot::drop_offset::DropOffset;
+    use eyre::Result;
+    use jotdown::{html, Parser, Render};
+
+    fn convert(s: &str, glossary: &Glossary) -> Result<(String, MarkupLookup)> {
+        let lookup = Rc::new(RefCell::new(MarkupLookup::new(s, 0)));
+        let context = ParseContext::default().with_glossary(glossary);
+        let parser = Parser::new(s).into_offset_iter();
+        let transformed = GlossaryTerms::new(parser, context, Some(lookup.clone()));
+        let transformed = DropOffset::new(transformed);
+        let mut body = String::new();
+        html::Renderer::default().push(transformed, &mut body)?;
+        let lookup = Rc::try_unwrap(lookup).unwrap().into_inner();
+        Ok((body, lookup))
+    }
+
+    #[test]
+    fn test_expand_first_term() -> Result<()> {
+        let glossary = Glossary::parse(r#"LSP = "Language Server Protocol""#)?;
+        let (html, lookup) = convert("An LSP is a [LSP](/x) thing, LSP again.", &glossary)?;
+        assert_eq!(
+            html,
+            r#"<p>An <a class="glossary-term" href="/glossary#lsp"><abbr title="Language Server Protocol">LSP</abbr></a> is a <a href="/x">LSP</a> thing, LSP again.</p>
+"#
+        );
+        assert_eq!(
+            lookup
+                .char_pos_to_element
+                .iter()
+                .filter(|(_, e)| matches!(e.element, Element::GlossaryTerm { .. }))
+                .count(),
+            2
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_explicit_terms() -> Result<()> {
+        let glossary = Glossary::parse(r#"LSP = "Language Server Protocol""#)?;
+        let (html, lookup) = convert("LSP and [LSP]{.term} and [MSP]{.term}", &glossary)?;
+        assert_eq!(
+            html,
+            r#"<p><a class="glossary-term" href="/glossary#lsp"><abbr title="Language Server Protocol">LSP</abbr></a> and <a class="glossary-term" href="/glossary#lsp"><abbr title="Language Server Protocol">LSP</abbr></a> and <span class="term">MSP</span></p>
+"#
+        );
+        assert!(lookup
+            .char_pos_to_element
+            .iter()
+            .any(|(_, e)| e.element == Element::UndefinedTerm { term: "MSP".into() }));
+        Ok(())
+    }
+}
@@ -0,0 +1,264 @@
+use eyre::{eyre, Result};
+use jotdown::{Attributes, Container, Event};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fs;
+use std::ops::Range;
+use std::rc::Rc;
+use tracing::warn;
+
+use crate::markup::djot::code_include::site_path;
+use crate::markup::graphviz::dot_to_svg;
+use crate::markup::markup_lookup::{Element, MarkupLookup};
+use crate::paths::RelPath;
+
+/// Render graphs in the `dot` language to inline svg, either from a code block:
+///
+///     {render=true}
+///     ```dot
+///     digraph { a -> b }
+///     ```
+///
+/// Or from an image linking to a file:
+///
+///     ![Payment flow](/graphviz/payment-flow.dot)
+///
+/// Graphs we fail to render are left as is and registered as errors in the lookup.
+pub struct Graphviz<'a, 'b, I: Iterator<Item = (Event<'a>, Range<usize>)>> {
+    parent: I,
+    event_queue: Vec<(Event<'a>, Range<usize>)>,
+    embedded_files: &'b RefCell<HashSet<RelPath>>,
+    lookup: Option<Rc<RefCell<MarkupLookup>>>,
+}
+
+impl<'a, 'b, I: Iterator<Item = (Event<'a>, Range<usize>)>> Graphviz<'a, 'b, I> {
+    pub fn new(
+        parent: I,
+        embedded_files: &'b RefCell<HashSet<RelPath>>,
+        lookup: Option<Rc<RefCell<MarkupLookup>>>,
+    ) -> Self {
+        Self {
+            parent,
+            event_queue: vec![],
+            embedded_files,
+            lookup,
+        }
+    }
+
+    /// Collect events until the end of the current container.
+    fn collect_inner(&mut self) -> (Vec<(Event<'a>, Range<usize>)>, (Event<'a>, Range<usize>)) {
+        let mut inner = Vec::new();
+        let mut depth = 0;
+        loop {
+            match self.parent.next().expect("Container should be closed") {
+                (Event::End(container), range) if depth == 0 => {
+                    return (inner, (Event::End(container), range))
+                }
+                (event, range) => {
+                    match &event {
+                        Event::Start(..) => depth += 1,
+                        Event::End(_) => depth -= 1,
+                        _ => {}
+                    }
+                    inner.push((event, range));
+                }
+            }
+        }
+    }
+
+    fn render(
+        &mut self,
+        start: (Event<'a>, Range<usize>),
+        inner: Vec<(Event<'a>, Range<usize>)>,
+        end: (Event<'a>, Range<usize>),
+        graph: Result<String>,
+        html: impl FnOnce(String) -> (Container<'a>, String),
+    ) -> Option<(Event<'a>, Range<usize>)> {
+        let range = start.1.start..end.1.end;
+        match graph {
+            Ok(svg) => {
+                let (container, html) = html(svg);
+                self.event_queue
+                    .push((Event::End(container.clone()), range.end..range.end));
+                self.event_queue
+                    .push((Event::Str(html.into()), range.clone()));
+                Some((
+                    Event::Start(container, Attributes::new()),
+                    range.start..range.start,
+                ))
+            }
+            Err(err) => {
+                warn!("Failed to render graph: {err}");
+                if let Some(lookup) = &self.lookup {
+                    lookup.borrow_mut().insert_element(
+                        Element::GraphvizError {
+                            message: err.to_string(),
+                        },
+                        range,
+                    );
+                }
+                self.event_queue.push(end);
+                for event in inner.into_iter().rev() {
+                    self.event_queue.push(event);
+                }
+                Some(start)
+            }
+        }
+    }
+}
+
+impl<'a, 'b, I: Iterator<Item = (Event<'a>, Range<usize>)>> Iterator for Graphviz<'a, 'b, I> {
+    type Item = (Event<'a>, Range<usize>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(event) = self.event_queue.pop() {
+            return Some(event);
+        }
+
+        match self.parent.next()? {
+            (Event::Start(Container::CodeBlock { language }, attrs), range)
+                if language == "dot" && should_render(&attrs) =>
+            {
+                let start = (
+                    Event::Start(Container::CodeBlock { language }, attrs),
+                    range,
+                );
+                let (inner, end) = self.collect_inner();
+                let dot: String = inner
+                    .iter()
+                    .filter_map(|(event, _)| match event {
+                        Event::Str(s) => Some(s.to_string()),
+                        _ => None,
+                    })
+                    .collect();
+                let graph = dot_to_svg(&dot);
+                self.render(start, inner, end, graph, |svg| {
+                    (
+                        Container::RawBlock { format: "html" },
+                        format!(r#"<figure class="graphviz">{svg}</figure>"#),
+                    )
+                })
+            }
+            (Event::Start(Container::Image(src, link_type), attrs), range)
+                if src.ends_with(".dot") =>
+            {
+                // Images typically include a leading `/` so strip it.
+                let path = site_path(src.strip_prefix('/').unwrap_or(&src));
+                if let Ok(path) = &path {
+                    self.embedded_files
+                        .borrow_mut()
+                        .insert(RelPath(path.clone()));
+                }
+
+                let start = (Event::Start(Container::Image(src, link_type), attrs), range);
+                let (inner, end) = self.collect_inner();
+                let alt: String = inner
+                    .iter()
+                    .filter_map(|(event, _)| match event {
+                        Event::Str(s) => Some(s.to_string()),
+                        _ => None,
+                    })
+                    .collect();
+                let graph = path.and_then(|path| {
+                    fs::read_to_string(&path)
+                        .map_err(|err| eyre!("Failed to read {path}: {err}"))
+                        .and_then(|dot| dot_to_svg(&dot))
+                });
+                self.render(start, inner, end, graph, |svg| {
+                    (
+                        Container::RawInline { format: "html" },
+                        format!(
+                            r#"<span class="graphviz" role="img" aria-label="{}">{svg}</span>"#,
+                            html_escape::encode_double_quoted_attribute(&alt)
+                        ),
+                    )
+                })
+            }
+            other => Some(other),
+        }
+    }
+}
+
+fn should_render(attrs: &Attributes) -> bool {
+    attrs
+        .get("render")
+        .map(|x| x.to_string() == "true")
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markup::djot::drop_offset::DropOffset;
+    use jotdown::{html, Parser, Render};
+
+    fn convert(s: &str) -> Result<(String, HashSet<RelPath>, MarkupLookup)> {
+        let lookup = Rc::new(RefCell::new(MarkupLookup::new(s, 0)));
+        let embedded_files = RefCell::new(HashSet::new());
+        let parser = Parser::new(s).into_offset_iter();
+        let transformed = Graphviz::new(parser, &embedded_files, Some(lookup.clone()));
+        let transformed = DropOffset::new(transformed);
+        let mut body = String::new();
+        html::Renderer::default().push(transformed, &mut body)?;
+        let lookup = Rc::try_unwrap(lookup).unwrap().into_inner();
+        Ok((body, embedded_files.into_inner(), lookup))
+    }
+
+    #[test]
+    fn test_render_code_block() -> Result<()> {
+        let s = r#"
+{render=true}
+```dot
+digraph { first -> second }
+```
+
+```dot
+digraph { not -> rendered }
+```
+"#;
+        let (html, _, _) = convert(s)?;
+        assert!(html.contains(r#"<figure class="graphviz"><svg"#));
+        assert!(html.contains("second"));
+        assert!(html.contains(r#"<pre><code class="language-dot">digraph { not -&gt; rendered }"#));
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_image() -> Result<()> {
+        let (html, embedded_files, _) = convert("![Payments](/graphviz/payment-flow.dot)")?;
+        assert!(
+            html.starts_with(r#"<p><span class="graphviz" role="img" aria-label="Payments"><svg"#)
+        );
+        assert!(embedded_files.contains(&RelPath("graphviz/payment-flow.dot".into())));
+        Ok(())
+    }
+
+    #[test]
+    fn test_image_outside_site() -> Result<()> {
+        let (html, embedded_files, lookup) = convert("![Secrets](/../secrets.dot)")?;
+        assert!(!html.contains("<svg"));
+        assert!(embedded_files.is_empty());
+        assert!(lookup
+            .char_pos_to_element
+            .iter()
+            .any(|(_, e)| matches!(e.element, Element::GraphvizError { .. })));
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_error() -> Result<()> {
+        let s = r#"
+{render=true}
+```dot
+digraph { a ->
+```
+"#;
+        let (html, _, lookup) = convert(s)?;
+        assert!(html.contains("<pre><code"));
+        assert!(lookup
+            .char_pos_to_element
+            .iter()
+            .any(|(_, e)| matches!(e.element, Element::GraphvizError { .. })));
+        Ok(())
+    }
+}
@@ -0,0 +1,200 @@
+use jotdown::{Attributes, Container, Event};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use tracing::warn;
+
+use crate::markup::keyboard_layout::KeyboardLayout;
+use crate::paths::RelPath;
+
+/// Render a layer of a layout in `keyboard_layouts/` as an inline svg:
+///
+///     {layout="cybershard" layer="_NUM" highlight="SE_J SE_P"}
+///     ::: keyboard
+///     ```
+///     SE_J SE_PLUS SE_ASTR ...
+///     ```
+///
+///     Numbers layer.
+///     :::
+///
+/// The code block holds the key codes in the order of the physical layout,
+/// without it the effort of each key is shown instead.
+/// Anything else in the div is used as the caption.
+pub struct KeyboardLayouts<'a, 'b, I: Iterator<Item = Event<'a>>> {
+    parent: I,
+    event_queue: Vec<Event<'a>>,
+    embedded_files: &'b RefCell<HashSet<RelPath>>,
+}
+
+impl<'a, 'b, I: Iterator<Item = Event<'a>>> KeyboardLayouts<'a, 'b, I> {
+    pub fn new(parent: I, embedded_files: &'b RefCell<HashSet<RelPath>>) -> Self {
+        Self {
+            parent,
+            event_queue: vec![],
+            embedded_files,
+        }
+    }
+}
+
+impl<'a, 'b, I: Iterator<Item = Event<'a>>> Iterator for KeyboardLayouts<'a, 'b, I> {
+    type Item = Event<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(event) = self.event_queue.pop() {
+            return Some(event);
+        }
+
+        let (class, attrs) = match self.parent.next()? {
+            Event::Start(Container::Div { class }, attrs) if class == "keyboard" => (class, attrs),
+            other => return Some(other),
+        };
+
+        let mut inner = Vec::new();
+        let end = loop {
+            match self.parent.next().expect("Div should be closed") {
+                Event::End(Container::Div { class: end_class }) if end_class == class => {
+                    break Event::End(Container::Div { class: end_class })
+                }
+                other => inner.push(other),
+            }
+        };
+
+        let layout = match attrs.get("layout") {
+            Some(layout) => layout.to_string(),
+            None => {
+                warn!("Missing `layout` attribute for keyboard");
+                return self.keep_div(class, attrs, inner, end);
+            }
+        };
+        if let Some(path) = KeyboardLayout::path(&layout) {
+            self.embedded_files.borrow_mut().insert(RelPath(path));
+        }
+
+        let mut keys = String::new();
+        let mut caption = Vec::new();
+        let mut in_code = false;
+        for event in inner.iter() {
+            match event {
+                Event::Start(Container::CodeBlock { .. }, _) => in_code = true,
+                Event::End(Container::CodeBlock { .. }) => in_code = false,
+                Event::Str(s) if in_code => keys.push_str(s),
+                Event::Blankline => {}
+                event if !in_code => caption.push(event.clone()),
+                _ => {}
+            }
+        }
+        let keys: Vec<_> = keys.split_whitespace().collect();
+
+        let layer = attrs
+            .get("layer")
+            .map(|x| x.to_string())
+            .unwrap_or_else(|| "default".into());
+        let highlight = attrs
+            .get("highlight")
+            .map(|x| x.to_string())
+            .unwrap_or_default();
+        let highlight: HashSet<_> = highlight.split_whitespace().collect();
+
+        let svg = match KeyboardLayout::load(&layout)
+            .and_then(|x| x.render_svg(&layout, &layer, &keys, &highlight))
+        {
+            Ok(svg) => svg,
+            Err(err) => {
+                warn!("Couldn't render keyboard layout `{layout}`: {err}");
+                return self.keep_div(class, attrs, inner, end);
+            }
+        };
+
+        let html = Container::RawBlock { format: "html" };
+        let raw = |s: String| {
+            [
+                Event::End(html.clone()),
+                Event::Str(s.into()),
+                Event::Start(html.clone(), Attributes::new()),
+            ]
+        };
+
+        // Pushed in reverse.
+        self.event_queue.extend(raw("</figure>".into()));
+        if !caption.is_empty() {
+            self.event_queue.extend(raw("</figcaption>".into()));
+            self.event_queue.extend(caption.into_iter().rev());
+            self.event_queue.extend(raw("<figcaption>".into()));
+        }
+        self.event_queue
+            .extend(raw(format!(r#"<figure class="keyboard-layout">{svg}"#)));
+        self.event_queue.pop()
+    }
+}
+
+impl<'a, 'b, I: Iterator<Item = Event<'a>>> KeyboardLayouts<'a, 'b, I> {
+    fn keep_div(
+        &mut self,
+        class: &'a str,
+        attrs: Attributes<'a>,
+        inner: Vec<Event<'a>>,
+        end: Event<'a>,
+    ) -> Option<Event<'a>> {
+        self.event_queue.push(end);
+        self.event_queue.extend(inner.into_iter().rev());
+        Some(Event::Start(Container::Div { class }, attrs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eyre::Result;
+    use jotdown::{html, Parser, Render};
+
+    fn convert(s: &str) -> Result<(String, HashSet<RelPath>)> {
+        let embedded_files = RefCell::new(HashSet::new());
+        let transformed = KeyboardLayouts::new(Parser::new(s), &embedded_files);
+        let mut body = String::new();
+        html::Renderer::default().push(transformed, &mut body)?;
+        Ok((body, embedded_files.into_inner()))
+    }
+
+    #[test]
+    fn test_keyboard_layout() -> Result<()> {
+        let s = r#"
+{layout="leftie" highlight="SE_J"}
+::: keyboard
+```
+SE_J SE_C SE_Y SE_F SE_P
+SE_R SE_S SE_T SE_H SE_K
+SE_X SE_V SE_G SE_D SE_B
+SE_A SE_E
+MT_SPC SE_O
+```
+
+The _base_ layer.
+:::
+"#;
+        let (html, embedded_files) = convert(s)?;
+        assert!(
+            html.starts_with("\n<figure class=\"keyboard-layout\"><svg class=\"keymap leftie\"")
+        );
+        assert!(html.contains(r#"<g class="keycap active_layer">"#));
+        assert!(html
+            .contains("<figcaption>\n<p>The <em>base</em> layer.</p>\n</figcaption>\n</figure>"));
+        assert!(embedded_files.contains(&RelPath("keyboard_layouts/leftie.json".into())));
+        Ok(())
+    }
+
+    #[test]
+    fn test_keyboard_layout_errors() -> Result<()> {
+        let s = r#"
+{layout="leftie"}
+::: keyboard
+```
+SE_J
+```
+:::
+"#;
+        let (html, _) = convert(s)?;
+        assert!(html.contains(r#"<div class="keyboard">"#));
+        assert!(!html.contains("<svg"));
+        Ok(())
+    }
+}
@@ -1,5 +1,5 @@
 use crate::markup::markup_lookup::{
-    Element, Heading, Img, ImgRef, Link, LinkDef, LinkRef, RawElementLookup,
+    Element, Heading, Img, ImgRef, Link, LinkDef, LinkRef, LinkTextKind, RawElementLookup,
 };
 use crate::markup::MarkupLookup;
 use crate::markup::ParseContext;
@@ -13,6 +13,8 @@ pub struct LookupRegister<'a, I: Iterator<Item = (Event<'a>, Range<usize>)>> {
     lookup: Rc<RefCell<MarkupLookup>>,
     context: ParseContext<'a>,
     event_stack: Vec<RawElementLookup>,
+    /// Start of the tables we're in, and if they have a header row.
+    table_stack: Vec<(usize, bool)>,
     src: &'a str,
 }
 
@@ -28,6 +30,7 @@ impl<'a, I: Iterator<Item = (Event<'a>, Range<usize>)>> LookupRegister<'a, I> {
             lookup,
             context,
             event_stack: Vec::new(),
+            table_stack: Vec::new(),
             src,
         }
     }
@@ -120,7 +123,15 @@ impl<'a, I: Iterator<Item = (Event<'a>, Range<usize>)>> Iterator for LookupRegis
                         img.link_ref = ImgRef::Reference { label, url };
                     }
 
+                    let alt_range = char_range.end..range.start;
                     char_range.end = range.end;
+                    insert_text(
+                        &mut lookup,
+                        LinkTextKind::Img,
+                        self.src,
+                        alt_range,
+                        &char_range,
+                    );
                     lookup.insert_img(img, char_range);
                 }
             }
@@ -198,7 +209,20 @@ impl<'a, I: Iterator<Item = (Event<'a>, Range<usize>)>> Iterator for LookupRegis
                         link.link_ref = LinkRef::Reference { label, url };
                     }
 
+                    let text_range = char_range.end..range.start;
                     char_range.end = range.end;
+                    if !matches!(
+                        link.link_ref,
+                        LinkRef::Email { .. } | LinkRef::AutoLink { .. }
+                    ) {
+                        insert_text(
+                            &mut lookup,
+                            LinkTextKind::Link,
+                            self.src,
+                            text_range,
+                            &char_range,
+                        );
+                    }
                     lookup.insert_link(link, char_range);
                 }
             }
@@ -226,12 +250,42 @@ impl<'a, I: Iterator<Item = (Event<'a>, Range<usize>)>> Iterator for LookupRegis
                     lookup.insert_link_def(link_def, char_range);
                 }
             }
+            (Event::Start(Container::Table, _), range) => {
+                self.table_stack.push((range.start, false));
+            }
+            (Event::Start(Container::TableRow { head: true }, _), _) => {
+                if let Some((_, has_header)) = self.table_stack.last_mut() {
+                    *has_header = true;
+                }
+            }
+            (Event::End(Container::Table), range) => {
+                if let Some((start, has_header)) = self.table_stack.pop() {
+                    lookup.insert_table(has_header, start..range.end);
+                }
+            }
             _ => {}
         }
         Some(next)
     }
 }
 
+/// Register the text of a link or image, falling back to the whole element if it's empty.
+fn insert_text(
+    lookup: &mut MarkupLookup,
+    kind: LinkTextKind,
+    src: &str,
+    text_range: Range<usize>,
+    element_range: &Range<usize>,
+) {
+    let text = src[text_range.clone()].to_string();
+    let range = if text.trim().is_empty() {
+        element_range.clone()
+    } else {
+        text_range
+    };
+    lookup.insert_link_text(kind, text, range);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -452,4 +506,35 @@ text");
             }),
         );
     }
+
+    #[test]
+    fn test_link_text_lookup() {
+        let lookup = gen("A [here](/a), ![](/b.png) and <https://c.se>");
+
+        let texts: Vec<_> = lookup
+            .link_texts
+            .iter()
+            .map(|x| (x.kind, x.text.as_str(), x.char_range.clone()))
+            .collect();
+        assert_eq!(
+            texts,
+            vec![
+                (LinkTextKind::Link, "here", 3..7),
+                (LinkTextKind::Img, "", 14..25),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_table_lookup() {
+        let lookup = gen("| a | b |
+|---|---|
+| 1 | 2 |
+
+| 1 | 2 |
+");
+
+        let tables: Vec<_> = lookup.tables.iter().map(|x| x.has_header).collect();
+        assert_eq!(tables, vec![true, false]);
+    }
 }
@@ -0,0 +1,145 @@
+use crate::markup::markup_lookup::{Element, MarkupLookup};
+use jotdown::{Attributes, Container, Event};
+use latex2mathml::{latex_to_mathml, DisplayStyle};
+use std::cell::RefCell;
+use std::ops::Range;
+use std::rc::Rc;
+use tracing::warn;
+
+/// Convert inline `$`...`$` and display `$$`...`$$` math to MathML,
+/// so it renders without any JavaScript.
+///
+/// Math we fail to convert is left as is and registered as an error
+/// in the lookup, if we have one.
+pub struct MathML<'a, I: Iterator<Item = (Event<'a>, Range<usize>)>> {
+    parent: I,
+    lookup: Option<Rc<RefCell<MarkupLookup>>>,
+    event_queue: Vec<(Event<'a>, Range<usize>)>,
+}
+
+impl<'a, I: Iterator<Item = (Event<'a>, Range<usize>)>> MathML<'a, I> {
+    pub fn new(parent: I, lookup: Option<Rc<RefCell<MarkupLookup>>>) -> Self {
+        Self {
+            parent,
+            lookup,
+            event_queue: vec![],
+        }
+    }
+}
+
+impl<'a, I: Iterator<Item = (Event<'a>, Range<usize>)>> Iterator for MathML<'a, I> {
+    type Item = (Event<'a>, Range<usize>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(event) = self.event_queue.pop() {
+            return Some(event);
+        }
+
+        let (start, start_range) = match self.parent.next()? {
+            (start @ Event::Start(Container::Math { .. }, _), range) => (start, range),
+            other => return Some(other),
+        };
+        let display = matches!(start, Event::Start(Container::Math { display: true }, _));
+
+        let mut inner = Vec::new();
+        let mut latex = String::new();
+        let end = loop {
+            match self.parent.next().expect("Math should be closed") {
+                end @ (Event::End(Container::Math { .. }), _) => break end,
+                (Event::Str(s), range) => {
+                    latex.push_str(&s);
+                    inner.push((Event::Str(s), range));
+                }
+                other => inner.push(other),
+            }
+        };
+
+        let style = if display {
+            DisplayStyle::Block
+        } else {
+            DisplayStyle::Inline
+        };
+
+        match latex_to_mathml(&latex, style) {
+            Ok(mathml) => {
+                let range = start_range.start..end.1.end;
+                let html = Container::RawInline { format: "html" };
+                self.event_queue
+                    .push((Event::End(html.clone()), range.end..range.end));
+                self.event_queue
+                    .push((Event::Str(mathml.into()), range.clone()));
+                Some((
+                    Event::Start(html, Attributes::new()),
+                    range.start..range.start,
+                ))
+            }
+            Err(err) => {
+                warn!("Failed to convert math `{latex}`: {err}");
+                if let Some(lookup) = &self.lookup {
+                    lookup.borrow_mut().insert_element(
+                        Element::MathError {
+                            message: err.to_string(),
+                        },
+                        start_range.start..end.1.end,
+                    );
+                }
+
+                self.event_queue.push(end);
+                for event in inner.into_iter().rev() {
+                    self.event_queue.push(event);
+                }
+                Some((start, start_range))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markup::djot::drop_offset::DropOffset;
+    use eyre::Result;
+    use jotdown::{html, Parser, Render};
+
+    fn convert(s: &str) -> Result<(String, MarkupLookup)> {
+        let lookup = Rc::new(RefCell::new(MarkupLookup::new(s, 0)));
+        let parser = Parser::new(s).into_offset_iter();
+        let transformed = MathML::new(parser, Some(lookup.clone()));
+        let transformed = DropOffset::new(transformed);
+        let mut body = String::new();
+        html::Renderer::default().push(transformed, &mut body)?;
+        let lookup = Rc::try_unwrap(lookup).unwrap().into_inner();
+        Ok((body, lookup))
+    }
+
+    #[test]
+    fn test_inline_math() -> Result<()> {
+        let (html, lookup) = convert("Area: $`\\pi r^2`")?;
+        assert!(html.starts_with("<p>Area: <math"));
+        assert!(html.contains("<msup>"));
+        assert!(!html.contains(r#"display="block""#));
+        assert!(lookup.char_pos_to_element.iter().next().is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_display_math() -> Result<()> {
+        let (html, _) = convert("$$`x^2`")?;
+        assert!(html.contains(r#"display="block""#));
+        assert!(!html.contains("class=\"math display\""));
+        Ok(())
+    }
+
+    #[test]
+    fn test_unsupported_math() -> Result<()> {
+        let (html, lookup) = convert("$`\\notacommand{x}`")?;
+        assert!(html.contains(r#"<span class="math inline">"#));
+        let errors: Vec<_> = lookup
+            .char_pos_to_element
+            .iter()
+            .map(|(_, e)| &e.element)
+            .collect();
+        assert!(matches!(errors[..], [Element::MathError { .. }]));
+        Ok(())
+    }
+}
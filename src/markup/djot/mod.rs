@@ -1,12 +1,23 @@
 mod auto_figures;
 mod changelog;
+mod charts;
+mod citations;
 mod code;
+mod code_include;
 mod div_transforms;
 mod drop_offset;
+mod embed_links;
 mod embed_svg;
-mod embed_youtube;
+mod glossary_terms;
+mod graphviz;
+mod keyboard_layouts;
 mod lookup_register;
+mod math;
+mod prose_lints;
 mod quote_transforms;
+mod sidenotes;
+mod source_text;
+mod spell_check;
 mod strip_elements;
 mod symbol_transforms;
 mod table_of_content;
@@ -15,17 +26,28 @@ mod transform_headers;
 
 use self::auto_figures::AutoFigures;
 use self::changelog::move_changelog;
+use self::charts::Charts;
+use self::citations::{move_references, Citations};
 use self::code::{CodeBlockSyntaxHighlight, InlineCodeSyntaxHighlight};
+use self::code_include::CodeIncludes;
 pub use self::div_transforms::DivTransform;
 use self::div_transforms::DivTransforms;
 use self::drop_offset::DropOffset;
+use self::embed_links::EmbedLinks;
 use self::embed_svg::EmbedSvg;
-use self::embed_youtube::EmbedYoutube;
+use self::glossary_terms::GlossaryTerms;
+use self::graphviz::Graphviz;
+use self::keyboard_layouts::KeyboardLayouts;
 use self::lookup_register::LookupRegister;
+use self::math::MathML;
+use self::prose_lints::ProseLints;
 use self::quote_transforms::QuoteTransforms;
+use self::sidenotes::Sidenotes;
+use self::spell_check::SpellCheck;
 use self::strip_elements::{StripElements, StripSymbols};
 pub use self::symbol_transforms::SymbolTransform;
 use self::symbol_transforms::SymbolTransforms;
+pub use self::table_of_content::{heading_tree, TocEntry};
 use self::table_of_content::{insert_toc, remove_toc};
 use self::todos::TransformTodoComments;
 use self::transform_headers::TransformHeaders;
@@ -42,56 +64,92 @@ pub fn djot_to_html(djot: &str, context: ParseContext) -> Result<HtmlParseRes> {
         context.markup_meta_line_count,
     )));
 
-    let mut embedded_files = HashSet::new();
+    let embedded_files = RefCell::new(HashSet::new());
 
     let transformed = Parser::new(djot).into_offset_iter();
 
     let transformed = LookupRegister::new(transformed, djot, lookup.clone(), context);
+    let transformed = SpellCheck::new(transformed, context.spelling, lookup.clone());
+    let transformed = ProseLints::new(
+        transformed,
+        context.prose_lint,
+        context.lint_rules,
+        lookup.clone(),
+    );
     let transformed = TransformTodoComments::new(transformed, context, lookup.clone());
+    let transformed = MathML::new(transformed, Some(lookup.clone()));
+    let transformed = Citations::new(transformed, context, Some(lookup.clone()));
+    let transformed = GlossaryTerms::new(transformed, context, Some(lookup.clone()));
+    let transformed = CodeIncludes::new(
+        transformed,
+        context.repository,
+        &embedded_files,
+        Some(lookup.clone()),
+    );
+    let transformed = Graphviz::new(transformed, &embedded_files, Some(lookup.clone()));
+    let transformed = Charts::new(transformed, &embedded_files, Some(lookup.clone()));
+    let transformed = Sidenotes::new(transformed, context.id_prefix, Some(lookup.clone()));
     let transformed = DropOffset::new(transformed);
 
     let transformed = TransformHeaders::new(transformed);
     let transformed = AutoFigures::new(transformed);
-    let transformed = EmbedSvg::new(transformed, &mut embedded_files);
-    let transformed = EmbedYoutube::new(transformed, true);
-    let transformed = CodeBlockSyntaxHighlight::new(transformed);
+    let transformed = EmbedSvg::new(transformed, &embedded_files);
+    let transformed = EmbedLinks::new(transformed, true, context.embed_cache);
+    let transformed = KeyboardLayouts::new(transformed, &embedded_files);
+    let transformed = CodeBlockSyntaxHighlight::new(transformed).with_id_prefix(context.id_prefix);
     let transformed = InlineCodeSyntaxHighlight::new(transformed);
-    let transformed = DivTransforms::new(transformed);
+    let transformed = DivTransforms::new(transformed)
+        .with_lookup(lookup.clone())
+        .with_templates(context.templates);
     let transformed = SymbolTransforms::new(transformed);
     let transformed = QuoteTransforms::new(transformed);
 
     let mut body = String::new();
     Renderer::default().push(transformed, &mut body)?;
 
-    let lookup = Rc::try_unwrap(lookup)
+    let mut lookup = Rc::try_unwrap(lookup)
         .expect("Should be able to unwrap lookup")
         .into_inner();
 
-    body = insert_toc(&body, &lookup).to_string();
+    body = insert_toc(&body, &mut lookup).to_string();
+    body = move_references(&body).to_string();
     body = move_changelog(&body).to_string();
 
     Ok(HtmlParseRes {
         html: Html(body),
         lookup: Some(lookup),
-        embedded_files,
+        embedded_files: embedded_files.into_inner(),
     })
 }
 
-pub fn djot_to_html_feed(djot: &str) -> Result<markup::FeedHtml> {
-    let transformed = Parser::new(djot);
+pub fn djot_to_html_feed(djot: &str, context: ParseContext) -> Result<markup::FeedHtml> {
+    let embedded_files = RefCell::new(HashSet::new());
+
+    let transformed = Parser::new(djot).into_offset_iter();
+    let transformed = MathML::new(transformed, None);
+    let transformed = Citations::new(transformed, context, None);
+    let transformed = GlossaryTerms::new(transformed, context, None);
+    let transformed = CodeIncludes::new(transformed, context.repository, &embedded_files, None);
+    let transformed = Graphviz::new(transformed, &embedded_files, None);
+    let transformed = Charts::new(transformed, &embedded_files, None);
+    let transformed = DropOffset::new(transformed);
     let transformed = StripSymbols::new(transformed, ["table-of-content"].into());
     let transformed = TransformHeaders::new(transformed);
     let transformed = AutoFigures::new(transformed);
-    let transformed = EmbedYoutube::new(transformed, false);
-    let transformed = CodeBlockSyntaxHighlight::new(transformed);
+    let transformed = EmbedLinks::new(transformed, false, context.embed_cache);
+    let transformed = KeyboardLayouts::new(transformed, &embedded_files);
+    let transformed = CodeBlockSyntaxHighlight::new(transformed).with_id_prefix(context.id_prefix);
     let transformed = InlineCodeSyntaxHighlight::new(transformed);
-    let transformed = DivTransforms::new(transformed);
+    let transformed = DivTransforms::new(transformed)
+        .in_feed(true)
+        .with_templates(context.templates);
     let transformed = QuoteTransforms::new(transformed);
 
     let mut body = String::new();
     Renderer::default().push(transformed, &mut body)?;
 
     body = remove_toc(&body).to_string();
+    body = move_references(&body).to_string();
     body = move_changelog(&body).to_string();
 
     Ok(markup::FeedHtml(body))
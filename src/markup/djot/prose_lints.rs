@@ -0,0 +1,194 @@
+use super::source_text::SourceText;
+use crate::markup::markup_lookup::MarkupLookup;
+use crate::markup::{LintRule, ProseLint};
+use jotdown::{Container, Event, LinkType};
+use std::cell::RefCell;
+use std::ops::Range;
+use std::rc::Rc;
+
+/// Register prose lint warnings for the text of paragraphs and headings in the lookup,
+/// leaving the events as they are.
+pub struct ProseLints<'a, 'b, I: Iterator<Item = (Event<'a>, Range<usize>)>> {
+    parent: I,
+    prose_lint: Option<&'b ProseLint>,
+    rules: &'b [LintRule],
+    lookup: Rc<RefCell<MarkupLookup>>,
+    text: SourceText,
+    /// If we're inside a paragraph or heading.
+    in_block: bool,
+    /// Depth of containers whose text isn't prose, such as code.
+    skip_depth: usize,
+}
+
+impl<'a, 'b, I: Iterator<Item = (Event<'a>, Range<usize>)>> ProseLints<'a, 'b, I> {
+    pub fn new(
+        parent: I,
+        prose_lint: Option<&'b ProseLint>,
+        rules: &'b [LintRule],
+        lookup: Rc<RefCell<MarkupLookup>>,
+    ) -> Self {
+        Self {
+            parent,
+            prose_lint: prose_lint.filter(|_| !rules.is_empty()),
+            rules,
+            lookup,
+            text: SourceText::default(),
+            in_block: false,
+            skip_depth: 0,
+        }
+    }
+
+    fn flush(&mut self) {
+        if let Some(prose_lint) = self.prose_lint {
+            let mut lookup = self.lookup.borrow_mut();
+            for warning in prose_lint.lint(self.text.as_str(), self.rules) {
+                lookup.insert_prose_lint(
+                    warning.rule,
+                    warning.message,
+                    self.text.source_range(warning.range),
+                );
+            }
+        }
+        self.text.clear();
+    }
+}
+
+impl<'a, 'b, I: Iterator<Item = (Event<'a>, Range<usize>)>> Iterator for ProseLints<'a, 'b, I> {
+    type Item = (Event<'a>, Range<usize>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.parent.next()?;
+        if self.prose_lint.is_none() {
+            return Some(next);
+        }
+
+        match &next {
+            (Event::Start(Container::Paragraph | Container::Heading { .. }, _), _) => {
+                self.flush();
+                self.in_block = true;
+            }
+            (Event::End(Container::Paragraph | Container::Heading { .. }), _) => {
+                self.flush();
+                self.in_block = false;
+            }
+            _ if !self.in_block => {}
+            (Event::Start(container, _), range) if is_skipped(container) => {
+                // Keep a placeholder so words around code aren't seen as repeated.
+                if self.skip_depth == 0 {
+                    self.text.push("\u{FFFC}", range.clone());
+                }
+                self.skip_depth += 1;
+            }
+            (Event::End(container), _) if is_skipped(container) => {
+                self.skip_depth = self.skip_depth.saturating_sub(1);
+            }
+            _ if self.skip_depth > 0 => {}
+            (Event::Str(s), range) => self.text.push(s, range.clone()),
+            (Event::LeftSingleQuote | Event::RightSingleQuote, range) => {
+                self.text.push("'", range.clone())
+            }
+            (Event::LeftDoubleQuote | Event::RightDoubleQuote, range) => {
+                self.text.push("\"", range.clone())
+            }
+            (Event::Ellipsis, range) => self.text.push("...", range.clone()),
+            (Event::EnDash | Event::EmDash, range) => self.text.push("-", range.clone()),
+            (Event::Softbreak | Event::Hardbreak | Event::NonBreakingSpace, range) => {
+                self.text.push(" ", range.clone())
+            }
+            _ => {}
+        }
+        Some(next)
+    }
+}
+
+fn is_skipped(container: &Container) -> bool {
+    matches!(
+        container,
+        Container::Verbatim
+            | Container::RawInline { .. }
+            | Container::Math { .. }
+            | Container::Link(_, LinkType::AutoLink | LinkType::Email)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markup::markup_lookup::Element;
+    use jotdown::Parser;
+
+    #[test]
+    fn test_prose_lints() {
+        let prose_lint = ProseLint::parse(
+            r#"
+[weasel-words]
+words = ["very"]
+
+[product-names]
+Neovim = ["NeoVim"]
+"#,
+        )
+        .unwrap();
+        let s = "# Very _NeoVim_\n\nThe `the` the\nthe end, {=very=}.\n\n```\nvery\n```\n";
+        let lookup = Rc::new(RefCell::new(MarkupLookup::new(s, 0)));
+        let rules = [
+            LintRule::RepeatedWords,
+            LintRule::WeaselWords,
+            LintRule::ProductNames,
+        ];
+        let events: Vec<_> = ProseLints::new(
+            Parser::new(s).into_offset_iter(),
+            Some(&prose_lint),
+            &rules,
+            lookup.clone(),
+        )
+        .collect();
+        assert!(!events.is_empty());
+
+        let lookup = lookup.borrow();
+        let found: Vec<_> = lookup
+            .prose_lints
+            .iter()
+            .map(|lint| (lint.rule, &s[lint.char_range.clone()]))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (LintRule::WeaselWords, "Very"),
+                (LintRule::ProductNames, "NeoVim"),
+                (LintRule::RepeatedWords, "the\nthe"),
+                (LintRule::WeaselWords, "very"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_prose_lints_keep_elements() {
+        let prose_lint = ProseLint::parse("[weasel-words]\nwords = [\"very\"]\n").unwrap();
+        let s = "A very big term";
+        let lookup = Rc::new(RefCell::new(MarkupLookup::new(s, 0)));
+        let term = Element::GlossaryTerm {
+            term: "very big term".into(),
+            definition: "A term".into(),
+        };
+        lookup.borrow_mut().insert_element(term.clone(), 2..15);
+        let rules = [LintRule::WeaselWords];
+        let events: Vec<_> = ProseLints::new(
+            Parser::new(s).into_offset_iter(),
+            Some(&prose_lint),
+            &rules,
+            lookup.clone(),
+        )
+        .collect();
+        assert!(!events.is_empty());
+
+        let lookup = lookup.borrow();
+        assert_eq!(lookup.prose_lints.len(), 1);
+        let elements: Vec<_> = lookup
+            .char_pos_to_element
+            .iter()
+            .map(|(_, e)| (&e.element, e.char_range.clone()))
+            .collect();
+        assert_eq!(elements, vec![(&term, 2..15)]);
+    }
+}
@@ -0,0 +1,384 @@
+use jotdown::{Attributes, Container, Event};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::rc::Rc;
+use std::vec::IntoIter;
+
+use crate::markup::markup_lookup::{Element, MarkupLookup};
+use crate::markup::prefixed_id;
+
+/// Render footnotes as numbered sidenotes placed next to the reference,
+/// instead of collecting them as endnotes at the bottom of the page.
+///
+/// A sidenote is placed inside a paragraph, so footnotes with block content
+/// such as lists or code are kept as endnotes, sharing the numbering with the sidenotes.
+/// References to missing footnotes are dropped and registered as errors in the lookup.
+///
+/// Footnote definitions are usually written after their references,
+/// so we need to consume the whole document before we can output anything.
+///
+/// Several items may be rendered on the same page, such as the notes stream,
+/// so ids are prefixed with `id_prefix` to keep them unique.
+pub struct Sidenotes<'a> {
+    events: IntoIter<(Event<'a>, Range<usize>)>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Note {
+    Side(usize),
+    End(usize),
+}
+
+impl<'a> Sidenotes<'a> {
+    pub fn new<I: Iterator<Item = (Event<'a>, Range<usize>)>>(
+        mut parent: I,
+        id_prefix: Option<&str>,
+        lookup: Option<Rc<RefCell<MarkupLookup>>>,
+    ) -> Self {
+        let ids = NoteIds { prefix: id_prefix };
+        let mut body = Vec::new();
+        let mut definitions: HashMap<&'a str, Vec<(Event<'a>, Range<usize>)>> = HashMap::new();
+
+        while let Some((event, range)) = parent.next() {
+            match event {
+                Event::Start(Container::Footnote { label }, _) => {
+                    definitions.insert(label, footnote_content(&mut parent));
+                }
+                other => body.push((other, range)),
+            }
+        }
+
+        let mut notes: HashMap<&'a str, Note> = HashMap::new();
+        let mut endnotes = Vec::new();
+        let mut events = Vec::with_capacity(body.len());
+        for (event, range) in body {
+            let label = match event {
+                Event::FootnoteReference(label) => label,
+                other => {
+                    events.push((other, range));
+                    continue;
+                }
+            };
+
+            // Repeated references point to the same note.
+            if let Some(note) = notes.get(label) {
+                let html = match note {
+                    Note::Side(num) => ids.sidenote_number(*num),
+                    Note::End(num) => format!(
+                        r##"<a href="#{}" role="doc-noteref"><sup>{num}</sup></a>"##,
+                        ids.endnote(*num)
+                    ),
+                };
+                push_html(&mut events, html, &range);
+                continue;
+            }
+
+            let content = match definitions.remove(label) {
+                Some(content) => content,
+                None => {
+                    if let Some(lookup) = &lookup {
+                        lookup.borrow_mut().insert_element(
+                            Element::UndefinedFootnote {
+                                label: label.to_string(),
+                            },
+                            range,
+                        );
+                    }
+                    continue;
+                }
+            };
+
+            let num = notes.len() + 1;
+            if is_inline(&content) {
+                notes.insert(label, Note::Side(num));
+                push_html(
+                    &mut events,
+                    format!(
+                        concat!(
+                            "{number}",
+                            r#"<input type="checkbox" id="{id}" class="sidenote-toggle" />"#,
+                            r#"<span class="sidenote" role="note"><span class="sidenote-label">{num}</span> "#
+                        ),
+                        number = ids.sidenote_number(num),
+                        id = ids.sidenote(num),
+                        num = num
+                    ),
+                    &range,
+                );
+                events.extend(flatten_paragraphs(content));
+                push_html(&mut events, "</span>".to_string(), &range);
+            } else {
+                notes.insert(label, Note::End(num));
+                push_html(&mut events, ids.endnote_reference(num), &range);
+                endnotes.push((num, content, range));
+            }
+        }
+
+        if !endnotes.is_empty() {
+            let end = events.last().map(|(_, range)| range.end).unwrap_or(0);
+            push_block_html(
+                &mut events,
+                r#"<section role="doc-endnotes"><hr><ol>"#.to_string(),
+                &(end..end),
+            );
+            for (num, content, range) in endnotes {
+                push_block_html(
+                    &mut events,
+                    format!(r#"<li id="{}" value="{num}">"#, ids.endnote(num)),
+                    &range,
+                );
+                events.extend(content);
+                push_block_html(
+                    &mut events,
+                    format!(
+                        r##"<a href="#{}" role="doc-backlink">↩︎︎</a></li>"##,
+                        ids.endnote_ref(num)
+                    ),
+                    &range,
+                );
+            }
+            push_block_html(&mut events, "</ol></section>".to_string(), &(end..end));
+        }
+
+        Self {
+            events: events.into_iter(),
+        }
+    }
+}
+
+impl<'a> Iterator for Sidenotes<'a> {
+    type Item = (Event<'a>, Range<usize>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.events.next()
+    }
+}
+
+struct NoteIds<'a> {
+    prefix: Option<&'a str>,
+}
+
+impl NoteIds<'_> {
+    fn sidenote(&self, num: usize) -> String {
+        prefixed_id(self.prefix, &format!("sidenote-{num}"))
+    }
+
+    fn endnote(&self, num: usize) -> String {
+        prefixed_id(self.prefix, &format!("fn{num}"))
+    }
+
+    fn endnote_ref(&self, num: usize) -> String {
+        prefixed_id(self.prefix, &format!("fnref{num}"))
+    }
+
+    fn sidenote_number(&self, num: usize) -> String {
+        format!(
+            r#"<label for="{}" class="sidenote-number">{num}</label>"#,
+            self.sidenote(num)
+        )
+    }
+
+    fn endnote_reference(&self, num: usize) -> String {
+        format!(
+            r##"<a id="{}" href="#{}" role="doc-noteref"><sup>{num}</sup></a>"##,
+            self.endnote_ref(num),
+            self.endnote(num)
+        )
+    }
+}
+
+/// Collect the content of a footnote until its end.
+fn footnote_content<'a, I: Iterator<Item = (Event<'a>, Range<usize>)>>(
+    parent: &mut I,
+) -> Vec<(Event<'a>, Range<usize>)> {
+    let mut res = Vec::new();
+    for (event, range) in parent.by_ref() {
+        match event {
+            Event::End(Container::Footnote { .. }) => break,
+            other => res.push((other, range)),
+        }
+    }
+    res
+}
+
+/// If the footnote only contains paragraphs of text, so it can be placed inside a paragraph.
+fn is_inline(content: &[(Event, Range<usize>)]) -> bool {
+    content.iter().all(|(event, _)| match event {
+        Event::Start(container, _) => matches!(
+            container,
+            Container::Paragraph
+                | Container::Span
+                | Container::Link(..)
+                | Container::Image(..)
+                | Container::Verbatim
+                | Container::Math { .. }
+                | Container::RawInline { .. }
+                | Container::Subscript
+                | Container::Superscript
+                | Container::Insert
+                | Container::Delete
+                | Container::Strong
+                | Container::Emphasis
+                | Container::Mark
+        ),
+        Event::ThematicBreak(_) => false,
+        _ => true,
+    })
+}
+
+/// Flatten paragraphs into line breaks to keep the html valid inside a paragraph.
+fn flatten_paragraphs<'a>(
+    content: Vec<(Event<'a>, Range<usize>)>,
+) -> Vec<(Event<'a>, Range<usize>)> {
+    let mut res = Vec::new();
+    let mut paragraphs = 0;
+    for (event, range) in content {
+        match event {
+            Event::Start(Container::Paragraph, _) => {
+                if paragraphs > 0 {
+                    res.push((Event::HardBreak, range));
+                }
+                paragraphs += 1;
+            }
+            Event::End(Container::Paragraph) => {}
+            other => res.push((other, range)),
+        }
+    }
+    res
+}
+
+fn push_html<'a>(events: &mut Vec<(Event<'a>, Range<usize>)>, html: String, range: &Range<usize>) {
+    let raw = Container::RawInline { format: "html" };
+    events.push((Event::Start(raw.clone(), Attributes::new()), range.clone()));
+    events.push((Event::Str(html.into()), range.clone()));
+    events.push((Event::End(raw), range.clone()));
+}
+
+fn push_block_html<'a>(
+    events: &mut Vec<(Event<'a>, Range<usize>)>,
+    html: String,
+    range: &Range<usize>,
+) {
+    let raw = Container::RawBlock { format: "html" };
+    events.push((Event::Start(raw.clone(), Attributes::new()), range.clone()));
+    events.push((Event::Str(html.into()), range.clone()));
+    events.push((Event::End(raw), range.clone()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markup::djot::drop_offset::DropOffset;
+    use eyre::Result;
+    use jotdown::{html, Parser, Render};
+
+    fn convert(s: &str) -> Result<(String, MarkupLookup)> {
+        convert_with_prefix(s, None)
+    }
+
+    fn convert_with_prefix(s: &str, id_prefix: Option<&str>) -> Result<(String, MarkupLookup)> {
+        let lookup = Rc::new(RefCell::new(MarkupLookup::new(s, 0)));
+        let parser = Parser::new(s).into_offset_iter();
+        let transformed = Sidenotes::new(parser, id_prefix, Some(lookup.clone()));
+        let transformed = DropOffset::new(transformed);
+        let mut body = String::new();
+        html::Renderer::default().push(transformed, &mut body)?;
+        let lookup = Rc::try_unwrap(lookup).unwrap().into_inner();
+        Ok((body, lookup))
+    }
+
+    #[test]
+    fn test_sidenotes() -> Result<()> {
+        let s = r#"Text[^note] and more[^other] and again[^note].
+
+[^note]: My *note*
+
+[^other]: First
+
+    Second
+"#;
+        let (html, _) = convert(s)?;
+        assert!(!html.contains("doc-endnotes"));
+        assert!(html.contains(
+            r#"Text<label for="sidenote-1" class="sidenote-number">1</label><input type="checkbox" id="sidenote-1" class="sidenote-toggle" /><span class="sidenote" role="note"><span class="sidenote-label">1</span> My <strong>note</strong></span> and more"#
+        ));
+        assert!(html.contains(r#"<span class="sidenote-label">2</span> First<br>"#));
+        assert!(html.contains("Second</span>"));
+        assert!(html.contains(r#"again<label for="sidenote-1" class="sidenote-number">1</label>."#));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_block_footnotes() -> Result<()> {
+        let s = r#"Side[^side] and list[^list] and again[^list].
+
+[^side]: Short
+
+[^list]: A list:
+
+    - One
+    - Two
+"#;
+        let (html, _) = convert(s)?;
+        assert!(html.contains(r#"<span class="sidenote-label">1</span> Short</span>"#));
+        assert!(html.contains(
+            r##"list<a id="fnref2" href="#fn2" role="doc-noteref"><sup>2</sup></a> and again<a href="#fn2" role="doc-noteref"><sup>2</sup></a>."##
+        ));
+        let endnotes = html
+            .find(r#"<section role="doc-endnotes"><hr><ol>"#)
+            .expect("Should have endnotes");
+        assert!(html[endnotes..].contains(r#"<li id="fn2" value="2">"#));
+        assert!(html[endnotes..].contains("One"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_undefined_footnote() -> Result<()> {
+        let s = "Text[^missing].\n";
+        let (html, lookup) = convert(s)?;
+        assert_eq!(html, "<p>Text.</p>\n");
+        let elements: Vec<_> = lookup
+            .char_pos_to_element
+            .iter()
+            .map(|(_, e)| (&e.element, &s[e.char_range.clone()]))
+            .collect();
+        assert_eq!(
+            elements,
+            vec![(
+                &Element::UndefinedFootnote {
+                    label: "missing".into()
+                },
+                "[^missing]"
+            )]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_prefixed_ids() -> Result<()> {
+        let s = r#"Side[^side] and list[^list].
+
+[^side]: Short
+
+[^list]: A list:
+
+    - One
+"#;
+        let (html, _) = convert_with_prefix(s, Some("my_note"))?;
+        assert!(
+            html.contains(r#"<label for="my_note-sidenote-1" class="sidenote-number">1</label>"#)
+        );
+        assert!(html.contains(r#"id="my_note-sidenote-1" class="sidenote-toggle""#));
+        assert!(html.contains(
+            r##"<a id="my_note-fnref2" href="#my_note-fn2" role="doc-noteref"><sup>2</sup></a>"##
+        ));
+        assert!(html.contains(r#"<li id="my_note-fn2" value="2">"#));
+        assert!(html.contains(r##"<a href="#my_note-fnref2" role="doc-backlink">"##));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+use std::ops::Range;
+
+/// Text joined from several events, that can be mapped back to the source.
+#[derive(Debug, Default)]
+pub struct SourceText {
+    text: String,
+    /// Where each pushed piece starts in `text`, and its range in the source.
+    segments: Vec<(usize, Range<usize>)>,
+}
+
+impl SourceText {
+    pub fn push(&mut self, text: &str, range: Range<usize>) {
+        self.segments.push((self.text.len(), range));
+        self.text.push_str(text);
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.segments.clear();
+    }
+
+    /// Map `range` in the text to a range in the source.
+    pub fn source_range(&self, range: Range<usize>) -> Range<usize> {
+        let start = self.source_pos(range.start);
+        if range.is_empty() {
+            return start..start;
+        }
+        // The end is outside the piece it belongs to, so map the last byte instead.
+        let end = self.source_pos(range.end - 1) + 1;
+        start..end.max(start)
+    }
+
+    fn source_pos(&self, pos: usize) -> usize {
+        match self
+            .segments
+            .iter()
+            .rev()
+            .find(|(text_start, _)| *text_start <= pos)
+        {
+            // Pieces that differ from the source, such as smart quotes, are clamped to their range.
+            Some((text_start, source)) => {
+                source.start + (pos - text_start).min(source.len().saturating_sub(1))
+            }
+            None => pos,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_range() {
+        // Source: `don’t _stop_`
+        let mut text = SourceText::default();
+        text.push("don", 0..3);
+        text.push("'", 3..6);
+        text.push("t ", 6..8);
+        text.push("stop", 9..13);
+        assert_eq!(text.as_str(), "don't stop");
+        assert_eq!(text.source_range(0..5), 0..7);
+        assert_eq!(text.source_range(6..10), 9..13);
+        assert_eq!(text.source_range(3..3), 3..3);
+    }
+}
@@ -0,0 +1,160 @@
+use super::source_text::SourceText;
+use crate::markup::markup_lookup::MarkupLookup;
+use crate::markup::SpellChecker;
+use jotdown::{Container, Event};
+use std::cell::RefCell;
+use std::ops::Range;
+use std::rc::Rc;
+
+/// Register misspelled words in the lookup, leaving the events as they are.
+///
+/// Code, math, raw html and link labels are skipped.
+pub struct SpellCheck<'a, 'b, I: Iterator<Item = (Event<'a>, Range<usize>)>> {
+    parent: I,
+    checker: Option<&'b SpellChecker>,
+    lookup: Rc<RefCell<MarkupLookup>>,
+    /// Text of consecutive text events, such as `don`, `'` and `t`.
+    text: SourceText,
+    /// Depth of containers where words shouldn't be checked.
+    skip_depth: usize,
+}
+
+impl<'a, 'b, I: Iterator<Item = (Event<'a>, Range<usize>)>> SpellCheck<'a, 'b, I> {
+    pub fn new(
+        parent: I,
+        checker: Option<&'b SpellChecker>,
+        lookup: Rc<RefCell<MarkupLookup>>,
+    ) -> Self {
+        Self {
+            parent,
+            checker: checker.filter(|checker| checker.is_enabled()),
+            lookup,
+            text: SourceText::default(),
+            skip_depth: 0,
+        }
+    }
+
+    fn push_str(&mut self, text: &str, range: &Range<usize>) {
+        // Escapes don't map cleanly back to the source.
+        if text.len() != range.len() {
+            self.flush();
+            return;
+        }
+        self.text.push(text, range.clone());
+    }
+
+    fn flush(&mut self) {
+        if let Some(checker) = self.checker {
+            let mut lookup = self.lookup.borrow_mut();
+            for (range, word) in checker.misspellings(self.text.as_str()) {
+                lookup.insert_misspelling(word.to_string(), self.text.source_range(range));
+            }
+        }
+        self.text.clear();
+    }
+}
+
+impl<'a, 'b, I: Iterator<Item = (Event<'a>, Range<usize>)>> Iterator for SpellCheck<'a, 'b, I> {
+    type Item = (Event<'a>, Range<usize>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = match self.parent.next() {
+            Some(next) => next,
+            None => {
+                self.flush();
+                return None;
+            }
+        };
+        if self.checker.is_none() {
+            return Some(next);
+        }
+
+        match &next {
+            (Event::Start(container, _), _) if is_skipped(container) => {
+                self.flush();
+                self.skip_depth += 1;
+            }
+            (Event::End(container), _) if is_skipped(container) => {
+                self.skip_depth = self.skip_depth.saturating_sub(1);
+            }
+            (Event::Str(s), range) if self.skip_depth == 0 => self.push_str(s, range),
+            (Event::LeftSingleQuote | Event::RightSingleQuote, range) if self.skip_depth == 0 => {
+                self.text.push("'", range.clone())
+            }
+            _ => self.flush(),
+        }
+        Some(next)
+    }
+}
+
+fn is_skipped(container: &Container) -> bool {
+    matches!(
+        container,
+        Container::Verbatim
+            | Container::CodeBlock { .. }
+            | Container::RawBlock { .. }
+            | Container::RawInline { .. }
+            | Container::Math { .. }
+            | Container::Link(..)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markup::markup_lookup::Element;
+    use jotdown::Parser;
+
+    const AFF: &str = "SET UTF-8\n";
+    const DIC: &str = "6\na\nwith\nlink\nisn't\ncode\nhere\n";
+
+    fn misspellings(s: &str) -> Vec<(String, String)> {
+        let lookup = Rc::new(RefCell::new(MarkupLookup::new(s, 0)));
+        spell_check(s, lookup.clone());
+        let lookup = lookup.borrow();
+        lookup
+            .misspellings
+            .iter()
+            .map(|m| (m.word.clone(), s[m.char_range.clone()].to_string()))
+            .collect()
+    }
+
+    fn spell_check(s: &str, lookup: Rc<RefCell<MarkupLookup>>) {
+        let checker = SpellChecker::new(AFF, DIC, "").unwrap();
+        let events: Vec<_> =
+            SpellCheck::new(Parser::new(s).into_offset_iter(), Some(&checker), lookup).collect();
+        assert!(!events.is_empty());
+    }
+
+    #[test]
+    fn test_misspellings() {
+        assert_eq!(
+            misspellings("Here isn't a lnik with `cdoe`, <https://exmaple.com> or [a lbael](/url)"),
+            vec![
+                ("lnik".to_string(), "lnik".to_string()),
+                ("or".to_string(), "or".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_misspellings_keep_elements() {
+        let s = "A lnik here";
+        let lookup = Rc::new(RefCell::new(MarkupLookup::new(s, 0)));
+        let term = Element::GlossaryTerm {
+            term: "lnik here".into(),
+            definition: "A term".into(),
+        };
+        lookup.borrow_mut().insert_element(term.clone(), 2..11);
+        spell_check(s, lookup.clone());
+
+        let lookup = lookup.borrow();
+        assert_eq!(lookup.misspellings.len(), 1);
+        let elements: Vec<_> = lookup
+            .char_pos_to_element
+            .iter()
+            .map(|(_, e)| (&e.element, e.char_range.clone()))
+            .collect();
+        assert_eq!(elements, vec![(&term, 2..11)]);
+    }
+}
@@ -1,8 +1,7 @@
-use crate::markup::graphs::PostStatsGraph;
-use chrono::NaiveDate;
 use eyre::Result;
 use jotdown::{Attributes, Container, Event};
 use serde::Serialize;
+use std::collections::BTreeMap;
 use std::iter::Peekable;
 use tracing::error;
 
@@ -70,12 +69,20 @@ impl<'a, I: Iterator<Item = Event<'a>>> Iterator for SymbolTransforms<'a, I> {
 #[derive(Debug, PartialEq, Eq, Serialize)]
 pub enum SymbolTransform {
     PostStatsGraph,
+    RecentPosts,
+    TagCloud,
+    SeriesList,
+    FavoritePosts,
 }
 
 impl SymbolTransform {
     pub fn parse(id: &str) -> Option<Self> {
         match id {
             "post-stats-graph" => Some(Self::PostStatsGraph),
+            "recent-posts" => Some(Self::RecentPosts),
+            "tag-cloud" => Some(Self::TagCloud),
+            "series-list" => Some(Self::SeriesList),
+            "favorite-posts" => Some(Self::FavoritePosts),
             _ => None,
         }
     }
@@ -83,13 +90,15 @@ impl SymbolTransform {
     pub fn as_str(&self) -> &'static str {
         match self {
             Self::PostStatsGraph => "post-stats-graph",
+            Self::RecentPosts => "recent-posts",
+            Self::TagCloud => "tag-cloud",
+            Self::SeriesList => "series-list",
+            Self::FavoritePosts => "favorite-posts",
         }
     }
 
     fn try_transform<'a>(self, attrs: &Attributes) -> Result<Vec<Event<'a>>> {
-        match self {
-            Self::PostStatsGraph => create_post_stats_graph(attrs),
-        }
+        create_site_symbol_placeholder(self.as_str(), attrs)
     }
 
     fn transform<'a>(self, attrs: &Attributes) -> Vec<Event<'a>> {
@@ -103,27 +112,23 @@ impl SymbolTransform {
     }
 }
 
-fn create_post_stats_graph<'a>(attrs: &Attributes) -> Result<Vec<Event<'a>>> {
-    let before_date_filter = if let Some(date) = attrs.get("before_date") {
-        Some(NaiveDate::parse_from_str(
-            date.to_string().as_str(),
-            "%Y-%m-%d",
-        )?)
-    } else {
-        None
-    };
-
-    let graph = PostStatsGraph {
-        before_date: before_date_filter,
-        caption: attrs.get("caption").map(|x| x.to_string()),
-    }
-    .generate()?;
+/// Site content isn't available while parsing markup,
+/// so leave a placeholder that `content::render_site_symbols` replaces during rendering.
+fn create_site_symbol_placeholder<'a>(symbol: &str, attrs: &Attributes) -> Result<Vec<Event<'a>>> {
+    let attrs: BTreeMap<String, String> = attrs
+        .unique_pairs()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+    let placeholder = format!(
+        "<!-- SITE_SYMBOL {symbol} {} -->",
+        serde_json::to_string(&attrs)?
+    );
 
     let html = Container::RawBlock { format: "html" };
 
     Ok(vec![
         Event::Start(html.clone(), Attributes::new()),
-        Event::Str(graph.into()),
+        Event::Str(placeholder.into()),
         Event::End(html),
     ])
 }
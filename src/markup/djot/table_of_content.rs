@@ -1,43 +1,99 @@
 use crate::markup::djot::djot_to_html_stripped;
-use crate::markup::markup_lookup::{Element, MarkupLookup};
+use crate::markup::markup_lookup::{Element, MarkupLookup, TocOptions};
 use lazy_static::lazy_static;
 use regex::{Captures, Regex};
+use serde::Serialize;
 use std::borrow::Cow;
 
-pub fn insert_toc<'a>(body: &'a str, lookup: &MarkupLookup) -> Cow<'a, str> {
+/// A heading in the heading tree of a post.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TocEntry {
+    pub id: String,
+    pub level: u16,
+    /// The heading content rendered as html, without links.
+    pub title: String,
+    /// The section number, such as `2.1`.
+    pub number: String,
+    pub children: Vec<TocEntry>,
+}
+
+/// Build a tree of all headings down to `depth`, nesting each heading
+/// below the closest preceding heading of a lower level.
+pub fn heading_tree(lookup: &MarkupLookup, depth: u16) -> Vec<TocEntry> {
+    let mut res = Vec::new();
+    for (_, x) in lookup.char_pos_to_element.iter() {
+        if let Element::Heading(ref heading) = x.element {
+            if heading.level > depth {
+                continue;
+            }
+            let title = djot_to_html_stripped(&format!("# {}", heading.content))
+                .expect("Failed to render Djot")
+                .0
+                .trim()
+                .to_string();
+            push_entry(
+                &mut res,
+                TocEntry {
+                    id: heading.id.clone(),
+                    level: heading.level,
+                    title,
+                    number: String::new(),
+                    children: Vec::new(),
+                },
+            );
+        }
+    }
+    number_entries(&mut res, "");
+    res
+}
+
+fn push_entry(entries: &mut Vec<TocEntry>, entry: TocEntry) {
+    match entries.last_mut() {
+        Some(last) if last.level < entry.level => push_entry(&mut last.children, entry),
+        _ => entries.push(entry),
+    }
+}
+
+fn number_entries(entries: &mut [TocEntry], prefix: &str) {
+    for (i, entry) in entries.iter_mut().enumerate() {
+        entry.number = if prefix.is_empty() {
+            (i + 1).to_string()
+        } else {
+            format!("{prefix}.{}", i + 1)
+        };
+        number_entries(&mut entry.children, &entry.number);
+    }
+}
+
+/// Replace the `:table-of-content:` symbol with the heading tree
+/// and register its options in the lookup.
+pub fn insert_toc<'a>(body: &'a str, lookup: &mut MarkupLookup) -> Cow<'a, str> {
     // Use a regex instead of relying on the parser because it might get offset in the output,
     // and we need to do a pass first to collect all headings before we insert the toc.
+    let caps = match TOC_SYMBOL.captures(body) {
+        Some(caps) => caps,
+        None => return Cow::Borrowed(body),
+    };
+    let opts = parse_options(&caps[1]);
+    lookup.toc = Some(opts);
+
+    if opts.sidebar {
+        return TOC_SYMBOL.replace(body, "");
+    }
+
+    let entries = heading_tree(lookup, opts.depth);
     TOC_SYMBOL.replace(body, |_caps: &Captures| {
-        let mut res = r#"<div class="toc">
+        let class = if opts.numbered { "toc numbered" } else { "toc" };
+        let mut res = format!(
+            r#"<div class="{class}">
   <details>
     <summary>Contents</summary>
     <nav>
-      <ol>
 "#
-        .to_string();
-
-        for (_, x) in lookup.char_pos_to_element.iter() {
-            if let Element::Heading(ref heading) = x.element {
-                let class = match heading.level {
-                    1 => "heading-1",
-                    2 => "heading-2",
-                    _ => continue,
-                };
-                res.push_str(&format!(
-                    r##"<li><a href="#{}" class="{}">{}</a></li>
-"##,
-                    heading.id,
-                    class,
-                    djot_to_html_stripped(&format!("# {}", heading.content))
-                        .expect("Failed to render Djot")
-                        .0
-                ));
-            }
-        }
-
+        );
+        push_entries(&entries, opts.numbered, &mut res);
         res.push_str(
-            r#"      </ol>
-    </nav>
+            r#"    </nav>
   </details>
 </div>"#,
         );
@@ -45,12 +101,52 @@ pub fn insert_toc<'a>(body: &'a str, lookup: &MarkupLookup) -> Cow<'a, str> {
     })
 }
 
+fn push_entries(entries: &[TocEntry], numbered: bool, res: &mut String) {
+    res.push_str("<ol>\n");
+    for entry in entries {
+        let number = if numbered {
+            format!(r#"<span class="toc-number">{}</span> "#, entry.number)
+        } else {
+            String::new()
+        };
+        res.push_str(&format!(
+            r##"<li><a href="#{}" class="heading-{}">{number}{}</a>"##,
+            entry.id, entry.level, entry.title
+        ));
+        if !entry.children.is_empty() {
+            res.push('\n');
+            push_entries(&entry.children, numbered, res);
+        }
+        res.push_str("</li>\n");
+    }
+    res.push_str("</ol>\n");
+}
+
+fn parse_options(attrs: &str) -> TocOptions {
+    let mut opts = TocOptions::default();
+    for caps in TOC_ATTR.captures_iter(attrs) {
+        match &caps[1] {
+            "depth" => {
+                if let Ok(depth) = caps[2].parse() {
+                    opts.depth = depth;
+                }
+            }
+            "numbered" => opts.numbered = &caps[2] != "false",
+            "sidebar" => opts.sidebar = &caps[2] != "false",
+            _ => {}
+        }
+    }
+    opts
+}
+
 pub fn remove_toc(body: &str) -> Cow<'_, str> {
     TOC_SYMBOL.replace(body, "")
 }
 
 lazy_static! {
-    pub static ref TOC_SYMBOL: Regex = Regex::new(r"(<p>:table-of-content:</p>)").unwrap();
+    pub static ref TOC_SYMBOL: Regex =
+        Regex::new(r#"<p((?:\s+[\w-]+="[^"]*")*)>:table-of-content:</p>"#).unwrap();
+    static ref TOC_ATTR: Regex = Regex::new(r#"([\w-]+)="([^"]*)""#).unwrap();
 }
 
 #[cfg(test)]
@@ -64,7 +160,7 @@ mod tests {
     use std::cell::RefCell;
     use std::rc::Rc;
 
-    fn convert(s: &str) -> Result<String> {
+    fn convert(s: &str) -> Result<(String, MarkupLookup)> {
         let lookup = Rc::new(RefCell::new(MarkupLookup::new(s, 0)));
         let parser = Parser::new(s).into_offset_iter();
         let transformed = LookupRegister::new(parser, s, lookup.clone(), ParseContext::default());
@@ -72,13 +168,13 @@ mod tests {
         let mut body = String::new();
         html::Renderer::default().push(transformed, &mut body)?;
 
-        let lookup = Rc::try_unwrap(lookup)
+        let mut lookup = Rc::try_unwrap(lookup)
             .expect("Should be able to unwrap lookup")
             .into_inner();
 
-        body = insert_toc(&body, &lookup).to_string();
+        body = insert_toc(&body, &mut lookup).to_string();
 
-        Ok(body)
+        Ok((body, lookup))
     }
 
     #[test]
@@ -94,8 +190,8 @@ mod tests {
 
 ## [Def]: link like a definition
 "#;
-        let s = convert(s)?;
-        println!("{}", s);
+        let (s, lookup) = convert(s)?;
+        assert_eq!(lookup.toc, Some(TocOptions::default()));
 
         assert_eq!(
             s,
@@ -104,16 +200,18 @@ mod tests {
   <details>
     <summary>Contents</summary>
     <nav>
-      <ol>
-<li><a href="#First" class="heading-1">First
-</a></li>
-<li><a href="#Nested" class="heading-2">Nested
-</a></li>
-<li><a href="#With-a-link" class="heading-1">With a link
-</a></li>
-<li><a href="#Def-link-like-a-definition" class="heading-2">[Def]: link like a definition
-</a></li>
-      </ol>
+<ol>
+<li><a href="#First" class="heading-1">First</a>
+<ol>
+<li><a href="#Nested" class="heading-2">Nested</a></li>
+</ol>
+</li>
+<li><a href="#With-a-link" class="heading-1">With a link</a>
+<ol>
+<li><a href="#Def-link-like-a-definition" class="heading-2">[Def]: link like a definition</a></li>
+</ol>
+</li>
+</ol>
     </nav>
   </details>
 </div>
@@ -134,4 +232,66 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_toc_depth_and_numbering() -> Result<()> {
+        let s = r#"
+{depth=3 numbered=true}
+:table-of-content:
+
+# First
+
+### Skipped level
+
+#### Too deep
+
+## Second
+"#;
+        let (s, lookup) = convert(s)?;
+        assert_eq!(
+            lookup.toc,
+            Some(TocOptions {
+                depth: 3,
+                numbered: true,
+                sidebar: false
+            })
+        );
+        assert!(s.contains(r#"<div class="toc numbered">"#));
+        assert!(s.contains(
+            r##"<li><a href="#Skipped-level" class="heading-3"><span class="toc-number">1.1</span> Skipped level</a></li>"##
+        ));
+        assert!(s.contains(
+            r##"<li><a href="#Second" class="heading-2"><span class="toc-number">1.2</span> Second</a></li>"##
+        ));
+        assert!(!s.contains(r##"href="#Too-deep""##));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_heading_tree() -> Result<()> {
+        let s = r#"
+{sidebar=true}
+:table-of-content:
+
+# One
+
+## One and a half
+
+# Two
+"#;
+        let (s, lookup) = convert(s)?;
+        assert!(!s.contains("table-of-content"));
+        assert!(!s.contains(r#"class="toc""#));
+        assert!(lookup.toc.unwrap().sidebar);
+
+        let tree = heading_tree(&lookup, 6);
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree[0].title, "One");
+        assert_eq!(tree[0].children[0].id, "One-and-a-half");
+        assert_eq!(tree[0].children[0].number, "1.1");
+        assert_eq!(tree[1].number, "2");
+
+        Ok(())
+    }
 }
@@ -0,0 +1,291 @@
+use crate::markup::embed_youtube::embed_yt;
+use crate::paths::AbsPath;
+use eyre::{eyre, Result};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use tracing::warn;
+
+/// Metadata for embedded links is committed here, so builds don't need network access.
+pub const EMBED_CACHE: &str = "embeds.toml";
+
+/// Replace a bare link with an embed, if it's from a known provider.
+///
+/// Without `embed_thumbnail` we're rendering for the feed, where we can't use
+/// iframes or scripts, so we link to the embed with a thumbnail instead.
+/// Other providers than YouTube need metadata from `cache`, without it they're left as links.
+pub fn embed_link(
+    line: &str,
+    embed_thumbnail: bool,
+    cache: Option<&EmbedCache>,
+) -> Result<Option<String>> {
+    if let Some(html) = embed_yt(line, embed_thumbnail)? {
+        return Ok(Some(html));
+    }
+
+    let (embed, cache) = match (Embed::parse(line.trim()), cache) {
+        (Some(embed), Some(cache)) => (embed, cache),
+        _ => return Ok(None),
+    };
+    Ok(embed.render(cache, !embed_thumbnail))
+}
+
+/// Metadata about embedded links, keyed by url:
+///
+/// ```toml
+/// ["https://vimeo.com/76979871"]
+/// title = "The New Vimeo Player"
+/// author = "Vimeo Staff"
+/// thumbnail = "images/embeds/vimeo-76979871.jpg"
+/// ```
+#[derive(Debug, Default)]
+pub struct EmbedCache {
+    pub entries: HashMap<String, EmbedMeta>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EmbedMeta {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    /// A local image shown instead of the embed in the feed.
+    pub thumbnail: Option<String>,
+}
+
+impl EmbedCache {
+    /// Load the cache if it exists, otherwise return an empty one.
+    pub fn load(path: &AbsPath) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)?;
+        Self::parse(&content).map_err(|err| eyre!("Failed to parse {path}: {err}"))
+    }
+
+    pub fn parse(s: &str) -> Result<Self> {
+        Ok(Self {
+            entries: toml::from_str(s)?,
+        })
+    }
+
+    pub fn get(&self, url: &str) -> Option<&EmbedMeta> {
+        self.entries.get(url)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmbedProvider {
+    Vimeo,
+    Gist,
+    Codepen,
+    PeerTube,
+    Mastodon,
+}
+
+impl EmbedProvider {
+    /// Providers with a fixed domain are matched before those that may be self-hosted.
+    const ALL: [Self; 5] = [
+        Self::Vimeo,
+        Self::Gist,
+        Self::Codepen,
+        Self::PeerTube,
+        Self::Mastodon,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Vimeo => "vimeo",
+            Self::Gist => "gist",
+            Self::Codepen => "codepen",
+            Self::PeerTube => "peertube",
+            Self::Mastodon => "mastodon",
+        }
+    }
+
+    fn re(&self) -> &'static Regex {
+        match self {
+            Self::Vimeo => &VIMEO,
+            Self::Gist => &GIST,
+            Self::Codepen => &CODEPEN,
+            Self::PeerTube => &PEERTUBE,
+            Self::Mastodon => &MASTODON,
+        }
+    }
+
+    /// The url of the iframe, or script for gists, from the captures of `re`.
+    fn src(&self, caps: &regex::Captures) -> String {
+        match self {
+            Self::Vimeo => format!("https://player.vimeo.com/video/{}", &caps[1]),
+            Self::Gist => format!("https://gist.github.com/{}/{}.js", &caps[1], &caps[2]),
+            Self::Codepen => format!(
+                "https://codepen.io/{}/embed/{}?default-tab=result",
+                &caps[1], &caps[2]
+            ),
+            Self::PeerTube => format!("https://{}/videos/embed/{}", &caps[1], &caps[2]),
+            Self::Mastodon => format!("https://{}/@{}/{}/embed", &caps[1], &caps[2], &caps[3]),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Embed<'a> {
+    provider: EmbedProvider,
+    url: &'a str,
+    src: String,
+}
+
+impl<'a> Embed<'a> {
+    fn parse(url: &'a str) -> Option<Self> {
+        EmbedProvider::ALL.iter().find_map(|provider| {
+            let caps = provider.re().captures(url)?;
+            Some(Self {
+                provider: *provider,
+                url,
+                src: provider.src(&caps),
+            })
+        })
+    }
+
+    /// Returns `None` if the link is missing from the cache, to leave it as a regular link.
+    /// PeerTube and Mastodon can be hosted anywhere, so we only embed links we know about.
+    fn render(&self, cache: &EmbedCache, in_feed: bool) -> Option<String> {
+        let meta = match cache.get(self.url) {
+            Some(meta) => meta,
+            None => {
+                warn!("Missing embed metadata for `{}` in {EMBED_CACHE}", self.url);
+                return None;
+            }
+        };
+
+        let url = html_escape::encode_double_quoted_attribute(self.url);
+        let title = meta.title.as_deref().unwrap_or(self.url);
+        let title_attr = html_escape::encode_double_quoted_attribute(title);
+        let caption = match &meta.author {
+            Some(author) => format!(
+                r#"<a href="{url}">{}</a> by {}"#,
+                html_escape::encode_text(title),
+                html_escape::encode_text(author)
+            ),
+            None => format!(r#"<a href="{url}">{}</a>"#, html_escape::encode_text(title)),
+        };
+        let class = self.provider.as_str();
+
+        let html = if in_feed {
+            match &meta.thumbnail {
+                Some(thumbnail) => format!(
+                    r#"<figure class="embed embed-{class}"><a href="{url}"><img src="/{}" alt="{title_attr}" /></a><figcaption>{caption}</figcaption></figure>"#,
+                    html_escape::encode_double_quoted_attribute(thumbnail.trim_start_matches('/'))
+                ),
+                None => format!("<p>{caption}</p>"),
+            }
+        } else if self.provider == EmbedProvider::Gist {
+            format!(
+                r#"<figure class="embed embed-{class}"><script src="{}"></script><noscript><a href="{url}">{title_attr}</a></noscript><figcaption>{caption}</figcaption></figure>"#,
+                html_escape::encode_double_quoted_attribute(&self.src)
+            )
+        } else {
+            format!(
+                r#"<figure class="embed embed-{class}"><div class="embed-container"><iframe src="{}" title="{title_attr}" loading="lazy" allowfullscreen></iframe></div><figcaption>{caption}</figcaption></figure>"#,
+                html_escape::encode_double_quoted_attribute(&self.src)
+            )
+        };
+        Some(html)
+    }
+}
+
+lazy_static! {
+    static ref VIMEO: Regex = Regex::new(r"^https?://(?:www\.)?vimeo\.com/(\d+)$").unwrap();
+    static ref GIST: Regex =
+        Regex::new(r"^https?://gist\.github\.com/([\w-]+)/([0-9a-f]+)$").unwrap();
+    static ref CODEPEN: Regex = Regex::new(r"^https?://codepen\.io/([\w-]+)/pen/(\w+)$").unwrap();
+    static ref PEERTUBE: Regex =
+        Regex::new(r"^https://([\w.-]+)/(?:w|videos/watch)/([\w-]+)$").unwrap();
+    static ref MASTODON: Regex = Regex::new(r"^https://([\w.-]+)/@(\w+)/(\d+)$").unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache() -> Result<EmbedCache> {
+        EmbedCache::parse(
+            r#"
+["https://vimeo.com/76979871"]
+title = "The New Vimeo Player"
+author = "Vimeo Staff"
+thumbnail = "images/embeds/vimeo-76979871.jpg"
+
+["https://fosstodon.org/@treeman/112233"]
+title = "A toot"
+"#,
+        )
+    }
+
+    #[test]
+    fn test_parse_providers() {
+        let parse = |url| Embed::parse(url).map(|embed| (embed.provider, embed.src));
+        assert_eq!(
+            parse("https://vimeo.com/76979871"),
+            Some((
+                EmbedProvider::Vimeo,
+                "https://player.vimeo.com/video/76979871".into()
+            ))
+        );
+        assert_eq!(
+            parse("https://gist.github.com/treeman/0a1b2c"),
+            Some((
+                EmbedProvider::Gist,
+                "https://gist.github.com/treeman/0a1b2c.js".into()
+            ))
+        );
+        assert_eq!(
+            parse("https://codepen.io/someone/pen/abcDEF"),
+            Some((
+                EmbedProvider::Codepen,
+                "https://codepen.io/someone/embed/abcDEF?default-tab=result".into()
+            ))
+        );
+        assert_eq!(
+            parse("https://framatube.org/w/9c9de5e8-0a1e-484a-b099-e80766180a6d"),
+            Some((
+                EmbedProvider::PeerTube,
+                "https://framatube.org/videos/embed/9c9de5e8-0a1e-484a-b099-e80766180a6d".into()
+            ))
+        );
+        assert_eq!(
+            parse("https://fosstodon.org/@treeman/112233"),
+            Some((
+                EmbedProvider::Mastodon,
+                "https://fosstodon.org/@treeman/112233/embed".into()
+            ))
+        );
+        assert_eq!(parse("https://www.jonashietala.se/"), None);
+    }
+
+    #[test]
+    fn test_render_embed() -> Result<()> {
+        let cache = cache()?;
+
+        let vimeo = Embed::parse("https://vimeo.com/76979871").unwrap();
+        assert_eq!(
+            vimeo.render(&cache, false).unwrap(),
+            r#"<figure class="embed embed-vimeo"><div class="embed-container"><iframe src="https://player.vimeo.com/video/76979871" title="The New Vimeo Player" loading="lazy" allowfullscreen></iframe></div><figcaption><a href="https://vimeo.com/76979871">The New Vimeo Player</a> by Vimeo Staff</figcaption></figure>"#
+        );
+        assert_eq!(
+            vimeo.render(&cache, true).unwrap(),
+            r#"<figure class="embed embed-vimeo"><a href="https://vimeo.com/76979871"><img src="/images/embeds/vimeo-76979871.jpg" alt="The New Vimeo Player" /></a><figcaption><a href="https://vimeo.com/76979871">The New Vimeo Player</a> by Vimeo Staff</figcaption></figure>"#
+        );
+
+        let toot = Embed::parse("https://fosstodon.org/@treeman/112233").unwrap();
+        assert_eq!(
+            toot.render(&cache, true).unwrap(),
+            r#"<p><a href="https://fosstodon.org/@treeman/112233">A toot</a></p>"#
+        );
+
+        let missing = Embed::parse("https://codepen.io/someone/pen/abcDEF").unwrap();
+        assert_eq!(missing.render(&cache, false), None);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+use eyre::{eyre, Result};
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::fs;
+
+use crate::paths::AbsPath;
+use crate::util;
+
+/// Terms and their definitions, loaded from `glossary.toml`:
+///
+/// ```toml
+/// LSP = "Language Server Protocol"
+/// Djot = "A light markup syntax derived from Markdown"
+/// ```
+#[derive(Debug, Default)]
+pub struct Glossary {
+    pub terms: BTreeMap<String, String>,
+    /// Matches any of the terms, preferring the longest.
+    matcher: Option<Regex>,
+}
+
+impl Glossary {
+    /// Load the glossary if it exists, otherwise return an empty one.
+    pub fn load(path: &AbsPath) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)?;
+        Self::parse(&content).map_err(|err| eyre!("Failed to parse {path}: {err}"))
+    }
+
+    pub fn parse(s: &str) -> Result<Self> {
+        let terms: BTreeMap<String, String> = toml::from_str(s)?;
+
+        let matcher = if terms.is_empty() {
+            None
+        } else {
+            let mut keys: Vec<_> = terms.keys().collect();
+            keys.sort_by_key(|term| std::cmp::Reverse(term.len()));
+            let alternatives: Vec<_> = keys.into_iter().map(|x| term_pattern(x)).collect();
+            Some(Regex::new(&alternatives.join("|"))?)
+        };
+
+        Ok(Self { terms, matcher })
+    }
+
+    pub fn get(&self, term: &str) -> Option<&str> {
+        self.terms.get(term).map(String::as_str)
+    }
+
+    /// Find all occurrences of glossary terms in `s`.
+    pub fn find_terms<'a>(&'a self, s: &'a str) -> impl Iterator<Item = regex::Match<'a>> + 'a {
+        self.matcher
+            .iter()
+            .flat_map(move |matcher| matcher.find_iter(s))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+}
+
+/// Only match whole words, but a `\b` next to a non-word character
+/// would prevent terms such as "C++" or ".NET" from matching at all.
+fn term_pattern(term: &str) -> String {
+    let boundary = |c: Option<char>| match c {
+        Some(c) if c.is_alphanumeric() || c == '_' => r"\b",
+        _ => "",
+    };
+    format!(
+        "{}{}{}",
+        boundary(term.chars().next()),
+        regex::escape(term),
+        boundary(term.chars().last()),
+    )
+}
+
+/// The anchor of a term on the `/glossary` page.
+pub fn term_id(term: &str) -> String {
+    util::to_id(term)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_terms() -> Result<()> {
+        let glossary = Glossary::parse(
+            r#"
+LSP = "Language Server Protocol"
+"LSP server" = "A server speaking LSP"
+"#,
+        )?;
+
+        assert_eq!(glossary.get("LSP"), Some("Language Server Protocol"));
+        let found: Vec<_> = glossary
+            .find_terms("An LSP server uses LSP, not LSPs")
+            .map(|m| m.as_str())
+            .collect();
+        assert_eq!(found, vec!["LSP server", "LSP"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_terms_with_symbols() -> Result<()> {
+        let glossary = Glossary::parse(
+            r#"
+"C++" = "A programming language"
+C = "Another programming language"
+".NET" = "A software framework"
+"#,
+        )?;
+
+        let found: Vec<_> = glossary
+            .find_terms("Both C++ and C run on .NET, but CSS doesn't")
+            .map(|m| m.as_str())
+            .collect();
+        assert_eq!(found, vec!["C++", "C", ".NET"]);
+
+        Ok(())
+    }
+}
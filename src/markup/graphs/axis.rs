@@ -0,0 +1,196 @@
+use itertools::join;
+use std::fmt::Display;
+
+/// Dimensions of a plot, with room for axis labels to the left of and below the plot.
+#[derive(Debug, Clone, Copy)]
+pub struct PlotArea {
+    pub viewbox_w: u32,
+    pub viewbox_h: u32,
+    pub label_margin_x: u32,
+    pub label_margin_y: u32,
+    pub padding: u32,
+}
+
+impl Default for PlotArea {
+    fn default() -> Self {
+        Self {
+            viewbox_w: 800,
+            viewbox_h: 600,
+            label_margin_x: 50,
+            label_margin_y: 65,
+            padding: 30,
+        }
+    }
+}
+
+impl PlotArea {
+    pub fn plot_w(&self) -> u32 {
+        self.viewbox_w - self.label_margin_x - self.padding * 2
+    }
+
+    pub fn plot_h(&self) -> u32 {
+        self.viewbox_h - self.label_margin_y - self.padding * 2
+    }
+
+    pub fn plot_left(&self) -> u32 {
+        self.label_margin_x + self.padding
+    }
+
+    pub fn plot_right(&self) -> u32 {
+        self.viewbox_w - self.padding
+    }
+
+    pub fn plot_top(&self) -> u32 {
+        self.padding
+    }
+
+    pub fn plot_bottom(&self) -> u32 {
+        self.plot_h() + self.padding
+    }
+
+    /// Where the y-axis tick labels end.
+    pub fn y_label_x(&self) -> u32 {
+        self.plot_left() - 10
+    }
+
+    /// The baseline of the x-axis tick labels.
+    pub fn x_label_y(&self) -> u32 {
+        self.plot_bottom() + 24
+    }
+
+    /// Map `value` in `min..=max` to an x coordinate inside the plot.
+    pub fn value_to_x(&self, value: f64, min: f64, max: f64) -> f64 {
+        let t = if max > min {
+            (value - min) / (max - min)
+        } else {
+            0.5
+        };
+        self.plot_left() as f64 + t * self.plot_w() as f64
+    }
+
+    /// Map `value` in `min..=max` to a y coordinate inside the plot, with `max` at the top.
+    pub fn value_to_y(&self, value: f64, min: f64, max: f64) -> f64 {
+        let t = if max > min {
+            (value - min) / (max - min)
+        } else {
+            0.5
+        };
+        self.plot_bottom() as f64 - t * self.plot_h() as f64
+    }
+}
+
+/// A tick label on one of the axes.
+pub fn tick_label(x: impl Display, y: impl Display, label: impl Display) -> String {
+    format!(r#"<text x="{x}" y="{y}">{label}</text>"#)
+}
+
+/// Labels for data sets, placed in rows below the plot.
+#[derive(Debug, Default)]
+pub struct Legend {
+    column: u32,
+    row: u32,
+}
+
+impl Legend {
+    const LABEL_WIDTH: u32 = 140;
+    const LABEL_HEIGHT: u32 = 25;
+    const LABELS_PER_ROW: u32 = 5;
+
+    pub fn next_label(&mut self, area: &PlotArea, title: &str) -> String {
+        let x_box_offset = 14;
+        let y_box_offset = 10;
+        let x = area.plot_left() + self.column * Self::LABEL_WIDTH + x_box_offset;
+        let y = area.plot_bottom() + 50 + self.row * Self::LABEL_HEIGHT;
+        let box_x = x - x_box_offset;
+        let box_y = y - y_box_offset;
+        let box_w = 10;
+        self.column += 1;
+        if self.column == Self::LABELS_PER_ROW {
+            self.column = 0;
+            self.row += 1;
+        }
+        format!(
+            r#"<rect x="{box_x}" y="{box_y}" width="{box_w}" height="{box_w}"
+                            class="data-set-label-box" />
+    <text x="{x}" y="{y}" class="data-set-label">{title}</text>"#
+        )
+    }
+}
+
+/// The content of a plot, to be placed inside the axes.
+#[derive(Debug, Default)]
+pub struct PlotSvg<'a> {
+    /// Class of the `<svg>` element.
+    pub class: &'a str,
+    pub x_ticks: Vec<String>,
+    pub y_ticks: Vec<String>,
+    pub x_title: Option<&'a str>,
+    pub y_title: Option<&'a str>,
+    pub data_sets: Vec<String>,
+    pub caption: Option<&'a str>,
+}
+
+impl PlotSvg<'_> {
+    pub fn render(self, area: &PlotArea) -> String {
+        let PlotArea {
+            viewbox_w,
+            viewbox_h,
+            padding,
+            ..
+        } = *area;
+        let plot_left = area.plot_left();
+        let plot_right = area.plot_right();
+        let plot_top = area.plot_top();
+        let plot_bottom = area.plot_bottom();
+        let y_label_x = area.y_label_x();
+        let class = self.class;
+
+        let x_ticks = join(self.x_ticks, "\n");
+        let x_title = match self.x_title {
+            Some(title) => format!(
+                r#"<text x="{plot_right}" y="{}" class="label-title">{title}</text>"#,
+                area.x_label_y() + 24
+            ),
+            None => "".into(),
+        };
+        let y_ticks = join(self.y_ticks, "\n");
+        let y_title = match self.y_title {
+            Some(title) => {
+                format!(r#"<text x="{y_label_x}" y="{padding}" class="label-title">{title}</text>"#)
+            }
+            None => "".into(),
+        };
+        // Reverse to place the first data sets on top
+        let data_sets = join(self.data_sets.into_iter().rev(), "\n");
+        let caption = match self.caption {
+            Some(x) => format!("<figcaption>{x}</figcaption>"),
+            None => "".into(),
+        };
+
+        format!(
+            r#"
+  <figure class="plot-wrapper">
+    <svg version="1.2" xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" class="{class}" role="img"
+        viewBox="0 0 {viewbox_w} {viewbox_h}">
+    <g class="grid x-axis">
+      <line x1="{plot_left}" x2="{plot_right}" y1="{plot_bottom}" y2="{plot_bottom}"></line>
+    </g>
+    <g class="grid y-grid">
+      <line x1="{plot_left}" x2="{plot_left}" y1="{plot_top}" y2="{plot_bottom}"></line>
+    </g>
+    <g class="labels x-labels">
+      {x_ticks}
+      {x_title}
+    </g>
+    <g class="labels y-labels">
+      {y_ticks}
+      {y_title}
+    </g>
+    {data_sets}
+    </svg>
+    {caption}
+  </figure>
+        "#
+        )
+    }
+}
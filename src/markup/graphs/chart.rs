@@ -0,0 +1,458 @@
+use super::axis::{tick_label, Legend, PlotArea, PlotSvg};
+use camino::Utf8Path;
+use eyre::{eyre, Result};
+use itertools::join;
+use serde::Deserialize;
+use std::fmt::Display;
+use std::fs;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartType {
+    Line,
+    Bar,
+    Scatter,
+}
+
+impl ChartType {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "line" => Some(Self::Line),
+            "bar" => Some(Self::Bar),
+            "scatter" => Some(Self::Scatter),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Line => "line",
+            Self::Bar => "bar",
+            Self::Scatter => "scatter",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum XValue {
+    Number(f64),
+    Label(String),
+}
+
+impl XValue {
+    fn parse(s: &str) -> Self {
+        match s.parse() {
+            Ok(x) => Self::Number(x),
+            Err(_) => Self::Label(s.to_string()),
+        }
+    }
+}
+
+impl Display for XValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Number(x) => write!(f, "{}", format_number(*x)),
+            Self::Label(x) => write!(f, "{x}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Series {
+    pub name: String,
+    pub y: Vec<f64>,
+}
+
+/// Data to plot, either as csv with the x values in the first column
+/// and a column for each series:
+///
+/// ```csv
+/// Year, Posts, Notes
+/// 2022, 20, 4
+/// 2023, 34, 12
+/// ```
+///
+/// Or as toml:
+///
+/// ```toml
+/// x_label = "Year"
+/// x = [2022, 2023]
+///
+/// [[series]]
+/// name = "Posts"
+/// y = [20, 34]
+/// ```
+///
+/// The x values may be labels instead of numbers, which are then evenly spaced.
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+pub struct ChartData {
+    pub x_label: Option<String>,
+    pub y_label: Option<String>,
+    pub x: Vec<XValue>,
+    pub series: Vec<Series>,
+}
+
+impl ChartData {
+    /// Load data from a `.csv` or `.toml` file.
+    pub fn load(path: &Utf8Path) -> Result<Self> {
+        let content =
+            fs::read_to_string(path).map_err(|err| eyre!("Failed to read {path}: {err}"))?;
+        match path.extension() {
+            Some("toml") => Self::parse_toml(&content),
+            _ => Self::parse_csv(&content),
+        }
+    }
+
+    /// Parse inline data, as toml if it looks like it, otherwise as csv.
+    pub fn parse(s: &str) -> Result<Self> {
+        let first_line = s
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with('#'));
+        match first_line {
+            Some(line) if line.starts_with('[') || line.contains('=') => Self::parse_toml(s),
+            _ => Self::parse_csv(s),
+        }
+    }
+
+    pub fn parse_toml(s: &str) -> Result<Self> {
+        let data: Self = toml::from_str(s)?;
+        data.validate()?;
+        Ok(data)
+    }
+
+    pub fn parse_csv(s: &str) -> Result<Self> {
+        let mut lines = s
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+
+        let header: Vec<_> = lines
+            .next()
+            .ok_or_else(|| eyre!("Missing csv header"))?
+            .split(',')
+            .map(str::trim)
+            .collect();
+        if header.len() < 2 {
+            return Err(eyre!("Expected an x column and at least one series"));
+        }
+
+        let mut data = Self {
+            x_label: Some(header[0].to_string()).filter(|x| !x.is_empty()),
+            y_label: None,
+            x: Vec::new(),
+            series: header[1..]
+                .iter()
+                .map(|name| Series {
+                    name: name.to_string(),
+                    y: Vec::new(),
+                })
+                .collect(),
+        };
+
+        for line in lines {
+            let columns: Vec<_> = line.split(',').map(str::trim).collect();
+            if columns.len() != header.len() {
+                return Err(eyre!(
+                    "Row `{line}` has {} columns, expected {}",
+                    columns.len(),
+                    header.len()
+                ));
+            }
+            data.x.push(XValue::parse(columns[0]));
+            for (series, column) in data.series.iter_mut().zip(&columns[1..]) {
+                series.y.push(
+                    column
+                        .parse()
+                        .map_err(|_| eyre!("Invalid number `{column}` in row `{line}`"))?,
+                );
+            }
+        }
+
+        data.validate()?;
+        Ok(data)
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.x.is_empty() || self.series.is_empty() {
+            return Err(eyre!("No data to plot"));
+        }
+        for series in &self.series {
+            if series.y.len() != self.x.len() {
+                return Err(eyre!(
+                    "Series `{}` has {} values, expected {}",
+                    series.name,
+                    series.y.len(),
+                    self.x.len()
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Labels on the x-axis are evenly spaced.
+    fn is_categorical(&self) -> bool {
+        self.x.iter().any(|x| matches!(x, XValue::Label(_)))
+    }
+}
+
+/// A line, bar or scatter chart, using the same axes as the blog stats graphs.
+pub struct Chart {
+    pub chart_type: ChartType,
+    pub data: ChartData,
+    pub caption: Option<String>,
+}
+
+impl Chart {
+    pub fn generate(self) -> Result<String> {
+        self.data.validate()?;
+
+        let area = PlotArea::default();
+        let data = &self.data;
+        let categorical = self.chart_type == ChartType::Bar || data.is_categorical();
+
+        // Categories are placed at their index, with room for bars on each side.
+        let xs: Vec<f64> = data
+            .x
+            .iter()
+            .enumerate()
+            .map(|(i, x)| match x {
+                XValue::Number(x) if !categorical => *x,
+                _ => i as f64,
+            })
+            .collect();
+        let (x_min, x_max, x_ticks) = if categorical {
+            let ticks = data
+                .x
+                .iter()
+                .enumerate()
+                .map(|(i, x)| (i as f64, html_escape::encode_text(&x.to_string()).into()))
+                .collect::<Vec<(f64, String)>>();
+            (-0.5, data.x.len() as f64 - 0.5, ticks)
+        } else {
+            let ticks = nice_ticks(min_value(&xs), max_value(&xs));
+            let ticks: Vec<(f64, String)> =
+                ticks.into_iter().map(|x| (x, format_number(x))).collect();
+            (ticks[0].0, ticks[ticks.len() - 1].0, ticks)
+        };
+
+        let ys: Vec<f64> = data
+            .series
+            .iter()
+            .flat_map(|s| s.y.iter().copied())
+            .collect();
+        let (mut y_min, mut y_max) = (min_value(&ys), max_value(&ys));
+        // Bars grow from zero.
+        if self.chart_type == ChartType::Bar {
+            y_min = y_min.min(0.0);
+            y_max = y_max.max(0.0);
+        }
+        let y_ticks = nice_ticks(y_min, y_max);
+        let (y_min, y_max) = (y_ticks[0], y_ticks[y_ticks.len() - 1]);
+
+        let to_x = |x: f64| area.value_to_x(x, x_min, x_max);
+        let to_y = |y: f64| area.value_to_y(y, y_min, y_max);
+
+        let x_ticks = x_ticks
+            .into_iter()
+            .map(|(x, label)| tick_label(format!("{:.1}", to_x(x)), area.x_label_y(), label))
+            .collect();
+        let y_ticks = y_ticks
+            .into_iter()
+            .map(|y| {
+                tick_label(
+                    area.y_label_x(),
+                    format!("{:.1}", to_y(y) + 4.0),
+                    format_number(y),
+                )
+            })
+            .collect();
+
+        let point_radius = 3.5;
+        let slot_w = area.plot_w() as f64 / (x_max - x_min) * 0.8;
+        let bar_w = slot_w / data.series.len() as f64;
+
+        let mut legend = Legend::default();
+        let mut data_sets = Vec::new();
+        for (i, series) in data.series.iter().enumerate() {
+            let name = html_escape::encode_text(&series.name);
+            let title = |j: usize| format!("{name}: {}, {}", data.x[j], format_number(series.y[j]));
+
+            let mut shapes = Vec::new();
+            if self.chart_type == ChartType::Line {
+                let points = join(
+                    xs.iter()
+                        .zip(&series.y)
+                        .map(|(x, y)| format!("{:.1},{:.1}", to_x(*x), to_y(*y))),
+                    " ",
+                );
+                shapes.push(format!(
+                    r#"<polyline points="{points}" fill="none" stroke="currentColor" class="line" />"#
+                ));
+            }
+            for (j, (x, y)) in xs.iter().zip(&series.y).enumerate() {
+                let title = title(j);
+                let val = format_number(*y);
+                shapes.push(match self.chart_type {
+                    ChartType::Line | ChartType::Scatter => format!(
+                        r#"<circle cx="{:.1}" cy="{:.1}" data-value="{val}" r="{point_radius}">
+      <title>{title}</title>
+    </circle>"#,
+                        to_x(*x),
+                        to_y(*y)
+                    ),
+                    ChartType::Bar => {
+                        let left = to_x(*x) - slot_w / 2.0 + i as f64 * bar_w;
+                        let top = to_y(y.max(0.0));
+                        let height = (to_y(*y) - to_y(0.0)).abs();
+                        format!(
+                            r#"<rect x="{left:.1}" y="{top:.1}" width="{bar_w:.1}" height="{height:.1}" data-value="{val}">
+      <title>{title}</title>
+    </rect>"#
+                        )
+                    }
+                });
+            }
+
+            let label = if data.series.len() > 1 {
+                legend.next_label(&area, &name)
+            } else {
+                String::new()
+            };
+            let shapes = join(shapes, "\n");
+            data_sets.push(format!(
+                r#"<g class="data-set series-{i}">
+      {shapes}
+      {label}
+    </g>"#
+            ));
+        }
+
+        let class = format!("chart chart-{}", self.chart_type.as_str());
+        let x_title = data.x_label.as_deref().map(html_escape::encode_text);
+        let y_title = data.y_label.as_deref().map(html_escape::encode_text);
+        Ok(PlotSvg {
+            class: &class,
+            x_ticks,
+            y_ticks,
+            x_title: x_title.as_deref(),
+            y_title: y_title.as_deref(),
+            data_sets,
+            caption: self.caption.as_deref(),
+        }
+        .render(&area))
+    }
+}
+
+fn min_value(xs: &[f64]) -> f64 {
+    xs.iter().copied().fold(f64::INFINITY, f64::min)
+}
+
+fn max_value(xs: &[f64]) -> f64 {
+    xs.iter().copied().fold(f64::NEG_INFINITY, f64::max)
+}
+
+/// Evenly spaced ticks at round numbers, covering `min..=max`.
+fn nice_ticks(min: f64, max: f64) -> Vec<f64> {
+    let (min, max) = if min < max {
+        (min, max)
+    } else {
+        (min - 1.0, max + 1.0)
+    };
+    let raw_step = (max - min) / 5.0;
+    let magnitude = 10f64.powf(raw_step.log10().floor());
+    let step = [1.0, 2.0, 2.5, 5.0, 10.0]
+        .into_iter()
+        .map(|x| x * magnitude)
+        .find(|step| *step >= raw_step)
+        .unwrap_or(10.0 * magnitude);
+    let start = (min / step).floor() * step;
+    let count = ((max - start) / step).ceil() as usize;
+    (0..=count).map(|i| start + i as f64 * step).collect()
+}
+
+/// Format without floating point noise, such as `0.30000000000000004`.
+fn format_number(x: f64) -> String {
+    let rounded = (x * 1e6).round() / 1e6;
+    if rounded == 0.0 {
+        "0".into()
+    } else {
+        rounded.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_data() -> Result<()> {
+        let csv = ChartData::parse(
+            "
+# Comments are skipped
+Year, Posts, Notes
+2022, 20, 4
+2023, 34, 12
+",
+        )?;
+        let toml = ChartData::parse(
+            r#"
+x_label = "Year"
+x = [2022, 2023]
+
+[[series]]
+name = "Posts"
+y = [20, 34]
+
+[[series]]
+name = "Notes"
+y = [4, 12]
+"#,
+        )?;
+        assert_eq!(csv, toml);
+        assert_eq!(csv.x, vec![XValue::Number(2022.0), XValue::Number(2023.0)]);
+        assert_eq!(csv.series[1].y, vec![4.0, 12.0]);
+
+        assert!(ChartData::parse("x, y\n1, 2\n3").is_err());
+        assert!(ChartData::parse("x, y\n1, two").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_nice_ticks() {
+        assert_eq!(nice_ticks(0.0, 34.0), vec![0.0, 10.0, 20.0, 30.0, 40.0]);
+        assert_eq!(
+            nice_ticks(-0.05, 0.7)
+                .into_iter()
+                .map(format_number)
+                .collect::<Vec<_>>(),
+            vec!["-0.2", "0", "0.2", "0.4", "0.6", "0.8"]
+        );
+    }
+
+    #[test]
+    fn test_generate_chart() -> Result<()> {
+        let data = ChartData::parse("Miner, Hash rate\nCPU, 1\nGPU, 10\nASIC, 100")?;
+        let svg = Chart {
+            chart_type: ChartType::Bar,
+            data: data.clone(),
+            caption: Some("Hash rates".into()),
+        }
+        .generate()?;
+        assert!(svg.contains(r#"class="chart chart-bar""#));
+        assert_eq!(svg.matches("<rect x=").count(), 3);
+        assert!(svg.contains(">ASIC</text>"));
+        assert!(svg.contains(r#"class="label-title">Miner</text>"#));
+        assert!(svg.contains("<figcaption>Hash rates</figcaption>"));
+
+        let svg = Chart {
+            chart_type: ChartType::Line,
+            data,
+            caption: None,
+        }
+        .generate()?;
+        assert!(svg.contains("<polyline points="));
+        assert_eq!(svg.matches("<circle").count(), 3);
+        Ok(())
+    }
+}
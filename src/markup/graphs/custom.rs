@@ -1,45 +1,26 @@
+use super::axis::{tick_label, Legend, PlotArea, PlotSvg};
 use crate::content::CountedWordsPostItem;
-use crate::markup;
-use crate::paths::AbsPath;
 use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime};
 use eyre::Result;
 use itertools::join;
-use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use std::cmp::{max, min};
 use std::collections::HashSet;
 
-#[allow(dead_code)]
-pub fn post_stats_graph_custom(
-    before_date: Option<NaiveDate>,
+/// Plot the word count of posts over time.
+/// The x-axis spans `date_range` if given, otherwise the posts themselves.
+pub fn posts_graph_custom(
+    posts: Vec<CountedWordsPostItem>,
+    date_range: Option<(NaiveDate, NaiveDate)>,
     caption: Option<String>,
 ) -> Result<String> {
-    let base = AbsPath::current_dir().unwrap();
-    let dir = base.join("posts");
-
-    // Yeah, maybe we could refactor things so that we don't read the files again,
-    // but that's a surprising amount of work and I don't feel that it's worth it at the moment.
-    let posts = markup::find_markup_files(base, &[dir])
-        .par_iter()
-        .map(|path| CountedWordsPostItem::from_file(path.abs_path()))
-        .filter(|file| {
-            if let Ok(file) = file {
-                if let Some(date) = before_date {
-                    file.created <= date
-                } else {
-                    true
-                }
-            } else {
-                true
-            }
-        })
-        .collect::<Result<Vec<_>>>()?;
-
     let plot_max_y = 6800;
 
     let mut groups = Groups::new();
 
-    let mut first_date = NaiveDate::from_ymd_opt(3000, 1, 1).unwrap();
-    let mut last_date = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
+    let (mut first_date, mut last_date) = date_range.unwrap_or((
+        NaiveDate::from_ymd_opt(3000, 1, 1).unwrap(),
+        NaiveDate::from_ymd_opt(2000, 1, 1).unwrap(),
+    ));
     for post in posts.into_iter() {
         if post.word_count > plot_max_y {
             panic!("You madman, you've created a post that's too long! Please update the y-axis labels to be pretty again.");
@@ -51,21 +32,14 @@ pub fn post_stats_graph_custom(
         groups.insert(post);
     }
 
-    let viewbox_w = 800;
-    let viewbox_h = 600;
-
-    let label_margin_x = 50;
-    let label_margin_y = 65;
-
-    let padding = 30;
-
-    let plot_w = viewbox_w - label_margin_x - padding * 2;
-    let plot_h = viewbox_h - label_margin_y - padding * 2;
-
-    let plot_left = label_margin_x + padding;
-    let plot_right = viewbox_w - padding;
-    let plot_top = padding;
-    let plot_bottom = plot_h + padding;
+    let area = PlotArea::default();
+    let PlotArea {
+        label_margin_x,
+        padding,
+        ..
+    } = area;
+    let plot_w = area.plot_w();
+    let plot_h = area.plot_h();
 
     let word_count_to_y = |count| {
         // Transform to 0..plot_h
@@ -77,18 +51,10 @@ pub fn post_stats_graph_custom(
         inverted + padding
     };
 
-    let y_label_x = plot_left - 10;
-
-    let y_ticks = join(
-        [0, 1000, 2000, 3000, 4000, 5000, 6000]
-            .into_iter()
-            .map(|count| {
-                let x = y_label_x;
-                let y = word_count_to_y(count) + 4;
-                format!(r#"<text x="{x}" y="{y}">{count}</text>"#)
-            }),
-        "\n",
-    );
+    let y_ticks = [0, 1000, 2000, 3000, 4000, 5000, 6000]
+        .into_iter()
+        .map(|count| tick_label(area.y_label_x(), word_count_to_y(count) + 4, count))
+        .collect();
 
     let first_unix_time = first_date
         .and_time(NaiveTime::from_hms_opt(0, 0, 0).unwrap())
@@ -114,36 +80,40 @@ pub fn post_stats_graph_custom(
 
     // How many years should we display as labels?
     let x_tick_count = 7;
+    // Show months instead of years when we're only plotting a single year.
+    let x_tick_format = if first_date.year() == last_date.year() {
+        "%b"
+    } else {
+        "%Y"
+    };
 
-    let x_ticks = join(
-        (0..x_tick_count)
-            .map(|i| {
-                // Interpolates and creates a date between the first and last post
-                let t = (i as f32) / (x_tick_count as f32 - 1.0);
-                let unix_time =
-                    first_unix_time + (t * (last_unix_time - first_unix_time) as f32) as i64;
-                DateTime::from_timestamp(unix_time, 0)
-                    .expect("Failed to create date from unix time")
-                    .naive_local()
-                    .date()
-            })
-            .filter_map(|date| {
-                if date < first_date {
-                    return None;
-                }
+    let x_ticks = (0..x_tick_count)
+        .map(|i| {
+            // Interpolates and creates a date between the first and last post
+            let t = (i as f32) / (x_tick_count as f32 - 1.0);
+            let unix_time =
+                first_unix_time + (t * (last_unix_time - first_unix_time) as f32) as i64;
+            DateTime::from_timestamp(unix_time, 0)
+                .expect("Failed to create date from unix time")
+                .naive_local()
+                .date()
+        })
+        .filter_map(|date| {
+            if date < first_date {
+                return None;
+            }
 
-                let y = plot_bottom + 24;
-                let x = date_to_x(&date);
-                let year = date.year();
-                Some(format!(r#"<text x="{x}" y="{y}">{year}</text>"#))
-            }),
-        "\n",
-    );
+            Some(tick_label(
+                date_to_x(&date),
+                area.x_label_y(),
+                date.format(x_tick_format),
+            ))
+        })
+        .collect();
 
     let point_radius = 3.5;
 
-    let mut label_column = 0;
-    let mut label_row = 0;
+    let mut legend = Legend::default();
 
     let mut data_sets = Vec::new();
 
@@ -165,27 +135,7 @@ pub fn post_stats_graph_custom(
             }),
             "\n",
         );
-        let label_width = 140;
-        let label_height = 25;
-        let num_labels_in_row = 5;
-        let x_box_offset = 14;
-        let y_box_offset = 10;
-        let x = plot_left + label_column * label_width + x_box_offset;
-        let y = plot_bottom + 50 + label_row * label_height;
-        let title = group.name;
-        let box_x = x - x_box_offset;
-        let box_y = y - y_box_offset;
-        let box_w = 10;
-        label_column += 1;
-        if label_column == num_labels_in_row {
-            label_column = 0;
-            label_row += 1;
-        }
-        let label = format!(
-            r#"<rect x="{box_x}" y="{box_y}" width="{box_w}" height="{box_w}"
-                            class="data-set-label-box" />
-    <text x="{x}" y="{y}" class="data-set-label">{title}</text>"#
-        );
+        let label = legend.next_label(&area, group.name);
         let class = group.id;
         data_sets.push(format!(
             r#"<g class="data-set {class}">
@@ -195,41 +145,16 @@ pub fn post_stats_graph_custom(
         ));
     }
 
-    // Reverse to place points on top
-    let data_sets = join(data_sets.into_iter().rev(), "\n");
-
-    let caption = if let Some(x) = caption {
-        format!("<figcaption>{x}</figcaption>")
-    } else {
-        "".into()
-    };
-
-    let custom_s = format!(
-        r#"
-  <figure class="plot-wrapper">
-    <svg version="1.2" xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" class="blog-stats-graph" role="img"
-        viewBox="0 0 {viewbox_w} {viewbox_h}">
-    <g class="grid x-axis">
-      <line x1="{plot_left}" x2="{plot_right}" y1="{plot_bottom}" y2="{plot_bottom}"></line>
-    </g>
-    <g class="grid y-grid">
-      <line x1="{plot_left}" x2="{plot_left}" y1="{plot_top}" y2="{plot_bottom}"></line>
-    </g>
-    <g class="labels x-labels">
-      {x_ticks}
-    </g>
-    <g class="labels y-labels">
-      {y_ticks}
-      <text x="{y_label_x}" y="{padding}" class="label-title">Words</text>
-    </g>
-    {data_sets}
-    </svg>
-    {caption}
-  </figure>
-        "#
-    );
-
-    Ok(custom_s)
+    Ok(PlotSvg {
+        class: "blog-stats-graph",
+        x_ticks,
+        y_ticks,
+        y_title: Some("Words"),
+        data_sets,
+        caption: caption.as_deref(),
+        ..Default::default()
+    }
+    .render(&area))
 }
 
 #[derive(Debug)]
@@ -1,18 +1,38 @@
 use chrono::NaiveDate;
 use eyre::Result;
 
+use crate::content::CountedWordsPostItem;
+
+mod axis;
+mod chart;
 // mod charming;
 mod custom;
 // mod plotly;
 
+pub use self::chart::{Chart, ChartData, ChartType};
+
+/// Plots the given posts over the dates they span.
 pub struct PostStatsGraph {
-    pub before_date: Option<NaiveDate>,
+    pub posts: Vec<CountedWordsPostItem>,
     pub caption: Option<String>,
 }
 
 impl PostStatsGraph {
     pub fn generate(self) -> Result<String> {
-        custom::post_stats_graph_custom(self.before_date, self.caption)
+        custom::posts_graph_custom(self.posts, None, self.caption)
+    }
+}
+
+/// Plots the given posts over a date range, such as a single year.
+pub struct PostsGraph {
+    pub posts: Vec<CountedWordsPostItem>,
+    pub date_range: (NaiveDate, NaiveDate),
+    pub caption: Option<String>,
+}
+
+impl PostsGraph {
+    pub fn generate(self) -> Result<String> {
+        custom::posts_graph_custom(self.posts, Some(self.date_range), self.caption)
     }
 }
 
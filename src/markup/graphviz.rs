@@ -0,0 +1,48 @@
+use eyre::{eyre, Result};
+use layout::backends::svg::SVGWriter;
+use layout::gv::{DotParser, GraphBuilder};
+use std::panic::{self, AssertUnwindSafe};
+
+/// Lay out a graph in the `dot` language and render it as svg.
+///
+/// This doesn't require the `dot` binary, but only supports a subset of what Graphviz does.
+pub fn dot_to_svg(dot: &str) -> Result<String> {
+    let mut parser = DotParser::new(dot);
+    let graph = parser
+        .process()
+        .map_err(|err| eyre!("Failed to parse graph: {err}"))?;
+
+    // The layout panics on some graphs it can't handle, which shouldn't bring down the site.
+    let svg = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut builder = GraphBuilder::new();
+        builder.visit_graph(&graph);
+        let mut visual_graph = builder.get();
+        let mut svg = SVGWriter::new();
+        visual_graph.do_it(false, false, false, &mut svg);
+        svg.finalize()
+    }))
+    .map_err(|_| eyre!("Failed to lay out graph"))?;
+
+    // Drop the xml declaration, if there is one, so it can be inlined in html.
+    match svg.find("<svg") {
+        Some(start) => Ok(svg[start..].to_string()),
+        None => Err(eyre!("Graph produced no svg")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_dot_to_svg() -> Result<()> {
+        let dot = fs::read_to_string("graphviz/payment-flow.dot")?;
+        let svg = dot_to_svg(&dot)?;
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("waiting for transaction"));
+
+        assert!(dot_to_svg("digraph { a -> ").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,316 @@
+use camino::Utf8PathBuf;
+use eyre::{eyre, Result};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::fs;
+
+/// Keyboard layouts are read from `keyboard_layouts/{name}.json`.
+const KEYBOARD_LAYOUTS_DIR: &str = "keyboard_layouts";
+
+/// Class given to highlighted keys.
+const HIGHLIGHT_CLASS: &str = "active_layer";
+
+const KEY_SIZE: f32 = 54.0;
+const KEY_INSET: f32 = 6.0;
+const MARGIN: f32 = 10.0;
+
+/// A keyboard layout, as used by [layout_gen](https://github.com/treeman/layout_gen).
+#[derive(Debug, Deserialize)]
+pub struct KeyboardLayout {
+    /// One string per row, with the effort of each key as a digit and a space for gaps.
+    pub physical_layout: Vec<String>,
+    /// The labels and classes of key codes, for each layer.
+    #[serde(default)]
+    pub layers: HashMap<String, Vec<KeyMapping>>,
+    /// Fill color for each key class.
+    #[serde(default)]
+    pub colors: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct KeyMapping {
+    pub keys: Vec<String>,
+    pub title: Option<String>,
+    pub hold_title: Option<String>,
+    pub class: Option<String>,
+}
+
+/// How a key should be drawn.
+#[derive(Debug, PartialEq, Eq)]
+struct KeyLabel<'a> {
+    title: &'a str,
+    hold_title: Option<&'a str>,
+    class: &'a str,
+}
+
+impl KeyboardLayout {
+    pub fn path(name: &str) -> Option<Utf8PathBuf> {
+        // Don't allow the name to escape the layout dir.
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+        {
+            return None;
+        }
+        Some(Utf8PathBuf::from(format!(
+            "{KEYBOARD_LAYOUTS_DIR}/{name}.json"
+        )))
+    }
+
+    pub fn load(name: &str) -> Result<Self> {
+        let path =
+            Self::path(name).ok_or_else(|| eyre!("Invalid keyboard layout name `{name}`"))?;
+        let content = fs::read_to_string(&path)?;
+        Self::parse(&content).map_err(|err| eyre!("Failed to parse {path}: {err}"))
+    }
+
+    pub fn parse(s: &str) -> Result<Self> {
+        Ok(serde_json::from_str(s)?)
+    }
+
+    /// Number of keys in the physical layout.
+    pub fn key_count(&self) -> usize {
+        self.physical_layout
+            .iter()
+            .flat_map(|row| row.chars())
+            .filter(|c| c.is_ascii_digit())
+            .count()
+    }
+
+    /// Look up a key in `layer`, falling back to the default layer
+    /// and then to the key code without its prefix.
+    fn label<'a>(&'a self, layer: &str, key: &'a str) -> KeyLabel<'a> {
+        let mapping = [layer, "default"].iter().find_map(|layer| {
+            self.layers
+                .get(*layer)?
+                .iter()
+                .find(|mapping| mapping.keys.iter().any(|x| x == key))
+        });
+        let title = mapping
+            .and_then(|mapping| mapping.title.as_deref())
+            .unwrap_or_else(|| strip_key_prefix(key));
+        KeyLabel {
+            title,
+            hold_title: mapping.and_then(|mapping| mapping.hold_title.as_deref()),
+            class: mapping
+                .and_then(|mapping| mapping.class.as_deref())
+                .unwrap_or("default"),
+        }
+    }
+
+    /// Render `keys` of `layer` as an svg, with keys placed in the order of the physical layout.
+    ///
+    /// Without any keys the effort of each key is shown instead.
+    pub fn render_svg(
+        &self,
+        name: &str,
+        layer: &str,
+        keys: &[&str],
+        highlight: &HashSet<&str>,
+    ) -> Result<String> {
+        if !keys.is_empty() && keys.len() != self.key_count() {
+            return Err(eyre!(
+                "Layout `{name}` has {} keys but {} were given",
+                self.key_count(),
+                keys.len()
+            ));
+        }
+        if !keys.is_empty() && layer != "default" && !self.layers.contains_key(layer) {
+            return Err(eyre!("Missing layer `{layer}` in layout `{name}`"));
+        }
+
+        let columns = self
+            .physical_layout
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0);
+        let width = 2.0 * MARGIN + columns as f32 * KEY_SIZE;
+        let height = 2.0 * MARGIN + self.physical_layout.len() as f32 * KEY_SIZE;
+
+        let mut svg = format!(
+            r#"<svg class="keymap {name}" width="{width}" height="{height}" viewBox="0 0 {width} {height}" xmlns="http://www.w3.org/2000/svg" role="img" aria-label="{}">"#,
+            html_escape::encode_double_quoted_attribute(&format!("{name} {layer}"))
+        );
+
+        let mut keys = keys.iter();
+        for (row_i, row) in self.physical_layout.iter().enumerate() {
+            for (col_i, effort) in row.chars().enumerate() {
+                if !effort.is_ascii_digit() {
+                    continue;
+                }
+                let x = MARGIN + col_i as f32 * KEY_SIZE;
+                let y = MARGIN + row_i as f32 * KEY_SIZE;
+
+                let effort_class = format!("effort_{effort}");
+                let label = match keys.next() {
+                    Some(key) => {
+                        let mut label = self.label(layer, key);
+                        if highlight.contains(key) {
+                            label.class = HIGHLIGHT_CLASS;
+                        }
+                        label
+                    }
+                    None => KeyLabel {
+                        title: "",
+                        hold_title: None,
+                        class: &effort_class,
+                    },
+                };
+                self.render_key(&mut svg, x, y, &label)?;
+            }
+        }
+
+        svg.push_str("</svg>");
+        Ok(svg)
+    }
+
+    fn render_key(&self, svg: &mut String, x: f32, y: f32, label: &KeyLabel) -> Result<()> {
+        // The fill is overridden with css, but the feed has no css.
+        let fill = match self.colors.get(label.class) {
+            Some(color) => format!(
+                r#" fill="{}""#,
+                html_escape::encode_double_quoted_attribute(color)
+            ),
+            None => String::new(),
+        };
+        let inner_size = KEY_SIZE - 2.0 * KEY_INSET;
+        write!(
+            svg,
+            r#"<g class="keycap {}"><rect x="{x}" y="{y}" width="{KEY_SIZE}" height="{KEY_SIZE}" rx="5"{fill} class="outer border"/><rect x="{}" y="{}" width="{inner_size}" height="{inner_size}" rx="5"{fill} class="inner border"/>"#,
+            html_escape::encode_double_quoted_attribute(label.class),
+            x + KEY_INSET,
+            y + KEY_INSET - 2.0,
+        )?;
+
+        let cx = x + KEY_SIZE / 2.0;
+        let cy = y + KEY_SIZE / 2.0 - 2.0;
+        let lines: Vec<_> = label.title.lines().collect();
+        if !lines.is_empty() {
+            // Center multi-line titles around the middle of the key.
+            let line_height = 12.0;
+            let first_y = cy - (lines.len() - 1) as f32 * line_height / 2.0;
+            write!(
+                svg,
+                r#"<text x="{cx}" y="{first_y}" text-anchor="middle" dominant-baseline="middle" class="main">"#
+            )?;
+            for (i, line) in lines.iter().enumerate() {
+                let dy = if i == 0 { 0.0 } else { line_height };
+                write!(
+                    svg,
+                    r#"<tspan x="{cx}" dy="{dy}">{}</tspan>"#,
+                    html_escape::encode_text(line)
+                )?;
+            }
+            svg.push_str("</text>");
+        }
+        if let Some(hold_title) = label.hold_title {
+            write!(
+                svg,
+                r#"<text x="{cx}" y="{}" text-anchor="middle" dominant-baseline="middle" class="sub">{}</text>"#,
+                y + KEY_SIZE - KEY_INSET - 4.0,
+                html_escape::encode_text(hold_title)
+            )?;
+        }
+        svg.push_str("</g>");
+        Ok(())
+    }
+}
+
+fn strip_key_prefix(key: &str) -> &str {
+    ["KC_", "SE_"]
+        .iter()
+        .find_map(|prefix| key.strip_prefix(prefix))
+        .unwrap_or(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout() -> Result<KeyboardLayout> {
+        KeyboardLayout::parse(
+            r##"{
+  "physical_layout": ["01", " 2"],
+  "layers": {
+    "default": [
+      { "keys": ["_______"], "title": "", "class": "blank" },
+      { "keys": ["MT_SPC"], "title": "Space", "hold_title": "NAV", "class": "layer_down" }
+    ],
+    "_NUM": [{ "keys": ["SE_J"], "title": "Shift\nJ", "class": "management" }]
+  },
+  "colors": { "blank": "#fff7f1", "default": "#ffecc9" }
+}"##,
+        )
+    }
+
+    #[test]
+    fn test_key_labels() -> Result<()> {
+        let layout = layout()?;
+        assert_eq!(layout.key_count(), 3);
+        assert_eq!(
+            layout.label("_NUM", "SE_J"),
+            KeyLabel {
+                title: "Shift\nJ",
+                hold_title: None,
+                class: "management"
+            }
+        );
+        assert_eq!(
+            layout.label("_NUM", "MT_SPC"),
+            KeyLabel {
+                title: "Space",
+                hold_title: Some("NAV"),
+                class: "layer_down"
+            }
+        );
+        assert_eq!(
+            layout.label("default", "SE_J"),
+            KeyLabel {
+                title: "J",
+                hold_title: None,
+                class: "default"
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_svg() -> Result<()> {
+        let layout = layout()?;
+        let svg = layout.render_svg(
+            "test",
+            "_NUM",
+            &["SE_J", "_______", "MT_SPC"],
+            &["MT_SPC"].into(),
+        )?;
+        assert!(svg.starts_with(r#"<svg class="keymap test" width="128" height="128""#));
+        assert!(
+            svg.contains(r#"<tspan x="37" dy="0">Shift</tspan><tspan x="37" dy="12">J</tspan>"#)
+        );
+        assert!(svg.contains(r##"<g class="keycap blank"><rect x="64" y="10" width="54" height="54" rx="5" fill="#fff7f1" class="outer border"/>"##));
+        assert!(svg.contains(r#"<g class="keycap active_layer">"#));
+        assert!(svg.contains(r#"class="sub">NAV</text>"#));
+
+        let effort = layout.render_svg("test", "default", &[], &HashSet::new())?;
+        assert!(effort.contains(r#"<g class="keycap effort_2">"#));
+
+        assert!(layout
+            .render_svg("test", "_NUM", &["SE_J"], &HashSet::new())
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_layouts() -> Result<()> {
+        for name in ["cybershard", "leftie", "t34"] {
+            let layout = KeyboardLayout::load(name)?;
+            assert!(layout.key_count() > 0);
+            assert!(layout.layers.contains_key("default"));
+        }
+        assert!(KeyboardLayout::path("../Cargo").is_none());
+        Ok(())
+    }
+}
@@ -38,6 +38,8 @@ impl<'a, I: Iterator<Item = Event<'a>>> Iterator for CodeBlockSyntaxHighlight<'a
             path: None,
             linenum_start: None,
             highlight_lines: None,
+            diff: false,
+            callout_id: None,
         }
         .push(&mut res);
 
@@ -1,22 +1,25 @@
-use crate::markup::embed_youtube::embed_yt;
+use crate::markup::embeds::{embed_link, EmbedCache};
 use pulldown_cmark::{Event, Tag, TagEnd};
 use std::iter::Peekable;
+use tracing::warn;
 
-pub struct EmbedYoutube<'a, I: Iterator<Item = Event<'a>>> {
+pub struct EmbedLinks<'a, 'b, I: Iterator<Item = Event<'a>>> {
     parent: Peekable<I>,
     embed_thumbnail: bool,
+    cache: Option<&'b EmbedCache>,
 }
 
-impl<'a, I: Iterator<Item = Event<'a>>> EmbedYoutube<'a, I> {
-    pub fn new(parent: I, embed_thumbnail: bool) -> Self {
+impl<'a, 'b, I: Iterator<Item = Event<'a>>> EmbedLinks<'a, 'b, I> {
+    pub fn new(parent: I, embed_thumbnail: bool, cache: Option<&'b EmbedCache>) -> Self {
         Self {
             parent: parent.peekable(),
             embed_thumbnail,
+            cache,
         }
     }
 }
 
-impl<'a, I: Iterator<Item = Event<'a>>> Iterator for EmbedYoutube<'a, I> {
+impl<'a, 'b, I: Iterator<Item = Event<'a>>> Iterator for EmbedLinks<'a, 'b, I> {
     type Item = Event<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -30,17 +33,19 @@ impl<'a, I: Iterator<Item = Event<'a>>> Iterator for EmbedYoutube<'a, I> {
             _ => return Some(start),
         };
 
-        let embedded = match embed_yt(text.as_ref(), self.embed_thumbnail)
-            .expect("Failed to process youtube link")
-        {
-            Some(x) => x,
-            None => return Some(start),
+        let embedded = match embed_link(text.as_ref(), self.embed_thumbnail, self.cache) {
+            Ok(Some(x)) => x,
+            Ok(None) => return Some(start),
+            Err(err) => {
+                warn!("Failed to embed link `{text}`: {err}");
+                return Some(start);
+            }
         };
 
-        // Consume the text and ending paragraph, which we should replace with the embedded video.
+        // Consume the text and ending paragraph, which we should replace with the embed.
         self.parent.next(); // Skip the Text
         if self.parent.next() != Some(Event::End(TagEnd::Paragraph)) {
-            panic!("Bare Youtube link not ending with a paragraph");
+            panic!("Bare embedded link not ending with a paragraph");
         }
 
         Some(Event::Html(embedded.into()))
@@ -54,7 +59,7 @@ mod tests {
 
     fn convert(s: &str, embed_thumbnail: bool) -> String {
         let parser = Parser::new_ext(s, Options::all());
-        let transformed = EmbedYoutube::new(parser, embed_thumbnail);
+        let transformed = EmbedLinks::new(parser, embed_thumbnail, None);
         let mut body = String::new();
         html::push_html(&mut body, transformed);
         body
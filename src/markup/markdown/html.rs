@@ -27,7 +27,7 @@ impl Figure<'_> {
         }
 
         if let Some(caption) = &self.caption {
-            let caption = strip_one_paragraph(markdown_to_html(caption).html.0.into());
+            let caption = strip_one_paragraph(markdown_to_html(caption, None).html.0.into());
             s.push_str(&format!("<figcaption>{caption}</figcaption>\n"));
         }
         s.push_str("</figure>");
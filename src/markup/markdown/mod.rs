@@ -1,7 +1,7 @@
 mod attrs;
 mod auto_figures;
 mod code;
-mod embed_youtube;
+mod embed_links;
 mod fenced_blocks;
 mod html;
 mod pd_html;
@@ -11,7 +11,7 @@ mod transform_headers;
 
 use auto_figures::AutoFigures;
 use code::{CodeBlockSyntaxHighlight, InlineCodeSyntaxHighlight};
-use embed_youtube::EmbedYoutube;
+use embed_links::EmbedLinks;
 use fenced_blocks::parse_fenced_blocks;
 use pulldown_cmark::{BrokenLink, CowStr, LinkType, Options, Parser};
 use quote_attrs::QuoteAttrs;
@@ -23,25 +23,25 @@ use transform_headers::TransformHeaders;
 
 use crate::util;
 
-use crate::markup::{FeedHtml, Html, HtmlParseRes};
+use crate::markup::{EmbedCache, FeedHtml, Html, HtmlParseRes};
 
-pub fn markdown_to_html(markdown: &str) -> HtmlParseRes {
+pub fn markdown_to_html(markdown: &str, embed_cache: Option<&EmbedCache>) -> HtmlParseRes {
     HtmlParseRes {
-        html: Html(parse_markdown(&preprocess(markdown))),
+        html: Html(parse_markdown(&preprocess(markdown), embed_cache)),
         lookup: None,
         embedded_files: HashSet::new(),
     }
 }
 
-pub fn markdown_to_html_feed(markdown: &str) -> FeedHtml {
-    FeedHtml(parse_markdown_to_feed(&preprocess(markdown)))
+pub fn markdown_to_html_feed(markdown: &str, embed_cache: Option<&EmbedCache>) -> FeedHtml {
+    FeedHtml(parse_markdown_to_feed(&preprocess(markdown), embed_cache))
 }
 
 fn preprocess(s: &str) -> Cow<str> {
     parse_fenced_blocks(s)
 }
 
-fn parse_markdown(s: &str) -> String {
+fn parse_markdown(s: &str, embed_cache: Option<&EmbedCache>) -> String {
     let mut cb = |link: BrokenLink<'_>| -> Option<(CowStr, CowStr)> {
         match link.link_type {
             // Try to convert shortcut links to fragment links
@@ -60,7 +60,7 @@ fn parse_markdown(s: &str) -> String {
     let transformed = Parser::new_with_broken_link_callback(s, Options::all(), Some(&mut cb));
     let transformed = TransformHeaders::new(transformed);
     let transformed = AutoFigures::new(transformed);
-    let transformed = EmbedYoutube::new(transformed, true);
+    let transformed = EmbedLinks::new(transformed, true, embed_cache);
     let transformed = CodeBlockSyntaxHighlight::new(transformed);
     let transformed = InlineCodeSyntaxHighlight::new(transformed);
     let transformed = QuoteAttrs::new(transformed);
@@ -71,10 +71,10 @@ fn parse_markdown(s: &str) -> String {
     body
 }
 
-fn parse_markdown_to_feed(s: &str) -> String {
+fn parse_markdown_to_feed(s: &str, embed_cache: Option<&EmbedCache>) -> String {
     let transformed = Parser::new_ext(s, Options::all());
     let transformed = AutoFigures::new(transformed);
-    let transformed = EmbedYoutube::new(transformed, false);
+    let transformed = EmbedLinks::new(transformed, false, embed_cache);
     let transformed = CodeBlockSyntaxHighlight::new(transformed);
     let transformed = InlineCodeSyntaxHighlight::new(transformed);
     let transformed = QuoteAttrs::new(transformed);
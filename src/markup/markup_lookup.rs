@@ -5,6 +5,8 @@ use regex::Regex;
 use serde::Serialize;
 use std::{collections::HashMap, ops::Range};
 
+use crate::markup::LintRule;
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct Pos {
     pub row: usize,
@@ -122,6 +124,14 @@ pub enum Element {
     Heading(Heading),
     Img(Img),
     Todo(TodoTag),
+    MathError { message: String },
+    UnknownCitation { key: String },
+    CodeIncludeError { message: String },
+    GlossaryTerm { term: String, definition: String },
+    UndefinedTerm { term: String },
+    UndefinedFootnote { label: String },
+    GraphvizError { message: String },
+    ChartError { message: String },
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -144,6 +154,45 @@ pub struct HeadingLookup {
     pub char_range: Range<usize>,
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum LinkTextKind {
+    Link,
+    Img,
+}
+
+/// The text of a link or the alt text of an image.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LinkTextLookup {
+    pub kind: LinkTextKind,
+    pub text: String,
+    pub range: PosRange,
+    pub char_range: Range<usize>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TableLookup {
+    pub has_header: bool,
+    pub range: PosRange,
+    pub char_range: Range<usize>,
+}
+
+/// A word the spell checker doesn't know.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MisspellingLookup {
+    pub word: String,
+    pub range: PosRange,
+    pub char_range: Range<usize>,
+}
+
+/// Text that breaks one of the prose lint rules.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ProseLintLookup {
+    pub rule: LintRule,
+    pub message: String,
+    pub range: PosRange,
+    pub char_range: Range<usize>,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct ElementLookup {
     pub element: Element,
@@ -157,6 +206,7 @@ type HeadingId = String;
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct ChangeLogItem {
     pub date: NaiveDate,
+    /// The description rendered as html.
     pub description: String,
 }
 
@@ -165,6 +215,34 @@ pub struct ChangeLog {
     pub items: Vec<ChangeLogItem>,
 }
 
+impl ChangeLog {
+    /// The most recent change.
+    pub fn latest(&self) -> Option<&ChangeLogItem> {
+        self.items.iter().max_by_key(|item| item.date)
+    }
+}
+
+/// Options given to the `:table-of-content:` symbol, such as `{depth=3 numbered=true}`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+pub struct TocOptions {
+    /// The deepest heading level to include.
+    pub depth: u16,
+    /// Prefix entries with their section number, such as `2.1`.
+    pub numbered: bool,
+    /// Render the table of content in a sidebar instead of inline.
+    pub sidebar: bool,
+}
+
+impl Default for TocOptions {
+    fn default() -> Self {
+        Self {
+            depth: 2,
+            numbered: false,
+            sidebar: false,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct MarkupLookup {
     // Element lookup by character position.
@@ -173,10 +251,17 @@ pub struct MarkupLookup {
     // Element lookup by id or type.
     pub link_defs: HashMap<LinkLabel, Vec<LinkDefLookup>>,
     pub headings: HashMap<HeadingId, Vec<HeadingLookup>>,
+    pub link_texts: Vec<LinkTextLookup>,
+    pub tables: Vec<TableLookup>,
+    pub misspellings: Vec<MisspellingLookup>,
+    pub prose_lints: Vec<ProseLintLookup>,
 
     // The post's changelog
     pub changelog: Option<ChangeLog>,
 
+    // Options of the post's table of content, if it has one.
+    pub toc: Option<TocOptions>,
+
     // Position translations.
     prev_line_size_sum: Vec<usize>,
     // Line calculation offset, to handle frontmatter that isn't
@@ -203,7 +288,12 @@ impl MarkupLookup {
             char_pos_to_element: RangeMap::new(),
             link_defs: HashMap::new(),
             headings: HashMap::new(),
+            link_texts: Vec::new(),
+            tables: Vec::new(),
+            misspellings: Vec::new(),
+            prose_lints: Vec::new(),
             changelog: None,
+            toc: None,
             prev_line_size_sum: line_size_sum,
             line_calc_offset,
         }
@@ -308,6 +398,60 @@ impl MarkupLookup {
     pub fn insert_link_def(&mut self, link_def: LinkDef, char_range: Range<usize>) {
         self.insert_element(Element::LinkDef(link_def), char_range);
     }
+
+    pub fn insert_link_text(&mut self, kind: LinkTextKind, text: String, char_range: Range<usize>) {
+        self.link_texts.push(LinkTextLookup {
+            kind,
+            text,
+            range: self.pos_range(&char_range),
+            char_range,
+        });
+    }
+
+    pub fn insert_table(&mut self, has_header: bool, char_range: Range<usize>) {
+        self.tables.push(TableLookup {
+            has_header,
+            range: self.pos_range(&char_range),
+            char_range,
+        });
+    }
+
+    pub fn insert_misspelling(&mut self, word: String, char_range: Range<usize>) {
+        self.misspellings.push(MisspellingLookup {
+            word,
+            range: self.pos_range(&char_range),
+            char_range,
+        });
+    }
+
+    pub fn insert_prose_lint(&mut self, rule: LintRule, message: String, char_range: Range<usize>) {
+        self.prose_lints.push(ProseLintLookup {
+            rule,
+            message,
+            range: self.pos_range(&char_range),
+            char_range,
+        });
+    }
+
+    /// Remove a heading that isn't rendered, such as a heading used as a tab label.
+    pub fn remove_heading(&mut self, id: &str) {
+        let headings = match self.headings.get_mut(id) {
+            Some(headings) if !headings.is_empty() => headings,
+            _ => return,
+        };
+        let removed = headings.remove(0);
+        if headings.is_empty() {
+            self.headings.remove(id);
+        }
+        self.char_pos_to_element.remove(removed.char_range);
+    }
+
+    /// Headings in the order they appear.
+    pub fn headings_in_order(&self) -> Vec<&HeadingLookup> {
+        let mut res: Vec<_> = self.headings.values().flatten().collect();
+        res.sort_by_key(|heading| heading.char_range.start);
+        res
+    }
 }
 
 #[cfg(test)]
@@ -1,8 +1,15 @@
+mod bibliography;
 mod djot;
 mod embed_youtube;
+mod embeds;
+mod glossary;
 mod graphs;
+mod graphviz;
+mod keyboard_layout;
 mod markdown;
 pub mod markup_lookup;
+mod prose_lint;
+mod spelling;
 mod syntax_highlight;
 
 use camino::Utf8Path;
@@ -14,16 +21,24 @@ use std::borrow::Cow;
 use std::collections::HashSet;
 use std::fs;
 use std::ops::Deref;
+use tera::Tera;
 use tracing::{error, warn};
 use walkdir::WalkDir;
 use yaml_front_matter::{Document, YamlFrontMatter};
 
-pub use self::djot::{DivTransform, SymbolTransform};
+pub use self::bibliography::{BibEntry, Bibliography, CitationStyle};
+pub use self::djot::{heading_tree, DivTransform, SymbolTransform, TocEntry};
+pub use self::embeds::{EmbedCache, EMBED_CACHE};
+pub use self::glossary::{term_id, Glossary};
+pub use self::graphs::{PostStatsGraph, PostsGraph};
+pub use self::prose_lint::{LintRule, ProseLint};
+pub use self::spelling::{add_word, SpellChecker, DICTIONARIES_DIR};
 pub use self::syntax_highlight::syntect_highlighter;
 pub use markup_lookup::MarkupLookup;
 
 use self::djot::{djot_to_html, djot_to_html_feed};
 use self::markdown::{markdown_to_html, markdown_to_html_feed};
+use crate::git::SiteRepository;
 use crate::paths::AbsPath;
 use crate::paths::FilePath;
 use crate::paths::RelPath;
@@ -105,15 +120,15 @@ impl Markup {
 
     pub fn parse(&self, context: ParseContext) -> Result<HtmlParseRes> {
         match self {
-            Self::Markdown(s) => Ok(markdown_to_html(s)),
+            Self::Markdown(s) => Ok(markdown_to_html(s, context.embed_cache)),
             Self::Djot(s) => djot_to_html(s, context.in_feed(false)),
         }
     }
 
-    pub fn parse_feed(&self, _context: ParseContext) -> Result<FeedHtml> {
+    pub fn parse_feed(&self, context: ParseContext) -> Result<FeedHtml> {
         match self {
-            Self::Markdown(s) => Ok(markdown_to_html_feed(s)),
-            Self::Djot(s) => djot_to_html_feed(s),
+            Self::Markdown(s) => Ok(markdown_to_html_feed(s, context.embed_cache)),
+            Self::Djot(s) => djot_to_html_feed(s, context.in_feed(true)),
         }
     }
 
@@ -235,6 +250,15 @@ pub fn find_markup_files<'a, B: AsRef<Utf8Path>, P: 'a + AsRef<Utf8Path>>(
         .collect()
 }
 
+/// Prefix a generated id, such as `sidenote-1`, so it's unique when
+/// several items are rendered on the same page.
+pub fn prefixed_id(prefix: Option<&str>, id: &str) -> String {
+    match prefix {
+        Some(prefix) => format!("{prefix}-{id}"),
+        None => id.to_string(),
+    }
+}
+
 fn strip_one_paragraph(html: Cow<str>) -> Cow<str> {
     // Why do something insane like use regex to strip a paragraph?
     // I tried to use `scraper::Html` to parse it properly, but the attribute order
@@ -259,6 +283,20 @@ pub struct ParseContext<'a> {
     pub in_feed: bool,
     pub create_lookup: bool,
     pub markup_meta_line_count: usize,
+    pub bibliography: Option<&'a Bibliography>,
+    pub glossary: Option<&'a Glossary>,
+    pub spelling: Option<&'a SpellChecker>,
+    pub prose_lint: Option<&'a ProseLint>,
+    pub lint_rules: &'a [LintRule],
+    pub citation_style: CitationStyle,
+    /// Site templates, used to render divs with templates in `templates/divs`.
+    pub templates: Option<&'a Tera>,
+    /// The site repository, used to include code from earlier commits.
+    pub repository: Option<&'a SiteRepository>,
+    /// Prefix for generated ids, for items rendered together on one page.
+    pub id_prefix: Option<&'a str>,
+    /// Metadata for embedded links, loaded from `embeds.toml`.
+    pub embed_cache: Option<&'a EmbedCache>,
 }
 
 impl<'a> ParseContext<'a> {
@@ -291,6 +329,52 @@ impl<'a> ParseContext<'a> {
         self
     }
 
+    pub fn with_bibliography(mut self, bibliography: &'a Bibliography) -> Self {
+        self.bibliography = Some(bibliography);
+        self
+    }
+
+    pub fn with_glossary(mut self, glossary: &'a Glossary) -> Self {
+        self.glossary = Some(glossary);
+        self
+    }
+
+    pub fn with_spelling(mut self, spelling: &'a SpellChecker) -> Self {
+        self.spelling = Some(spelling);
+        self
+    }
+
+    pub fn with_prose_lint(mut self, prose_lint: &'a ProseLint, rules: &'a [LintRule]) -> Self {
+        self.prose_lint = Some(prose_lint);
+        self.lint_rules = rules;
+        self
+    }
+
+    pub fn with_id_prefix(mut self, id_prefix: &'a str) -> Self {
+        self.id_prefix = Some(id_prefix);
+        self
+    }
+
+    pub fn with_templates(mut self, templates: &'a Tera) -> Self {
+        self.templates = Some(templates);
+        self
+    }
+
+    pub fn with_repository(mut self, repository: &'a SiteRepository) -> Self {
+        self.repository = Some(repository);
+        self
+    }
+
+    pub fn with_embed_cache(mut self, embed_cache: &'a EmbedCache) -> Self {
+        self.embed_cache = Some(embed_cache);
+        self
+    }
+
+    pub fn citation_style(mut self, style: CitationStyle) -> Self {
+        self.citation_style = style;
+        self
+    }
+
     pub fn log_broken_link(self, target: &str) {
         if self.in_feed {
             return;
@@ -305,6 +389,34 @@ impl<'a> ParseContext<'a> {
         }
     }
 
+    pub fn log_unknown_citation(self, key: &str) {
+        if self.in_feed {
+            return;
+        }
+
+        let msg = format!("Unknown citation `{key}` in {}", self.format_path());
+
+        if self.is_draft {
+            warn!("{}", msg);
+        } else {
+            error!("{}", msg);
+        }
+    }
+
+    pub fn log_undefined_term(self, term: &str) {
+        if self.in_feed {
+            return;
+        }
+
+        let msg = format!("Undefined glossary term `{term}` in {}", self.format_path());
+
+        if self.is_draft {
+            warn!("{}", msg);
+        } else {
+            error!("{}", msg);
+        }
+    }
+
     pub fn log_todo_comment(self, comment: &str) {
         warn!("{} in {}", comment, self.format_path())
     }
@@ -0,0 +1,294 @@
+use eyre::{eyre, Result};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::ops::Range;
+
+use crate::paths::AbsPath;
+
+/// Rules that can be enabled in `prose_lint.toml` or per post with
+/// `prose_lint = ["repeated-words"]` in the frontmatter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LintRule {
+    /// The same word twice in a row, such as "the the".
+    RepeatedWords,
+    /// Sentences with more words than `max_words`.
+    LongSentences,
+    /// Words such as "very" and "quite" that weaken the text.
+    WeaselWords,
+    /// Product names that aren't spelled the preferred way, such as "NeoVim".
+    ProductNames,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct ProseLintFile {
+    /// Rules used by posts that don't list their own.
+    #[serde(default)]
+    default: Vec<LintRule>,
+    #[serde(default)]
+    long_sentences: LongSentences,
+    #[serde(default)]
+    weasel_words: WeaselWords,
+    /// Preferred spelling mapped to the spellings to replace.
+    #[serde(default)]
+    product_names: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct LongSentences {
+    max_words: usize,
+}
+
+impl Default for LongSentences {
+    fn default() -> Self {
+        Self { max_words: 40 }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct WeaselWords {
+    words: Vec<String>,
+}
+
+/// A rule that matched, with the range of the offending text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintWarning {
+    pub rule: LintRule,
+    pub range: Range<usize>,
+    pub message: String,
+}
+
+/// Prose lint rules loaded from `prose_lint.toml`.
+#[derive(Debug)]
+pub struct ProseLint {
+    pub default_rules: Vec<LintRule>,
+    max_sentence_words: usize,
+    weasel_words: Option<Regex>,
+    /// Matches a misspelled product name, mapped to the preferred spelling.
+    product_names: Vec<(Regex, String)>,
+}
+
+impl Default for ProseLint {
+    fn default() -> Self {
+        Self {
+            default_rules: Vec::new(),
+            max_sentence_words: LongSentences::default().max_words,
+            weasel_words: None,
+            product_names: Vec::new(),
+        }
+    }
+}
+
+impl ProseLint {
+    pub fn load(path: &AbsPath) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)?;
+        Self::parse(&content).map_err(|err| eyre!("Failed to parse {path}: {err}"))
+    }
+
+    pub fn parse(s: &str) -> Result<Self> {
+        let file: ProseLintFile = toml::from_str(s)?;
+
+        let weasel_words = if file.weasel_words.words.is_empty() {
+            None
+        } else {
+            Some(word_regex(&file.weasel_words.words, true)?)
+        };
+
+        let product_names = file
+            .product_names
+            .into_iter()
+            .map(|(name, variants)| Ok((word_regex(&variants, false)?, name)))
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            default_rules: file.default,
+            max_sentence_words: file.long_sentences.max_words,
+            weasel_words,
+            product_names,
+        })
+    }
+
+    /// Check `text` against `rules`, where `text` is the text of a paragraph or heading.
+    pub fn lint(&self, text: &str, rules: &[LintRule]) -> Vec<LintWarning> {
+        let mut res = Vec::new();
+        for rule in rules {
+            match rule {
+                LintRule::RepeatedWords => self.repeated_words(text, &mut res),
+                LintRule::LongSentences => self.long_sentences(text, &mut res),
+                LintRule::WeaselWords => self.weasel_words(text, &mut res),
+                LintRule::ProductNames => self.product_names(text, &mut res),
+            }
+        }
+        res.sort_by_key(|warning| warning.range.start);
+        res
+    }
+
+    fn repeated_words(&self, text: &str, res: &mut Vec<LintWarning>) {
+        let mut prev: Option<(Range<usize>, &str)> = None;
+        for m in WORD.find_iter(text) {
+            if let Some((prev_range, prev_word)) = prev {
+                let between = &text[prev_range.end..m.start()];
+                if between.chars().all(char::is_whitespace)
+                    && prev_word.eq_ignore_ascii_case(m.as_str())
+                {
+                    res.push(LintWarning {
+                        rule: LintRule::RepeatedWords,
+                        range: prev_range.start..m.end(),
+                        message: format!("Repeated word: `{}`", m.as_str()),
+                    });
+                }
+            }
+            prev = Some((m.range(), m.as_str()));
+        }
+    }
+
+    fn long_sentences(&self, text: &str, res: &mut Vec<LintWarning>) {
+        for sentence in SENTENCE.find_iter(text) {
+            let words = WORD.find_iter(sentence.as_str()).count();
+            if words > self.max_sentence_words {
+                let trimmed = sentence.as_str().trim_end();
+                res.push(LintWarning {
+                    rule: LintRule::LongSentences,
+                    range: sentence.start()..sentence.start() + trimmed.len(),
+                    message: format!(
+                        "Long sentence: {words} words, consider splitting it up (max {})",
+                        self.max_sentence_words
+                    ),
+                });
+            }
+        }
+    }
+
+    fn weasel_words(&self, text: &str, res: &mut Vec<LintWarning>) {
+        let re = match &self.weasel_words {
+            Some(re) => re,
+            None => return,
+        };
+        for m in re.find_iter(text) {
+            res.push(LintWarning {
+                rule: LintRule::WeaselWords,
+                range: m.range(),
+                message: format!("Weasel word: `{}`", m.as_str()),
+            });
+        }
+    }
+
+    fn product_names(&self, text: &str, res: &mut Vec<LintWarning>) {
+        for (re, name) in &self.product_names {
+            for m in re.find_iter(text) {
+                res.push(LintWarning {
+                    rule: LintRule::ProductNames,
+                    range: m.range(),
+                    message: format!("Inconsistent spelling: `{}`, use `{name}`", m.as_str()),
+                });
+            }
+        }
+    }
+}
+
+/// A regex matching any of `words` as whole words.
+fn word_regex(words: &[String], ignore_case: bool) -> Result<Regex> {
+    let alternatives: Vec<_> = words.iter().map(|word| regex::escape(word)).collect();
+    let flags = if ignore_case { "(?i)" } else { "" };
+    Ok(Regex::new(&format!(
+        r"{flags}\b(?:{})\b",
+        alternatives.join("|")
+    ))?)
+}
+
+lazy_static! {
+    static ref WORD: Regex = Regex::new(r"\p{Alphabetic}+(?:'\p{Alphabetic}+)*").unwrap();
+    // A sentence ends with punctuation followed by whitespace, or at the end of the text.
+    static ref SENTENCE: Regex = Regex::new(r"(?s)[^\s].*?(?:[.!?]+(?:\s+|$)|$)").unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lint() -> ProseLint {
+        ProseLint::parse(
+            r#"
+default = ["repeated-words"]
+
+[long-sentences]
+max-words = 5
+
+[weasel-words]
+words = ["very", "quite"]
+
+[product-names]
+Neovim = ["NeoVim", "neovim"]
+"#,
+        )
+        .unwrap()
+    }
+
+    fn messages<'a>(text: &'a str, rules: &[LintRule]) -> Vec<(&'a str, String)> {
+        lint()
+            .lint(text, rules)
+            .into_iter()
+            .map(|warning| (&text[warning.range], warning.message))
+            .collect()
+    }
+
+    #[test]
+    fn test_repeated_words() {
+        assert_eq!(
+            messages("This is the the end. End end", &[LintRule::RepeatedWords]),
+            vec![
+                ("the the", "Repeated word: `the`".to_string()),
+                ("End end", "Repeated word: `end`".to_string())
+            ]
+        );
+        assert!(messages("That, that is it", &[LintRule::RepeatedWords]).is_empty());
+    }
+
+    #[test]
+    fn test_long_sentences() {
+        assert_eq!(
+            messages(
+                "Short one. This one has far too many words! Ok?",
+                &[LintRule::LongSentences]
+            ),
+            vec![(
+                "This one has far too many words!",
+                "Long sentence: 7 words, consider splitting it up (max 5)".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_weasel_words_and_product_names() {
+        assert_eq!(
+            messages(
+                "Very quiet, quite NeoVim. Neovim and neovims",
+                &[LintRule::WeaselWords, LintRule::ProductNames]
+            ),
+            vec![
+                ("Very", "Weasel word: `Very`".to_string()),
+                ("quite", "Weasel word: `quite`".to_string()),
+                (
+                    "NeoVim",
+                    "Inconsistent spelling: `NeoVim`, use `Neovim`".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_default_rules() {
+        assert_eq!(lint().default_rules, vec![LintRule::RepeatedWords]);
+        assert!(ProseLint::default()
+            .lint("the the", &[LintRule::WeaselWords])
+            .is_empty());
+    }
+}
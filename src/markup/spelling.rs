@@ -0,0 +1,158 @@
+use eyre::{eyre, Result};
+use lazy_static::lazy_static;
+use regex::Regex;
+use spellbook::Dictionary;
+use std::collections::HashSet;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::ops::Range;
+use tracing::warn;
+
+use crate::paths::AbsPath;
+
+/// Hunspell dictionaries and the site word list are read from here.
+pub const DICTIONARIES_DIR: &str = "dictionaries";
+/// Words that are correct on the site but missing from the dictionary, one per line.
+pub const WORD_LIST: &str = "words.txt";
+const LANGUAGE: &str = "en_US";
+
+/// Checks words against a Hunspell dictionary, such as `dictionaries/en_US.{aff,dic}`,
+/// and the site's own word list.
+///
+/// The dictionary isn't committed, see the README for where to get it.
+/// Without a dictionary every word is considered correct.
+#[derive(Default)]
+pub struct SpellChecker {
+    dictionary: Option<Dictionary>,
+    words: HashSet<String>,
+}
+
+impl SpellChecker {
+    /// Load the dictionary and word list from `dir` if they exist.
+    pub fn load(dir: &AbsPath) -> Result<Self> {
+        let aff_path = dir.join(format!("{LANGUAGE}.aff"));
+        let dic_path = dir.join(format!("{LANGUAGE}.dic"));
+        if !aff_path.exists() || !dic_path.exists() {
+            warn!("Spell checking is disabled, missing the dictionary {aff_path} and {dic_path}");
+            return Ok(Self::default());
+        }
+        let words_path = dir.join(WORD_LIST);
+        let words = if words_path.exists() {
+            fs::read_to_string(&words_path)?
+        } else {
+            String::new()
+        };
+        Self::new(
+            &fs::read_to_string(&aff_path)?,
+            &fs::read_to_string(&dic_path)?,
+            &words,
+        )
+        .map_err(|err| eyre!("Failed to load dictionary {dic_path}: {err}"))
+    }
+
+    pub fn new(aff: &str, dic: &str, words: &str) -> Result<Self> {
+        let dictionary = Dictionary::new(aff, dic).map_err(|err| eyre!("{err}"))?;
+        Ok(Self {
+            dictionary: Some(dictionary),
+            words: parse_word_list(words),
+        })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.dictionary.is_some()
+    }
+
+    pub fn check(&self, word: &str) -> bool {
+        let dictionary = match &self.dictionary {
+            Some(dictionary) => dictionary,
+            None => return true,
+        };
+        self.words.contains(word)
+            || self.words.contains(&word.to_lowercase())
+            || dictionary.check(word)
+    }
+
+    /// Find misspelled words in `text`, with their byte ranges.
+    ///
+    /// Words inside urls aren't checked.
+    pub fn misspellings<'a>(
+        &'a self,
+        text: &'a str,
+    ) -> impl Iterator<Item = (Range<usize>, &'a str)> {
+        let urls: Vec<_> = URL.find_iter(text).map(|m| m.range()).collect();
+        WORD.find_iter(text)
+            .filter(move |m| !urls.iter().any(|url| url.contains(&m.start())))
+            .map(|m| (m.range(), m.as_str()))
+            .filter(|(_, word)| should_check(word))
+            .filter(|(_, word)| !self.check(word))
+    }
+}
+
+// The dictionary doesn't implement `Debug`.
+impl fmt::Debug for SpellChecker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SpellChecker")
+            .field("enabled", &self.is_enabled())
+            .field("words", &self.words)
+            .finish()
+    }
+}
+
+/// Add `word` to the word list in `dir`.
+pub fn add_word(dir: &AbsPath, word: &str) -> Result<()> {
+    let word = word.trim();
+    if word.is_empty() || word.contains(char::is_whitespace) {
+        return Err(eyre!("Invalid word `{word}`"));
+    }
+    let path = dir.join(WORD_LIST);
+    if path.exists() && parse_word_list(&fs::read_to_string(&path)?).contains(word) {
+        return Ok(());
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    writeln!(file, "{word}")?;
+    Ok(())
+}
+
+fn parse_word_list(s: &str) -> HashSet<String> {
+    s.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect()
+}
+
+/// Skip single letters, acronyms and words that look like code.
+fn should_check(word: &str) -> bool {
+    word.chars().count() > 1
+        && !word.chars().all(|c| c.is_uppercase() || c == '\'')
+        && !word.chars().skip(1).any(|c| c.is_uppercase())
+}
+
+lazy_static! {
+    // Letters, with apostrophes inside words such as "don't".
+    static ref WORD: Regex = Regex::new(r"\p{Alphabetic}+(?:'\p{Alphabetic}+)*").unwrap();
+    static ref URL: Regex = Regex::new(r"\S+://\S+").unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AFF: &str = "SET UTF-8\n\nSFX S Y 1\nSFX S 0 s .\n";
+    const DIC: &str = "4\nhello\nworld/S\ndon't\nis\n";
+
+    #[test]
+    fn test_misspellings() -> Result<()> {
+        let checker = SpellChecker::new(AFF, DIC, "# Site words\nDjot\n")?;
+        assert!(checker.check("worlds"));
+        assert!(checker.check("Djot"));
+
+        let text = "Hello worlds, don't wrold Djot LSP camelCase https://wrold.com";
+        let found: Vec<_> = checker.misspellings(text).collect();
+        assert_eq!(found, vec![(20..25, "wrold")]);
+
+        assert!(SpellChecker::default().check("wrold"));
+        Ok(())
+    }
+}
@@ -26,9 +26,73 @@ pub enum Code<'a> {
         path: Option<&'a str>,
         linenum_start: Option<u32>,
         highlight_lines: Option<RangeSet<u32>>,
+        /// Treat leading `+` and `-` as added and removed lines.
+        diff: bool,
+        /// Replace callout markers such as `// <1>` with badges, using this as the id prefix.
+        callout_id: Option<&'a str>,
     },
 }
 
+/// Returns true if any line ends with a callout marker, such as `// <1>`.
+pub fn has_callouts(code: &str) -> bool {
+    code.lines().any(|line| CALLOUT.is_match(line))
+}
+
+/// Strip callout markers so they don't interfere with highlighting,
+/// returning the callout numbers for each line.
+fn split_callouts(code: &str) -> (String, Vec<Vec<u32>>) {
+    let mut stripped = String::with_capacity(code.len());
+    let mut callouts = Vec::new();
+
+    for line in code.lines() {
+        match CALLOUT.captures(line) {
+            Some(caps) => {
+                stripped.push_str(line[..caps.get(0).unwrap().start()].trim_end());
+                callouts.push(
+                    CALLOUT_NUMBER
+                        .captures_iter(&caps[1])
+                        .filter_map(|num| num[1].parse().ok())
+                        .collect(),
+                );
+            }
+            None => {
+                stripped.push_str(line);
+                callouts.push(Vec::new());
+            }
+        }
+        stripped.push('\n');
+    }
+
+    (stripped, callouts)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DiffLine {
+    Added,
+    Removed,
+    Unchanged,
+}
+
+/// Strip the `+` and `-` markers so the code can be highlighted as the underlying language.
+fn split_diff(code: &str) -> (String, Vec<DiffLine>) {
+    let mut stripped = String::with_capacity(code.len());
+    let mut lines = Vec::new();
+
+    for line in code.lines() {
+        let (kind, line) = match line.chars().next() {
+            Some('+') => (DiffLine::Added, &line[1..]),
+            Some('-') => (DiffLine::Removed, &line[1..]),
+            Some(' ') => (DiffLine::Unchanged, &line[1..]),
+            _ => (DiffLine::Unchanged, line),
+        };
+        lines.push(kind);
+        stripped.push_str(line);
+        stripped.push('\n');
+    }
+
+    (stripped, lines)
+}
+
 impl Code<'_> {
     pub fn push(&self, s: &mut String) {
         match self {
@@ -44,13 +108,40 @@ impl Code<'_> {
                 path,
                 linenum_start,
                 highlight_lines,
+                diff,
+                callout_id,
             } => {
-                let transformed = TransformedCode::new(*lang, code);
+                let mut code = Cow::Borrowed(*code);
+                let diff_lines = if *diff {
+                    let (stripped, lines) = split_diff(&code);
+                    code = Cow::Owned(stripped);
+                    Some(lines)
+                } else {
+                    None
+                };
+                let callouts = match callout_id {
+                    Some(id) => {
+                        let (stripped, callouts) = split_callouts(&code);
+                        code = Cow::Owned(stripped);
+                        Some((*id, callouts))
+                    }
+                    None => None,
+                };
+
+                let transformed = TransformedCode::new(*lang, &code);
                 transformed.push_wrapper_tag_start(s);
                 push_descr(*lang, *path, s);
                 s.push_str("<pre>");
                 transformed.push_code_tag_start(s);
-                transformed.push_code_lines(*linenum_start, highlight_lines.as_ref(), s);
+                transformed.push_code_lines(
+                    *linenum_start,
+                    highlight_lines.as_ref(),
+                    diff_lines.as_deref(),
+                    callouts
+                        .as_ref()
+                        .map(|(id, callouts)| (*id, callouts.as_slice())),
+                    s,
+                );
                 s.push_str("</code>");
                 s.push_str("</pre>");
                 s.push_str("</div>");
@@ -141,6 +232,8 @@ impl<'a> TransformedCode<'a> {
         &self,
         linenum_start: Option<u32>,
         highlight_lines: Option<&RangeSet<u32>>,
+        diff_lines: Option<&[DiffLine]>,
+        callouts: Option<(&str, &[Vec<u32>])>,
         s: &mut String,
     ) {
         let code = self.code();
@@ -166,6 +259,11 @@ impl<'a> TransformedCode<'a> {
             {
                 classes.push("hl");
             };
+            match diff_lines.and_then(|lines| lines.get(num)) {
+                Some(DiffLine::Added) => classes.push("diff-add"),
+                Some(DiffLine::Removed) => classes.push("diff-remove"),
+                _ => {}
+            }
             let data_linenum = match linenum_start {
                 Some(start) => format!(
                     r#"data-linenum="{:>width$}""#,
@@ -180,6 +278,13 @@ impl<'a> TransformedCode<'a> {
                 data_linenum
             ));
             s.push_str(line);
+            if let Some((id, callouts)) = callouts {
+                for n in callouts.get(num).into_iter().flatten() {
+                    s.push_str(&format!(
+                        r#"<span class="callout" id="{id}-{n}">{n}</span>"#
+                    ));
+                }
+            }
             s.push('\n');
             s.push_str("</div>");
         }
@@ -252,6 +357,9 @@ lazy_static! {
     pub static ref BLOCK_CODE_SPEC: Regex = Regex::new(r"^[\w_-]+$").unwrap();
     pub static ref INLINE_CODE_SPEC: Regex = Regex::new(r"^([\w_-]+)(.*?)$").unwrap();
     pub static ref RANGE: Regex = Regex::new(r"^(\d+)..(\d+)$").unwrap();
+    static ref CALLOUT: Regex =
+        Regex::new(r"(?:^|\s)(?:(?://|#|--|;)\s*)?((?:<\d+>\s*)+)$").unwrap();
+    static ref CALLOUT_NUMBER: Regex = Regex::new(r"<(\d+)>").unwrap();
 }
 
 pub fn inline_code_spec(s: &str) -> Option<(String, String)> {
@@ -315,6 +423,31 @@ mod tests {
     //         );
     //     }
 
+    #[test]
+    fn test_split_diff() {
+        let (code, lines) = split_diff("fn x() {\n-    1\n+    2\n }");
+        assert_eq!(code, "fn x() {\n    1\n    2\n}\n");
+        assert_eq!(
+            lines,
+            [
+                DiffLine::Unchanged,
+                DiffLine::Removed,
+                DiffLine::Added,
+                DiffLine::Unchanged
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_callouts() {
+        assert!(has_callouts("let x = 2; // <1>"));
+        assert!(!has_callouts("let x: Foo<1>"));
+
+        let (code, callouts) = split_callouts("let x = 2; // <1>\nlet y = 3;\nset -x # <2> <3>");
+        assert_eq!(code, "let x = 2;\nlet y = 3;\nset -x\n");
+        assert_eq!(callouts, [vec![1], vec![], vec![2, 3]]);
+    }
+
     #[test]
     fn test_parse_line_highlight_spec() -> Result<()> {
         let set = parse_line_highlight_spec("0,2,4..5")?;
@@ -2,10 +2,10 @@ use crate::content::SeriesItem;
 use crate::content::StandaloneItem;
 use crate::content::Tag;
 use crate::content::{
-    BrokenLinkInfo, ConstantInfo, ContentInfo, DivClassInfo, HeadingContext, HeadingInfo, ImgInfo,
-    LinkDefInfo, PostInfo, SeriesInfo, StandaloneInfo, SymbolInfo, TagInfo,
+    BrokenLinkInfo, CitationInfo, ConstantInfo, ContentInfo, DivClassInfo, HeadingContext,
+    HeadingInfo, ImgInfo, LinkDefInfo, PostInfo, SeriesInfo, StandaloneInfo, SymbolInfo, TagInfo,
 };
-use crate::content::{PostItem, PostRef};
+use crate::content::{PostItem, TaggedItems};
 use crate::markup::markup_lookup::{Element, Img, ImgRef, Link, LinkRef};
 use crate::markup::MarkupLookup;
 use crate::markup::{DivTransform, SymbolTransform};
@@ -77,6 +77,11 @@ pub fn complete(
             return heading_completions(lookup, HeadingSource::SameFile);
         }
 
+        // Expand citation keys in `[@key]` or `[@key1; @key2]`
+        if CITATION_KEY.is_match(cursor_before_line) {
+            return citation_completions(site);
+        }
+
         // Expand url definition tags in `[text][tag]`
         if FULL_LINK_TAG.is_match(cursor_before_line) {
             return link_tag_completions(lookup);
@@ -97,7 +102,7 @@ pub fn complete(
 
         // Expand div classes
         if AFTER_DIV_MARKER.is_match(cursor_before_line) {
-            return div_class_completions();
+            return div_class_completions(site);
         }
 
         // Expand symbols
@@ -118,6 +123,7 @@ lazy_static! {
     static ref FULL_LINK_TAG: Regex = Regex::new(r"\[[^\]]+\]\[[^\]]*$").unwrap();
     static ref FRONTMATTER_TAG: Regex = Regex::new(r"^tags(:| =) ").unwrap();
     static ref FRONTMATTER_SERIES: Regex = Regex::new(r"^series(:| =) ").unwrap();
+    static ref CITATION_KEY: Regex = Regex::new(r"\[@[^\]]*$").unwrap();
     static ref OPEN_BRACKET: Regex = Regex::new(r"\[[^\]]*$").unwrap();
     static ref OPEN_BRACKET_FIRST: Regex = Regex::new(r"^\[[^\]]*$").unwrap();
     static ref AFTER_DIV_MARKER: Regex = Regex::new(r":{3,}\s+\w*$").unwrap();
@@ -249,6 +255,15 @@ fn append_broken_link_completions(lookup: &MarkupLookup, res: &mut Vec<Completio
     }
 }
 
+fn citation_completions(site: &Site) -> Vec<CompletionItem> {
+    site.content
+        .bibliography
+        .entries
+        .values()
+        .map(|entry| CompletionItemBuilder::Citation(entry.into()).into())
+        .collect()
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum CompletionType {
     Url,
@@ -262,8 +277,12 @@ fn tags_completions(site: &Site) -> Vec<CompletionItem> {
 }
 
 fn append_tags(t: CompletionType, site: &Site, res: &mut Vec<CompletionItem>) {
-    for (tag, posts) in site.lookup.tags.iter() {
-        res.push(CompletionItemBuilder::new_tag(t, tag, posts, site).into());
+    for (tag, items) in site.lookup.tags.iter() {
+        // Only tags with posts have an archive to link to.
+        if matches!(t, CompletionType::Url) && items.posts.is_empty() {
+            continue;
+        }
+        res.push(CompletionItemBuilder::new_tag(t, tag, items, site).into());
     }
 }
 
@@ -297,11 +316,11 @@ impl DivClass {
             "greek" => Some(Self::ListGreek),
             "dash" => Some(Self::ListDash),
             "plus" => Some(Self::ListPlus),
-            x => DivTransform::parse(x).map(Self::Transform),
+            x => DivTransform::parse(x, None).map(Self::Transform),
         }
     }
 
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
             Self::Transform(t) => t.as_str(),
             Self::Epigraph => "epigraph",
@@ -318,7 +337,7 @@ impl Display for DivClass {
     }
 }
 
-fn div_class_completions() -> Vec<CompletionItem> {
+fn div_class_completions(site: &Site) -> Vec<CompletionItem> {
     [
         DivClass::Transform(DivTransform::Flex),
         DivClass::Transform(DivTransform::Gallery),
@@ -327,12 +346,18 @@ fn div_class_completions() -> Vec<CompletionItem> {
         DivClass::Transform(DivTransform::Warn),
         DivClass::Transform(DivTransform::Important),
         DivClass::Transform(DivTransform::Update),
+        DivClass::Transform(DivTransform::Tabs),
         DivClass::Epigraph,
         DivClass::ListGreek,
         DivClass::ListDash,
         DivClass::ListPlus,
     ]
     .into_iter()
+    .chain(
+        DivTransform::templates(&site.templates)
+            .into_iter()
+            .map(DivClass::Transform),
+    )
     .map(|e| CompletionItemBuilder::DivClass(e).into())
     .collect()
 }
@@ -368,10 +393,16 @@ impl Display for Symbol {
 }
 
 fn symbol_completions() -> Vec<CompletionItem> {
-    [Symbol::TableOfContent]
-        .into_iter()
-        .map(|e| CompletionItemBuilder::Symbol(e).into())
-        .collect()
+    [
+        Symbol::TableOfContent,
+        Symbol::Transform(SymbolTransform::RecentPosts),
+        Symbol::Transform(SymbolTransform::FavoritePosts),
+        Symbol::Transform(SymbolTransform::TagCloud),
+        Symbol::Transform(SymbolTransform::SeriesList),
+    ]
+    .into_iter()
+    .map(|e| CompletionItemBuilder::Symbol(e).into())
+    .collect()
 }
 
 #[allow(dead_code)]
@@ -440,6 +471,7 @@ pub enum CompletionItemBuilder {
     LinkDefInfo(LinkDefInfo),
     DivClass(DivClass),
     Symbol(Symbol),
+    Citation(CitationInfo),
 }
 
 impl CompletionItemBuilder {
@@ -469,8 +501,8 @@ impl CompletionItemBuilder {
         Self::Series(t, SeriesInfo::from(item, site))
     }
 
-    pub fn new_tag(t: CompletionType, tag: &Tag, posts: &[PostRef], site: &Site) -> Self {
-        Self::Tag(t, TagInfo::from_tag(tag, posts, site))
+    pub fn new_tag(t: CompletionType, tag: &Tag, items: &TaggedItems, site: &Site) -> Self {
+        Self::Tag(t, TagInfo::from_tag(tag, items, site))
     }
 }
 
@@ -547,7 +579,7 @@ impl From<CompletionItemBuilder> for CompletionItem {
             CompletionItemBuilder::DivClass(class) => CompletionItem {
                 label: class.to_string(),
                 info: Some(ContentInfo::DivClass(DivClassInfo {
-                    name: class.as_str(),
+                    name: class.as_str().to_string(),
                 })),
                 kind: CompletionItemKind::Keyword,
                 ..Default::default()
@@ -558,6 +590,15 @@ impl From<CompletionItemBuilder> for CompletionItem {
                 kind: CompletionItemKind::Keyword,
                 ..Default::default()
             },
+            CompletionItemBuilder::Citation(info) => CompletionItem {
+                filter_text: Some(
+                    [info.key.as_str(), info.title.as_deref().unwrap_or_default()].join("|"),
+                ),
+                label: info.key.clone(),
+                insert_text: Some(info.key.clone()),
+                kind: CompletionItemKind::Reference,
+                info: Some(ContentInfo::Citation(info)),
+            },
         }
     }
 }
@@ -898,6 +939,42 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_citation_completion() -> Result<()> {
+        let test_site = TestSiteBuilder {
+            include_drafts: false,
+        }
+        .build()?;
+
+        let items = complete(
+            "As shown in [@kn",
+            0,
+            7,
+            test_site
+                .input_path("posts/2022-01-31-test_post.dj")
+                .as_str(),
+            &test_site.site,
+        );
+
+        assert_eq!(
+            items,
+            [CompletionItem {
+                label: "knuth1984".into(),
+                insert_text: Some("knuth1984".into()),
+                filter_text: Some("knuth1984|The TeXbook".into()),
+                kind: CompletionItemKind::Reference,
+                info: Some(ContentInfo::Citation(CitationInfo {
+                    key: "knuth1984".into(),
+                    title: Some("The TeXbook".into()),
+                    authors: "Knuth".into(),
+                    year: Some("1984".into()),
+                }))
+            }]
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn test_frontmatter_completion() -> Result<()> {
         let test_site = TestSiteBuilder {
@@ -915,7 +992,8 @@ mod tests {
             &test_site.site,
         );
 
-        assert_eq!(tags.len(), 3);
+        // Tags only used by notes are included.
+        assert_eq!(tags.len(), 4);
 
         let one = find_insert_text("One", &tags).expect("Should find tag `One`");
         assert_eq!(one.label, "One");
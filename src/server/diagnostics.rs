@@ -1,5 +1,6 @@
 use crate::item::Item;
-use crate::markup::markup_lookup::{Element, ImgRef, LinkRef, PosRange, TodoTag};
+use crate::markup::markup_lookup::{Element, ImgRef, LinkRef, LinkTextKind, PosRange, TodoTag};
+use crate::markup::MarkupLookup;
 use crate::paths::AbsPath;
 use crate::site_url::SiteUrl;
 use crate::Site;
@@ -101,6 +102,62 @@ pub fn generate_file_diagnostics(path: &AbsPath, site: &Site) -> Option<Vec<Diag
                     &mut res,
                 );
             }
+            Element::MathError { message } => {
+                push_diagnostic(
+                    &e.range,
+                    format!("Unsupported math: {message}"),
+                    DiagnosticSeverity::ERROR,
+                    &mut res,
+                );
+            }
+            Element::UnknownCitation { key } => {
+                push_diagnostic(
+                    &e.range,
+                    format!("Unknown citation key: `{key}`"),
+                    DiagnosticSeverity::ERROR,
+                    &mut res,
+                );
+            }
+            Element::CodeIncludeError { message } => {
+                push_diagnostic(
+                    &e.range,
+                    format!("Failed to include file: {message}"),
+                    DiagnosticSeverity::ERROR,
+                    &mut res,
+                );
+            }
+            Element::GraphvizError { message } => {
+                push_diagnostic(
+                    &e.range,
+                    format!("Failed to render graph: {message}"),
+                    DiagnosticSeverity::ERROR,
+                    &mut res,
+                );
+            }
+            Element::ChartError { message } => {
+                push_diagnostic(
+                    &e.range,
+                    format!("Failed to render chart: {message}"),
+                    DiagnosticSeverity::ERROR,
+                    &mut res,
+                );
+            }
+            Element::UndefinedTerm { term } => {
+                push_diagnostic(
+                    &e.range,
+                    format!("Term not in glossary: `{term}`"),
+                    DiagnosticSeverity::WARN,
+                    &mut res,
+                );
+            }
+            Element::UndefinedFootnote { label } => {
+                push_diagnostic(
+                    &e.range,
+                    format!("Undefined footnote: `{label}`"),
+                    DiagnosticSeverity::ERROR,
+                    &mut res,
+                );
+            }
             _ => {}
         }
     }
@@ -147,9 +204,98 @@ pub fn generate_file_diagnostics(path: &AbsPath, site: &Site) -> Option<Vec<Diag
         }
     }
 
+    for misspelling in lookup.misspellings.iter() {
+        push_diagnostic(
+            &misspelling.range,
+            format!("Unknown word: `{}`", misspelling.word),
+            DiagnosticSeverity::HINT,
+            &mut res,
+        );
+    }
+
+    for lint in lookup.prose_lints.iter() {
+        push_diagnostic(
+            &lint.range,
+            lint.message.clone(),
+            DiagnosticSeverity::INFO,
+            &mut res,
+        );
+    }
+
+    res.extend(accessibility_diagnostics(lookup));
+
     Some(res)
 }
 
+/// Images without alt text, links that don't describe where they lead,
+/// skipped heading levels and tables without a header row.
+pub fn accessibility_diagnostics(lookup: &MarkupLookup) -> Vec<Diagnostic> {
+    let mut res = Vec::new();
+
+    for text in lookup.link_texts.iter() {
+        match text.kind {
+            LinkTextKind::Img if text.text.trim().is_empty() => {
+                push_diagnostic(
+                    &text.range,
+                    "Image without alt text".to_string(),
+                    DiagnosticSeverity::WARN,
+                    &mut res,
+                );
+            }
+            LinkTextKind::Link if is_non_descriptive(&text.text) => {
+                push_diagnostic(
+                    &text.range,
+                    format!("Non-descriptive link text: `{}`", text.text.trim()),
+                    DiagnosticSeverity::WARN,
+                    &mut res,
+                );
+            }
+            _ => {}
+        }
+    }
+
+    let headings = lookup.headings_in_order();
+    for (prev, curr) in headings.iter().zip(headings.iter().skip(1)) {
+        if curr.heading.level > prev.heading.level + 1 {
+            push_diagnostic(
+                &curr.range,
+                format!(
+                    "Heading level jumps from h{} to h{}",
+                    prev.heading.level, curr.heading.level
+                ),
+                DiagnosticSeverity::WARN,
+                &mut res,
+            );
+        }
+    }
+
+    for table in lookup.tables.iter().filter(|table| !table.has_header) {
+        push_diagnostic(
+            &table.range,
+            "Table without a header row".to_string(),
+            DiagnosticSeverity::WARN,
+            &mut res,
+        );
+    }
+
+    res
+}
+
+fn is_non_descriptive(text: &str) -> bool {
+    let text: String = text
+        .chars()
+        .filter(|c| c.is_alphanumeric() || c.is_whitespace())
+        .collect::<String>()
+        .to_lowercase();
+    matches!(
+        text.split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .as_str(),
+        "here" | "click here" | "this" | "this link" | "link" | "read more" | "more" | "this page"
+    )
+}
+
 fn check_url(range: &PosRange, url: &str, site: &Site, res: &mut Vec<Diagnostic>) {
     if !url.starts_with('/') {
         return;
@@ -199,8 +345,10 @@ fn push_diagnostic(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::markup::{Markup, ParseContext};
     use crate::tests::*;
     use eyre::Result;
+    use std::fs;
 
     #[test]
     fn test_diagnostics() -> Result<()> {
@@ -224,14 +372,85 @@ mod tests {
                 "Duplicate heading id: `duplicate-heading`",
                 "Duplicate link definition: `tag1`",
                 "Duplicate link definition: `tag1`",
+                "Image without alt text",
                 "Link to non-existent link definition: `broken_tag`",
                 "Link to non-existent url: `/blog/xxx`",
                 "Link to non-existent url: `/xxx.png`",
                 "Link to non-existent url: `/xxx`",
+                "Non-descriptive link text: `link`",
                 "Unused link definition: `bad`",
             ]
         );
 
         Ok(())
     }
+
+    #[test]
+    fn test_spelling_diagnostics() -> Result<()> {
+        let mut test_site = TestSiteBuilder {
+            include_drafts: false,
+        }
+        .build()?;
+
+        // Spell checking is disabled without a dictionary.
+        for file in ["en_US.aff", "en_US.dic"] {
+            let content = fs::read_to_string(format!("test-site/fixtures/dictionaries/{file}"))?;
+            test_site.create_file(&format!("dictionaries/{file}"), &content)?;
+        }
+        test_site.create_file(
+            "posts/2023-01-31-spelling.dj",
+            r#"---toml
+title = "Spelling"
+tags = ["One"]
+---
+
+A small speling mistake
+"#,
+        )?;
+
+        let post_path = test_site.input_path("posts/2023-01-31-spelling.dj");
+        let diagnostics = generate_file_diagnostics(&post_path, &test_site.site)
+            .expect("Should find diagnostics for test file");
+        let hints: Vec<_> = diagnostics
+            .iter()
+            .filter(|d| d.severity == DiagnosticSeverity::HINT)
+            .map(|d| d.message.as_str())
+            .collect();
+        assert_eq!(hints, vec!["Unknown word: `speling`"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_accessibility_diagnostics() {
+        let s = r#"# Title
+
+### Skipped
+
+Read about it [here](/a) or [in the docs](/b).
+
+![](/img.png)
+
+| 1 | 2 |
+"#;
+        let lookup = Markup::Djot(s.to_string())
+            .parse(ParseContext::default())
+            .unwrap()
+            .lookup
+            .unwrap();
+
+        let messages: Vec<_> = accessibility_diagnostics(&lookup)
+            .into_iter()
+            .map(|d| (d.lnum, d.message))
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                (4, "Non-descriptive link text: `here`".to_string()),
+                (6, "Image without alt text".to_string()),
+                (2, "Heading level jumps from h1 to h3".to_string()),
+                (8, "Table without a header row".to_string()),
+            ]
+        );
+    }
 }
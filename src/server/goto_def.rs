@@ -57,6 +57,14 @@ pub fn goto_def(linenum: usize, column: usize, path: &str, site: &Site) -> Optio
         Element::LinkDef(LinkDef { url, .. }) => goto_url(url, lookup, site),
         Element::Heading(_) => None,
         Element::Todo(_) => None,
+        Element::MathError { .. } => None,
+        Element::UnknownCitation { .. } => None,
+        Element::CodeIncludeError { .. } => None,
+        Element::GlossaryTerm { .. } => None,
+        Element::UndefinedTerm { .. } => None,
+        Element::UndefinedFootnote { .. } => None,
+        Element::GraphvizError { .. } => None,
+        Element::ChartError { .. } => None,
     }
 }
 
@@ -2,6 +2,7 @@ use super::complete;
 use super::goto_def::{self, GotoDefRes};
 use super::messages::{NeovimEvent, NeovimResponse, WebEvent};
 use crate::content::TagInfo;
+use crate::markup::{self, DICTIONARIES_DIR};
 use crate::server::{diagnostics, info};
 use crate::site::Site;
 use std::sync::{Arc, Mutex};
@@ -41,7 +42,7 @@ pub fn handle_msg(msg: NeovimEvent, site: Arc<Mutex<Site>>) -> Option<Response>
                 .lookup
                 .tags
                 .iter()
-                .map(|(tag, post_refs)| TagInfo::from_tag(tag, post_refs, &site))
+                .map(|(tag, items)| TagInfo::from_tag(tag, items, &site))
                 .collect();
 
             Some(Response::Reply(NeovimResponse::ListTags {
@@ -106,5 +107,13 @@ pub fn handle_msg(msg: NeovimEvent, site: Arc<Mutex<Site>>) -> Option<Response>
                 },
             )
         }
+        NeovimEvent::AddWord { word } => {
+            // Writing the word list triggers a rebuild with the new word.
+            let dir = site.opts.input_dir.join(DICTIONARIES_DIR);
+            if let Err(err) = markup::add_word(&dir, &word) {
+                warn!("Failed to add `{word}` to the word list: {err}");
+            }
+            None
+        }
     }
 }
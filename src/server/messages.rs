@@ -51,6 +51,9 @@ pub enum NeovimEvent {
     RefreshDiagnostics {
         path: String,
     },
+    AddWord {
+        word: String,
+    },
 }
 
 #[derive(Debug, Serialize, Clone)]
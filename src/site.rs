@@ -1,6 +1,6 @@
 use camino::Utf8Path;
+use chrono::Datelike;
 use eyre::Result;
-use git2::Repository;
 use hotwatch::notify::event::AccessKind;
 use hotwatch::notify::event::AccessMode;
 use hotwatch::notify::event::ModifyKind;
@@ -24,17 +24,30 @@ use tokio::sync::broadcast::Sender;
 use tracing::{debug, error, info, warn};
 use url::Url;
 
+use crate::content::load_bookmarks;
+use crate::content::load_notes;
 use crate::content::load_series;
+use crate::content::notes_archives;
 use crate::content::set_post_prev_next;
+use crate::content::BookmarkItem;
+use crate::content::BookmarkRef;
+use crate::content::BookmarksArchiveItem;
+use crate::content::GlossaryItem;
+use crate::content::NoteItem;
+use crate::content::NoteRef;
+use crate::content::NotesArchiveItem;
 use crate::content::PostRef;
 use crate::content::SeriesArchiveItem;
 use crate::content::SeriesItem;
 use crate::content::SeriesRef;
-use crate::context::LoadContext;
-use crate::feed::SiteFeed;
-use crate::git::LatestCommits;
+use crate::context::{LoadContext, MarkupResources};
+use crate::feed::{NotesFeed, SiteFeed};
+use crate::git::{LatestCommits, SiteRepository};
 use crate::item::Item;
-use crate::markup::markup_lookup::MarkupLookup;
+use crate::markup::markup_lookup::{Element, MarkupLookup};
+use crate::markup::{
+    Bibliography, EmbedCache, Glossary, ProseLint, SpellChecker, DICTIONARIES_DIR, EMBED_CACHE,
+};
 use crate::paths;
 use crate::paths::AbsPath;
 use crate::paths::FilePath;
@@ -44,8 +57,9 @@ use crate::server::diagnostics;
 use crate::server::messages::{NeovimResponse, WebEvent};
 use crate::{
     content::{
-        load_posts, load_standalones, post_archives, tags_archives, ArchiveItem, HomepageItem,
-        JsItem, PostItem, ProjectsItem, SassItem, StandaloneItem, Tag, TagListItem,
+        has_site_symbols, load_posts, load_standalones, post_archives, tags_archives, year_reviews,
+        ArchiveItem, HomepageItem, JsItem, PostItem, ProjectsItem, SassItem, StandaloneItem, Tag,
+        TagListItem, TaggedItems, YearReviewItem,
     },
     context::RenderContext,
     site_url::SiteUrl,
@@ -75,8 +89,16 @@ pub struct SiteContent {
     pub posts: BTreeMap<PostRef, PostItem>,
     pub series: BTreeMap<SeriesRef, SeriesItem>,
     pub drafts: Option<BTreeSet<PostRef>>,
+    pub notes: BTreeMap<NoteRef, NoteItem>,
+    pub bookmarks: BTreeMap<BookmarkRef, BookmarkItem>,
 
     pub standalones: HashSet<StandaloneItem>,
+
+    pub bibliography: Bibliography,
+    pub glossary: Glossary,
+    pub spelling: SpellChecker,
+    pub prose_lint: ProseLint,
+    pub embed_cache: EmbedCache,
 }
 
 impl SiteContent {
@@ -90,9 +112,35 @@ impl SiteContent {
         .map(|x| context.opts.input_dir.join(x))
         .collect::<Vec<_>>();
 
-        let mut posts = load_posts(&post_dirs, context)?;
-        let series = load_series(context.opts.input_dir.join("series"), context, &mut posts)?;
-        let standalones = load_standalones(context.opts.input_dir.join("standalone"), context)?;
+        let bibliography = Bibliography::load(&context.opts.input_dir.join("bibliography.bib"))?;
+        let glossary = Glossary::load(&context.opts.input_dir.join("glossary.toml"))?;
+        let spelling = SpellChecker::load(&context.opts.input_dir.join(DICTIONARIES_DIR))?;
+        let prose_lint = ProseLint::load(&context.opts.input_dir.join("prose_lint.toml"))?;
+        let embed_cache = EmbedCache::load(&context.opts.input_dir.join(EMBED_CACHE))?;
+        let resources = MarkupResources {
+            bibliography: &bibliography,
+            glossary: &glossary,
+            spelling: &spelling,
+            prose_lint: &prose_lint,
+            embed_cache: &embed_cache,
+            templates: context.templates,
+            repository: context.repository,
+        };
+        let mut posts = load_posts(&post_dirs, context, resources)?;
+        let series = load_series(
+            context.opts.input_dir.join("series"),
+            context,
+            resources,
+            &mut posts,
+        )?;
+        let standalones = load_standalones(
+            context.opts.input_dir.join("standalone"),
+            context,
+            resources,
+        )?;
+        let notes = load_notes(context.opts.input_dir.join("notes"), context, resources)?;
+        let bookmarks =
+            load_bookmarks(context.opts.input_dir.join("bookmarks"), context, resources)?;
 
         let drafts = if context.opts.include_drafts {
             Some(
@@ -112,23 +160,39 @@ impl SiteContent {
         };
 
         info!(
-            "posts: {} series: {} standalones: {} drafts: {}",
+            "posts: {} series: {} standalones: {} notes: {} bookmarks: {} drafts: {}",
             posts.len(),
             series.len(),
             standalones.len(),
+            notes.len(),
+            bookmarks.len(),
             drafts.as_ref().map(|x: &BTreeSet<_>| x.len()).unwrap_or(0)
         );
 
         let projects = ProjectsItem::new(&context.opts.input_dir, context)?;
-        let homepage = HomepageItem::new(&posts, &series, &projects.projects, &projects.games)?;
+        let homepage = HomepageItem::new(
+            &context.opts.input_dir,
+            &posts,
+            &series,
+            &projects.projects,
+            &projects.games,
+            context.repository,
+        )?;
 
         Ok(Self {
             posts,
             series,
             standalones,
             drafts,
+            notes,
+            bookmarks,
             homepage,
             projects,
+            bibliography,
+            glossary,
+            spelling,
+            prose_lint,
+            embed_cache,
         })
     }
 
@@ -152,6 +216,47 @@ impl SiteContent {
         self.standalones.iter().find(|x| x.path.as_str() == path)
     }
 
+    pub fn find_note_by_path<'a>(&'a self, path: &str) -> Option<&'a NoteItem> {
+        self.notes.values().find(|x| x.path.as_str() == path)
+    }
+
+    pub fn find_bookmark_by_path<'a>(&'a self, path: &str) -> Option<&'a BookmarkItem> {
+        self.bookmarks.values().find(|x| x.path.as_str() == path)
+    }
+
+    /// Glossary terms that no post uses, and terms marked with `[term]{.term}`
+    /// that are missing from the glossary, together with the post that uses them.
+    pub fn glossary_usage(&self) -> (Vec<&str>, Vec<(&str, &AbsPath)>) {
+        let mut used = HashSet::new();
+        let mut undefined = Vec::new();
+        for post in self.posts.values() {
+            let lookup = match &post.markup_lookup {
+                Some(lookup) => lookup,
+                None => continue,
+            };
+            for (_, e) in lookup.char_pos_to_element.iter() {
+                match &e.element {
+                    Element::GlossaryTerm { term, .. } => {
+                        used.insert(term.as_str());
+                    }
+                    Element::UndefinedTerm { term } => {
+                        undefined.push((term.as_str(), &post.path));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let unused = self
+            .glossary
+            .terms
+            .keys()
+            .map(String::as_str)
+            .filter(|term| !used.contains(term))
+            .collect();
+        (unused, undefined)
+    }
+
     pub fn insert_post(&mut self, post: PostItem) -> Option<PostItem> {
         let post_ref = post.post_ref();
         if post.is_draft {
@@ -179,15 +284,26 @@ impl SiteContent {
 }
 
 pub struct SiteLookup {
-    pub tags: HashMap<Tag, Vec<PostRef>>,
+    pub tags: HashMap<Tag, TaggedItems>,
 }
 
 impl SiteLookup {
     fn from_content(content: &SiteContent) -> Self {
-        let mut tags: HashMap<Tag, Vec<PostRef>> = HashMap::new();
+        let mut tags: HashMap<Tag, TaggedItems> = HashMap::new();
         for (post_ref, post) in &content.posts {
             for tag in &post.tags {
-                tags.entry(tag.clone()).or_default().push(post_ref.clone());
+                tags.entry(tag.clone())
+                    .or_default()
+                    .posts
+                    .push(post_ref.clone());
+            }
+        }
+        for (note_ref, note) in &content.notes {
+            for tag in &note.tags {
+                tags.entry(tag.clone())
+                    .or_default()
+                    .notes
+                    .push(note_ref.clone());
             }
         }
         Self { tags }
@@ -200,12 +316,18 @@ struct SiteRenderOpts<'a> {
     all_standalones: bool,
     draft_archive: bool,
     post_archives: bool,
+    year_reviews: YearReviews,
     tags_archives: bool,
     series_archive: bool,
     tags_list: bool,
     homepage: bool,
     projects: bool,
     series: bool,
+    notes: bool,
+    bookmarks: bool,
+    glossary: bool,
+    /// Posts and standalones with site aware symbols, such as `:recent-posts:`.
+    site_symbols: bool,
     sass: bool,
     js: bool,
     copy_files: bool,
@@ -221,12 +343,17 @@ impl SiteRenderOpts<'_> {
             all_standalones: true,
             draft_archive: true,
             post_archives: true,
+            year_reviews: YearReviews::All,
             tags_archives: true,
             series_archive: true,
             tags_list: true,
             homepage: true,
             projects: true,
             series: true,
+            notes: true,
+            bookmarks: true,
+            glossary: true,
+            site_symbols: true,
             sass: true,
             js: true,
             copy_files: true,
@@ -244,10 +371,16 @@ impl SiteRenderOpts<'_> {
             tags_archives: has_tags,
             tags_list: has_tags,
             post_archives: true,
+            year_reviews: if post.is_draft {
+                YearReviews::None
+            } else {
+                YearReviews::Years(vec![post.created.year()])
+            },
             draft_archive: post.is_draft,
             series_archive: has_series,
             series: has_series,
             homepage: true,
+            site_symbols: !post.is_draft,
             extra_render: vec![post],
             feed: true,
             ..Default::default()
@@ -260,7 +393,16 @@ impl SiteRenderOpts<'_> {
         let series_changed = new.series != old.series;
         let recommended_changed = new.recommended != old.recommended;
         let favorite_changed = new.favorite != old.favorite;
+        let content_changed = new.markup.content() != old.markup.content();
         let is_draft = old.is_draft || new.is_draft;
+        // Word counts may change with any edit, but only the reviews of the years
+        // the post belongs to are affected.
+        let mut review_years: Vec<i32> = [old, new]
+            .iter()
+            .filter(|post| !post.is_draft)
+            .map(|post| post.created.year())
+            .collect();
+        review_years.dedup();
 
         SiteRenderOpts {
             // NOTE
@@ -271,6 +413,14 @@ impl SiteRenderOpts<'_> {
             tags_archives: title_changed || tags_changed || favorite_changed,
             tags_list: tags_changed || favorite_changed,
             post_archives: title_changed || favorite_changed,
+            // Series started or completed in other years may shift.
+            year_reviews: if series_changed {
+                YearReviews::All
+            } else if review_years.is_empty() {
+                YearReviews::None
+            } else {
+                YearReviews::Years(review_years)
+            },
             draft_archive: is_draft && (title_changed || favorite_changed),
             series_archive: title_changed || series_changed || favorite_changed,
             series: title_changed || series_changed,
@@ -279,6 +429,12 @@ impl SiteRenderOpts<'_> {
                 || tags_changed
                 || series_changed
                 || favorite_changed,
+            // The post stats graph plots word counts.
+            site_symbols: title_changed
+                || tags_changed
+                || series_changed
+                || favorite_changed
+                || (content_changed && !review_years.is_empty()),
             extra_render: vec![new],
             feed: true,
             ..Default::default()
@@ -286,21 +442,47 @@ impl SiteRenderOpts<'_> {
     }
 }
 
+/// The year review pages to render.
+#[derive(Default, Debug)]
+enum YearReviews {
+    #[default]
+    None,
+    Years(Vec<i32>),
+    All,
+}
+
 struct SiteRenderExtra<'a> {
     post_archives: Option<Vec<ArchiveItem>>,
+    year_reviews: Option<Vec<YearReviewItem>>,
     series_archive: Option<SeriesArchiveItem>,
     tags_archives: Option<Vec<ArchiveItem>>,
     tags_list: Option<TagListItem<'a>>,
     draft_archive: Option<ArchiveItem>,
+    notes_archives: Option<Vec<NotesArchiveItem>>,
+    bookmarks_archive: Option<BookmarksArchiveItem>,
+    glossary: Option<GlossaryItem<'a>>,
 }
 
 impl<'a> SiteRenderExtra<'a> {
-    fn new(opts: &SiteRenderOpts, site: &'a Site) -> SiteRenderExtra<'a> {
+    fn new(opts: &SiteRenderOpts, site: &'a Site) -> Result<SiteRenderExtra<'a>> {
         let post_archives = if opts.post_archives {
             Some(post_archives(&site.content.posts))
         } else {
             None
         };
+        let year_reviews = match &opts.year_reviews {
+            YearReviews::None => None,
+            YearReviews::Years(years) => Some(year_reviews(
+                &site.content.posts,
+                &site.content.series,
+                Some(years),
+            )?),
+            YearReviews::All => Some(year_reviews(
+                &site.content.posts,
+                &site.content.series,
+                None,
+            )?),
+        };
         let tags_archives = if opts.tags_archives {
             Some(tags_archives(&site.lookup.tags))
         } else {
@@ -321,14 +503,33 @@ impl<'a> SiteRenderExtra<'a> {
         } else {
             None
         };
+        let notes_archives = if opts.notes {
+            Some(notes_archives(&site.content.notes))
+        } else {
+            None
+        };
+        let bookmarks_archive = if opts.bookmarks {
+            Some(BookmarksArchiveItem::new(&site.content.bookmarks))
+        } else {
+            None
+        };
+        let glossary = if opts.glossary && !site.content.glossary.is_empty() {
+            Some(GlossaryItem::new(&site.content.glossary))
+        } else {
+            None
+        };
 
-        SiteRenderExtra {
+        Ok(SiteRenderExtra {
             post_archives,
+            year_reviews,
             series_archive,
             tags_archives,
             tags_list,
             draft_archive,
-        }
+            notes_archives,
+            bookmarks_archive,
+            glossary,
+        })
     }
 }
 
@@ -341,12 +542,19 @@ enum PathEvent {
     Standalone,
     Draft,
     Series,
+    Note,
+    Bookmark,
+    DivTemplate,
     Template,
     Font,
     Image,
-    #[allow(dead_code)]
     Homepage,
     Project,
+    Bibliography,
+    Glossary,
+    Dictionary,
+    ProseLint,
+    EmbedCache,
     Unknown,
     Ignore,
 }
@@ -367,12 +575,30 @@ impl PathEvent {
             Self::Draft
         } else if path.rel_path.starts_with("series/") {
             Self::Series
+        } else if path.rel_path.starts_with("notes/") {
+            Self::Note
+        } else if path.rel_path.starts_with("bookmarks/") {
+            Self::Bookmark
+        } else if path.rel_path.starts_with("templates/divs/") {
+            Self::DivTemplate
         } else if path.rel_path.starts_with("templates/") {
             Self::Template
         } else if path.rel_path.starts_with("fonts/") {
             Self::Font
         } else if path.rel_path.starts_with("images/") {
             Self::Image
+        } else if path.rel_path == "homepage.dj" {
+            Self::Homepage
+        } else if path.rel_path == "bibliography.bib" {
+            Self::Bibliography
+        } else if path.rel_path == "glossary.toml" {
+            Self::Glossary
+        } else if path.rel_path.starts_with("dictionaries/") {
+            Self::Dictionary
+        } else if path.rel_path == "prose_lint.toml" {
+            Self::ProseLint
+        } else if path.rel_path == EMBED_CACHE {
+            Self::EmbedCache
         } else if path.rel_path == "projects.dj" || path.rel_path.starts_with("projects/") {
             Self::Project
         } else if unknown_change_msg(&path.rel_path) {
@@ -397,7 +623,7 @@ pub struct Site {
     // Latest commits for all markup files.
     latest_commits: LatestCommits,
     // The git repository.
-    repository: Repository,
+    repository: SiteRepository,
 
     web_notifier: Option<Sender<WebEvent>>,
     nvim_notifier: Option<Sender<NeovimResponse>>,
@@ -405,15 +631,18 @@ pub struct Site {
 
 impl Site {
     pub fn load_content(opts: SiteOptions) -> Result<Self> {
-        let repository = Repository::open(".")?;
-        let latest_commits = LatestCommits::new(&repository, opts.git_path_offset)?;
+        let repository = SiteRepository::open(".", opts.git_path_offset)?;
+        let latest_commits = repository.latest_commits()?;
+        // Div templates are rendered when loading content, so load templates first.
+        let templates = load_templates("templates/**/*.html")?;
         let context = LoadContext {
             opts: &opts,
             latest_commits: &latest_commits,
+            templates: &templates,
+            repository: &repository,
         };
         let content = SiteContent::load(&context)?;
         let lookup = SiteLookup::from_content(&content);
-        let templates = load_templates("templates/*.html")?;
         let context =
             Context::from_serialize(SiteContext::new(opts.include_drafts, opts.include_js))
                 .unwrap();
@@ -447,6 +676,41 @@ impl Site {
         )
     }
 
+    /// Render only drafts, each at an unguessable url under `/preview/`
+    /// that's derived from the draft file and `secret`, so it stays stable across edits.
+    /// Returns the draft files together with their preview urls.
+    pub fn render_draft_previews(&mut self, secret: &str) -> Result<Vec<(AbsPath, SiteUrl)>> {
+        if self.opts.clear_output_dir && self.opts.output_dir.exists() {
+            debug!("Removing {}", self.opts.output_dir);
+            fs::remove_dir_all(&self.opts.output_dir)?;
+        }
+
+        let mut previews = Vec::new();
+        for post in self.content.posts.values_mut().filter(|post| post.is_draft) {
+            let file_path = FilePath::from_path(&self.opts.input_dir, &post.path)?;
+            post.url = draft_preview_url(&file_path.rel_path, secret)?;
+            previews.push((post.path.clone(), post.url.clone()));
+        }
+
+        // Previews shouldn't link to the draft archive, nor be indexed.
+        self.context.insert("include_drafts", &false);
+        self.context.insert("noindex", &true);
+
+        let drafts = self
+            .content
+            .posts
+            .values()
+            .filter(|post| post.is_draft)
+            .map(|post| post as &dyn Item)
+            .collect();
+        self.render(SiteRenderOpts {
+            extra_render: drafts,
+            ..Default::default()
+        })?;
+
+        Ok(previews)
+    }
+
     pub fn render_all(&self) -> Result<()> {
         if self.opts.clear_output_dir && self.opts.output_dir.exists() {
             debug!("Removing {}", self.opts.clear_output_dir);
@@ -458,7 +722,7 @@ impl Site {
     fn render(&self, opts: SiteRenderOpts<'_>) -> Result<()> {
         let ctx = self.render_ctx();
 
-        let extra = SiteRenderExtra::new(&opts, self);
+        let extra = SiteRenderExtra::new(&opts, self)?;
 
         let mut items = opts.extra_render;
         if opts.all_posts {
@@ -473,6 +737,20 @@ impl Site {
                 items.push(standalone);
             }
         }
+        if opts.site_symbols && !opts.all_posts {
+            for post in self.content.posts.values() {
+                if has_site_symbols(&post.content) {
+                    items.push(post);
+                }
+            }
+        }
+        if opts.site_symbols && !opts.all_standalones {
+            for standalone in &self.content.standalones {
+                if has_site_symbols(&standalone.content) {
+                    items.push(standalone);
+                }
+            }
+        }
         if opts.homepage {
             info!("Rebuilding homepage");
             items.push(&self.content.homepage);
@@ -487,12 +765,34 @@ impl Site {
                 items.push(serie);
             }
         }
+        if opts.notes {
+            info!("Rebuilding notes");
+            for note in self.content.notes.values() {
+                items.push(note);
+            }
+        }
+        if let Some(ref notes_archives) = extra.notes_archives {
+            info!("Rebuilding notes archives");
+            for i in notes_archives {
+                items.push(i);
+            }
+        }
+        if let Some(ref bookmarks_archive) = extra.bookmarks_archive {
+            info!("Rebuilding bookmarks archive");
+            items.push(bookmarks_archive);
+        }
         if let Some(ref post_archives) = extra.post_archives {
             info!("Rebuilding post archives");
             for i in post_archives {
                 items.push(i);
             }
         }
+        if let Some(ref year_reviews) = extra.year_reviews {
+            info!("Rebuilding year reviews");
+            for i in year_reviews {
+                items.push(i);
+            }
+        }
         if let Some(ref series_archive) = extra.series_archive {
             info!("Rebuilding series archives");
             items.push(series_archive);
@@ -511,6 +811,10 @@ impl Site {
             info!("Rebuilding draft archive");
             items.push(draft_archive);
         }
+        if let Some(ref glossary) = extra.glossary {
+            info!("Rebuilding glossary");
+            items.push(glossary);
+        }
 
         let sass = SassItem;
         if opts.sass {
@@ -518,8 +822,10 @@ impl Site {
             items.push(&sass);
         }
 
-        let js = JsItem;
-        if self.opts.include_js && opts.js {
+        let js = JsItem {
+            include_dev_js: self.opts.include_js,
+        };
+        if opts.js {
             info!("Rebuilding js");
             items.push(&js);
         }
@@ -529,6 +835,12 @@ impl Site {
             info!("Rebuilding feed");
             items.push(&feed);
         }
+
+        let notes_feed = NotesFeed;
+        if self.opts.generate_feed && opts.notes {
+            info!("Rebuilding notes feed");
+            items.push(&notes_feed);
+        }
         render_items(&items, &ctx)?;
         debug!("Rendered {} items", items.len());
 
@@ -564,6 +876,19 @@ impl Site {
             tera: &self.templates,
             output_dir: &self.opts.output_dir,
             content: &self.content,
+            repository: &self.repository,
+        }
+    }
+
+    fn markup_resources(&self) -> MarkupResources<'_> {
+        MarkupResources {
+            bibliography: &self.content.bibliography,
+            glossary: &self.content.glossary,
+            spelling: &self.content.spelling,
+            prose_lint: &self.content.prose_lint,
+            embed_cache: &self.content.embed_cache,
+            templates: &self.templates,
+            repository: &self.repository,
         }
     }
 
@@ -571,6 +896,8 @@ impl Site {
         LoadContext {
             opts: &self.opts,
             latest_commits: &self.latest_commits,
+            templates: &self.templates,
+            repository: &self.repository,
         }
     }
 
@@ -627,8 +954,15 @@ impl Site {
 
     fn write_event(&mut self, path: PathBuf) -> Result<()> {
         let path = self.file_path_from_std(path)?;
+        let event = PathEvent::from_path(&path);
 
-        match PathEvent::from_path(&path) {
+        // Files included in code blocks may be anywhere, even source files.
+        if event != PathEvent::Image && self.is_embedded(&path) {
+            self.rebuild_embedding_items(&path)?;
+            self.notify_refresh()?;
+        }
+
+        match event {
             PathEvent::SourceFile => error!("Source file changed `{path}`, please rebuild"),
             PathEvent::Css => self.rebuild_css()?,
             PathEvent::Js => self.rebuild_js()?,
@@ -636,11 +970,21 @@ impl Site {
             PathEvent::Draft => self.rebuild_draft(path.abs_path())?,
             PathEvent::Standalone => self.rebuild_standalone(path.abs_path())?,
             PathEvent::Series => self.rebuild_series(path.abs_path())?,
+            PathEvent::Note => self.rebuild_note(path)?,
+            PathEvent::Bookmark => self.rebuild_bookmark(path)?,
+            // Div templates are used when parsing markup, so everything needs to be parsed again.
+            PathEvent::DivTemplate => self.rebuild_all()?,
             PathEvent::Template => self.rebuild_template(path.abs_path())?,
             PathEvent::Font => self.rebuild_copy(path, true)?,
             PathEvent::Image => self.rebuild_img(path)?,
             PathEvent::Homepage => self.rebuild_homepage()?,
             PathEvent::Project => self.rebuild_projects(path.abs_path())?,
+            // Citations may be anywhere, so rebuild everything.
+            PathEvent::Bibliography => self.rebuild_all()?,
+            PathEvent::Glossary => self.rebuild_all()?,
+            PathEvent::Dictionary => self.rebuild_all()?,
+            PathEvent::ProseLint => self.rebuild_all()?,
+            PathEvent::EmbedCache => self.rebuild_all()?,
             PathEvent::Unknown => warn!("Unknown write: {path}"),
             PathEvent::Ignore => (),
         }
@@ -700,19 +1044,17 @@ impl Site {
     }
 
     fn rebuild_js(&self) -> Result<()> {
-        if self.opts.include_js {
-            info!("Rebuilding js");
-            self.render_item(&JsItem {})
-        } else {
-            debug!("Skip js");
-            Ok(())
-        }
+        info!("Rebuilding js");
+        self.render_item(&JsItem {
+            include_dev_js: self.opts.include_js,
+        })
     }
 
     fn rebuild_post(&mut self, path: AbsPath) -> Result<()> {
         info!("Post changed: {path}");
         let file_path = self.file_path(path)?;
-        let mut updated = PostItem::from_file(&file_path, &self.load_ctx())?;
+        let mut updated =
+            PostItem::from_file(&file_path, &self.load_ctx(), self.markup_resources())?;
 
         if let Some(series) = updated
             .series_id
@@ -762,7 +1104,8 @@ impl Site {
     fn rebuild_standalone(&mut self, path: AbsPath) -> Result<()> {
         info!("Standalone changed: {path}");
         let file_path = self.file_path(path)?;
-        let updated = StandaloneItem::from_file(&file_path, &self.load_ctx())?;
+        let updated =
+            StandaloneItem::from_file(&file_path, &self.load_ctx(), self.markup_resources())?;
         self.render_item(&updated)?;
 
         self.content.standalones.insert(updated);
@@ -781,7 +1124,7 @@ impl Site {
     fn rebuild_series(&mut self, path: AbsPath) -> Result<()> {
         info!("Series changed: {path}");
         let file_path = self.file_path(path)?;
-        let mut updated = SeriesItem::from_file(&file_path)?;
+        let mut updated = SeriesItem::from_file(&file_path, self.markup_resources())?;
 
         // We need to loop as we can't build a SeriesRef without having the last updated field.
         let old_ref = self
@@ -809,11 +1152,47 @@ impl Site {
             homepage: true,
             all_posts: rebuild_posts,
             series_archive: true,
+            site_symbols: true,
             extra_render: vec![updated],
             ..Default::default()
         })
     }
 
+    fn rebuild_note(&mut self, path: FilePath) -> Result<()> {
+        info!("Note changed: {path}");
+        let updated = NoteItem::from_file(&path, self.markup_resources())?;
+
+        // The timestamp may have changed, so we can't rely on the ref to replace it.
+        self.content
+            .notes
+            .retain(|_, note| note.path != updated.path);
+        self.content.notes.insert(updated.note_ref(), updated);
+        self.lookup = SiteLookup::from_content(&self.content);
+
+        self.render(SiteRenderOpts {
+            notes: true,
+            ..Default::default()
+        })
+    }
+
+    fn rebuild_bookmark(&mut self, path: FilePath) -> Result<()> {
+        info!("Bookmark changed: {path}");
+        let updated = BookmarkItem::from_file(&path, self.markup_resources())?;
+
+        self.content
+            .bookmarks
+            .retain(|_, bookmark| bookmark.path != updated.path);
+        self.content
+            .bookmarks
+            .insert(updated.bookmark_ref(), updated);
+
+        self.render(SiteRenderOpts {
+            bookmarks: true,
+            feed: true,
+            ..Default::default()
+        })
+    }
+
     fn rebuild_homepage(&mut self) -> Result<()> {
         self.update_homepage_item()?;
 
@@ -838,10 +1217,12 @@ impl Site {
 
     fn update_homepage_item(&mut self) -> Result<()> {
         self.content.homepage = HomepageItem::new(
+            &self.opts.input_dir,
             &self.content.posts,
             &self.content.series,
             &self.content.projects.projects,
             &self.content.projects.games,
+            &self.repository,
         )?;
 
         Ok(())
@@ -864,10 +1245,14 @@ impl Site {
                 all_standalones: true,
                 draft_archive: true,
                 post_archives: true,
+                year_reviews: YearReviews::All,
                 tags_archives: true,
                 tags_list: true,
                 homepage: true,
                 projects: true,
+                notes: true,
+                bookmarks: true,
+                glossary: true,
                 ..Default::default()
             },
             "archive.html" => SiteRenderOpts {
@@ -904,11 +1289,28 @@ impl Site {
                 series_archive: true,
                 ..Default::default()
             },
+            "note.html" | "notes.html" | "note_item.html" => SiteRenderOpts {
+                notes: true,
+                ..Default::default()
+            },
+            "links.html" => SiteRenderOpts {
+                bookmarks: true,
+                ..Default::default()
+            },
+            "glossary.html" => SiteRenderOpts {
+                glossary: true,
+                ..Default::default()
+            },
+            "year_review.html" => SiteRenderOpts {
+                year_reviews: YearReviews::All,
+                ..Default::default()
+            },
             "post_info.html" => SiteRenderOpts {
                 all_posts: true,
                 all_standalones: true,
                 draft_archive: true,
                 post_archives: true,
+                year_reviews: YearReviews::All,
                 tags_archives: true,
                 homepage: true,
                 ..Default::default()
@@ -942,6 +1344,25 @@ impl Site {
     fn rebuild_img(&mut self, path: FilePath) -> Result<()> {
         // NOTE when changing multiple images (such as generating keyboard layout images)
         // this may rebuild the post multiple times.
+        self.rebuild_embedding_items(&path)?;
+        self.rebuild_copy(path, false)?;
+        self.notify_refresh()?;
+        Ok(())
+    }
+
+    fn is_embedded(&self, path: &FilePath) -> bool {
+        self.content
+            .posts
+            .values()
+            .any(|post| post.embedded_files.contains(&path.rel_path))
+            || self
+                .content
+                .notes
+                .values()
+                .any(|note| note.embedded_files.contains(&path.rel_path))
+    }
+
+    fn rebuild_embedding_items(&mut self, path: &FilePath) -> Result<()> {
         let changed_posts = self
             .content
             .posts
@@ -949,21 +1370,29 @@ impl Site {
             .filter(|post| post.embedded_files.contains(&path.rel_path))
             .map(|post| post.path.clone())
             .collect::<Vec<_>>();
+        let changed_notes = self
+            .content
+            .notes
+            .values()
+            .filter(|note| note.embedded_files.contains(&path.rel_path))
+            .map(|note| note.path.clone())
+            .collect::<Vec<_>>();
 
         // TODO should check series and standalones as well
 
         for change in changed_posts {
             self.rebuild_post(change.clone())?;
         }
-
-        self.rebuild_copy(path, false)?;
-        self.notify_refresh()?;
+        for change in changed_notes {
+            let path = self.file_path(&change)?;
+            self.rebuild_note(path)?;
+        }
         Ok(())
     }
 
     fn rebuild_all(&mut self) -> Result<()> {
         self.templates.full_reload()?;
-        self.latest_commits = LatestCommits::new(&self.repository, self.opts.git_path_offset)?;
+        self.latest_commits = self.repository.latest_commits()?;
         self.content = SiteContent::load(&self.load_ctx())?;
         self.lookup = SiteLookup::from_content(&self.content);
         self.render_all()
@@ -991,7 +1420,16 @@ impl Site {
         if let Some(x) = self.content.find_standalone_by_path(path) {
             return Some(Cow::Borrowed(x.url()));
         }
+        if let Some(x) = self.content.find_note_by_path(path) {
+            return Some(Cow::Borrowed(&x.url));
+        }
+        if let Some(x) = self.content.find_bookmark_by_path(path) {
+            return Some(Cow::Borrowed(&x.url));
+        }
         if let Ok(file_path) = self.file_path(path) {
+            if file_path.rel_path == "homepage.dj" {
+                return Some(Cow::Borrowed(&self.content.homepage.url));
+            }
             if file_path.rel_path.0.starts_with("projects") {
                 return Some(Cow::Owned(ProjectsItem::url()));
             }
@@ -1010,9 +1448,18 @@ impl Site {
         if let Some(x) = self.content.find_standalone_by_path(path.as_str()) {
             return x.markup_lookup.as_ref();
         }
+        if let Some(x) = self.content.find_note_by_path(path.as_str()) {
+            return x.markup_lookup.as_ref();
+        }
+        if let Some(x) = self.content.find_bookmark_by_path(path.as_str()) {
+            return x.markup_lookup.as_ref();
+        }
         if let x @ Some(_) = self.content.projects.find_lookup_by_path(path) {
             return x;
         }
+        if let x @ Some(_) = self.content.homepage.find_lookup_by_path(path) {
+            return x;
+        }
         None
     }
 
@@ -1057,6 +1504,12 @@ fn unknown_change_msg(path: &RelPath) -> bool {
         false
     } else if path.starts_with("test-site/") {
         false
+    } else if path.starts_with("keyboard_layouts/")
+        || path.starts_with("graphviz/")
+        || path.starts_with("plots/")
+    {
+        // Posts embedding these are rebuilt when they change.
+        false
     } else if path.starts_with("itemref-derive/") {
         false
     } else if path == "Cargo.toml" {
@@ -1072,12 +1525,18 @@ fn unknown_change_msg(path: &RelPath) -> bool {
     }
 }
 
+fn draft_preview_url(rel_path: &RelPath, secret: &str) -> Result<SiteUrl> {
+    let digest = md5::compute(format!("{secret}:{rel_path}"));
+    SiteUrl::parse(&format!("/preview/{digest:x}/"))
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct SiteContext {
     mail: &'static str,
     meta_keywords: Vec<String>,
     include_drafts: bool,
     include_js: bool,
+    noindex: bool,
 }
 
 impl SiteContext {
@@ -1087,6 +1546,7 @@ impl SiteContext {
             meta_keywords: vec![],
             include_drafts,
             include_js,
+            noindex: false,
         }
     }
 }
@@ -1531,6 +1991,427 @@ My new series post
         Ok(())
     }
 
+    #[test]
+    fn test_homepage_file() -> Result<()> {
+        let mut test_site = TestSiteBuilder {
+            include_drafts: false,
+        }
+        .build()?;
+
+        let homepage = test_site.output_content("index.html")?;
+        assert!(homepage.contains(r#"<hr data-label="Recent" />"#));
+        assert!(homepage.contains("Feb post 1"));
+
+        test_site.create_file(
+            "homepage.dj",
+            r#"---toml
+[[sections]]
+type = "posts"
+title = "Tagged"
+
+[[sections.groups]]
+label = "Tag1 posts"
+tags = ["Tag1"]
+---
+
+My custom intro
+"#,
+        )?;
+
+        let homepage = test_site.output_content("index.html")?;
+        assert!(homepage.contains("My custom intro"));
+        assert!(homepage.contains(r#"<hr data-label="Tag1 posts" />"#));
+        assert!(homepage.contains("/blog/2022/01/31/test_post"));
+        assert!(!homepage.contains("Feb post 1"));
+        assert!(!homepage.contains(r#"<article class="series">"#));
+
+        test_site.change_file("homepage.dj", "Tag1", "One")?;
+        let homepage = test_site.output_content("index.html")?;
+        assert!(homepage.contains("Feb post 1"));
+        assert!(!homepage.contains("/blog/2022/01/31/test_post"));
+
+        test_site.change_file("homepage.dj", "One posts", r#"Say \"hi\" posts"#)?;
+        let homepage = test_site.output_content("index.html")?;
+        assert!(homepage.contains(r#"<hr data-label="Say &quot;hi&quot; posts" />"#));
+
+        test_site.remove_file("homepage.dj")?;
+        let homepage = test_site.output_content("index.html")?;
+        assert!(!homepage.contains("My custom intro"));
+        assert!(homepage.contains(r#"<hr data-label="Recent" />"#));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_notes() -> Result<()> {
+        let mut test_site = TestSiteBuilder {
+            include_drafts: false,
+        }
+        .build()?;
+
+        let noted = &test_site.site.lookup.tags[&Tag::new("Noted")];
+        assert_eq!(noted.notes.len(), 1);
+        assert!(noted.posts.is_empty());
+        assert!(!test_site.output_path("blog/tags/noted/index.html").exists());
+
+        assert!(test_site
+            .output_content("notes/index.html")?
+            .contains("A short note about"));
+        assert!(test_site
+            .output_content("notes/first_note/index.html")?
+            .contains("A short note about"));
+        assert!(test_site
+            .output_content("notes/feed.xml")?
+            .contains("http://jonashietala.se/notes/first_note/index.html"));
+        assert!(!test_site
+            .output_content("blog/index.html")?
+            .contains("/notes/first_note"));
+
+        test_site.change_file("notes/first_note.dj", "A short note", "An edited note")?;
+        assert!(test_site
+            .output_content("notes/index.html")?
+            .contains("An edited note"));
+
+        test_site.create_file(
+            "notes/second_note.dj",
+            r#"---toml
+created = "2022-02-04 08:00"
+---
+
+Another note
+"#,
+        )?;
+        let notes = test_site.output_content("notes/index.html")?;
+        let second = notes.find("Another note").unwrap();
+        let first = notes.find("An edited note").unwrap();
+        assert!(second < first);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_notes_footnote_ids() -> Result<()> {
+        let mut test_site = TestSiteBuilder {
+            include_drafts: false,
+        }
+        .build()?;
+
+        for (name, created) in [
+            ("side_one", "2022-02-04 08:00"),
+            ("side_two", "2022-02-05 08:00"),
+        ] {
+            test_site.create_file(
+                &format!("notes/{name}.dj"),
+                &format!(
+                    r#"---toml
+created = "{created}"
+---
+
+Note with a sidenote[^side].
+
+[^side]: The sidenote
+"#
+                ),
+            )?;
+        }
+
+        let notes = test_site.output_content("notes/index.html")?;
+        for name in ["side_one", "side_two"] {
+            assert!(notes.contains(&format!(r#"<label for="{name}-sidenote-1""#)));
+            assert!(notes.contains(&format!(r#"id="{name}-sidenote-1""#)));
+        }
+        assert!(!notes.contains(r#""sidenote-1""#));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_bookmarks() -> Result<()> {
+        let mut test_site = TestSiteBuilder {
+            include_drafts: false,
+        }
+        .build()?;
+
+        let links = test_site.output_content("links/index.html")?;
+        assert!(links.contains("Djot syntax reference"));
+        assert!(links.contains(r#"id="djot_syntax""#));
+        assert!(links.contains("A good reference for the markup language."));
+
+        test_site.change_file(
+            "bookmarks/djot_syntax.dj",
+            "A good reference",
+            "An excellent reference",
+        )?;
+        assert!(test_site
+            .output_content("links/index.html")?
+            .contains("An excellent reference"));
+        assert!(test_site
+            .output_content("feed.xml")?
+            .contains("An excellent reference"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_year_review() -> Result<()> {
+        let mut test_site = TestSiteBuilder {
+            include_drafts: true,
+        }
+        .build()?;
+
+        let review = test_site.output_content("blog/2022/review/index.html")?;
+        assert!(review.contains("2022</a> in review"));
+        assert!(review.contains("Feb post 1"));
+        assert!(!review.contains("A draft"));
+        assert!(review.contains("<svg"));
+
+        test_site.change_file(
+            "posts/2022-02-01-feb_post.dj",
+            "Feb post 1",
+            "Reviewed post",
+        )?;
+        assert!(test_site
+            .output_content("blog/2022/review/index.html")?
+            .contains("Reviewed post"));
+
+        // Only the review of the edited post's year is rendered again.
+        test_site.create_file(
+            "posts/2023-01-01-new_year.dj",
+            r#"---toml
+title = "New year"
+tags = ["One"]
+---
+
+Content
+"#,
+        )?;
+        let review_2023 = test_site.output_path("blog/2023/review/index.html");
+        assert!(fs::read_to_string(&review_2023)?.contains("New year"));
+        fs::write(&review_2023, "Stale")?;
+        test_site.change_file("posts/2022-02-01-feb_post.dj", "Reviewed post", "Edited")?;
+        assert!(test_site
+            .output_content("blog/2022/review/index.html")?
+            .contains("Edited"));
+        assert_eq!(fs::read_to_string(&review_2023)?, "Stale");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_citations() -> Result<()> {
+        let mut test_site = TestSiteBuilder {
+            include_drafts: false,
+        }
+        .build()?;
+
+        test_site.create_file(
+            "posts/2022-03-01-cited.dj",
+            r#"---toml
+title = "Cited"
+tags = ["One"]
+---
+
+As described in [@knuth1984] and [@missing].
+"#,
+        )?;
+
+        let post = test_site.output_content("blog/2022/03/01/cited/index.html")?;
+        assert!(post.contains(r##"<a href="#ref-knuth1984" role="doc-biblioref">1</a>"##));
+        assert!(post.contains(r#"<li id="ref-knuth1984">Donald E. Knuth (1984)."#));
+
+        let path = test_site.input_path("posts/2022-03-01-cited.dj");
+        let diagnostics = diagnostics::generate_file_diagnostics(&path, &test_site.site).unwrap();
+        let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(messages, ["Unknown citation key: `missing`"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_glossary() -> Result<()> {
+        let mut test_site = TestSiteBuilder {
+            include_drafts: false,
+        }
+        .build()?;
+
+        test_site.create_file(
+            "glossary.toml",
+            r#"
+LSP = "Language Server Protocol"
+Unused = "Never mentioned"
+"#,
+        )?;
+        test_site.create_file(
+            "posts/2023-01-31-lsp.dj",
+            r#"---toml
+title = "Servers"
+tags = ["One"]
+---
+
+An LSP and another LSP, but [MSP]{.term} isn't defined.
+"#,
+        )?;
+
+        let glossary = test_site.output_content("glossary/index.html")?;
+        assert!(glossary.contains(r#"<dt id="lsp">LSP</dt>"#));
+        assert!(glossary.contains("<dd>Language Server Protocol</dd>"));
+
+        let post = test_site.output_content("blog/2023/01/31/lsp/index.html")?;
+        assert!(post.contains(
+            r#"<a class="glossary-term" href="/glossary#lsp"><abbr title="Language Server Protocol">LSP</abbr></a> and another LSP"#
+        ));
+
+        let (unused, undefined) = test_site.site.content.glossary_usage();
+        assert_eq!(unused, vec!["Unused"]);
+        assert_eq!(undefined.len(), 1);
+        assert_eq!(undefined[0].0, "MSP");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_embed_cache() -> Result<()> {
+        let mut test_site = TestSiteBuilder {
+            include_drafts: false,
+        }
+        .build()?;
+
+        test_site.create_file(
+            "embeds.toml",
+            r#"
+["https://vimeo.com/76979871"]
+title = "The New Vimeo Player"
+"#,
+        )?;
+        test_site.create_file(
+            "notes/embed.dj",
+            r#"---toml
+created = "2024-03-01 10:00"
+---
+
+https://vimeo.com/76979871
+
+https://vimeo.com/1234
+"#,
+        )?;
+
+        let note = test_site.output_content("notes/embed/index.html")?;
+        assert!(note.contains(r#"<iframe src="https://player.vimeo.com/video/76979871""#));
+        // Links missing from the cache are left alone.
+        assert!(note.contains("<p>https://vimeo.com/1234</p>"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_toc_sidebar() -> Result<()> {
+        let mut test_site = TestSiteBuilder {
+            include_drafts: false,
+        }
+        .build()?;
+
+        test_site.create_file(
+            "posts/2023-01-31-toc.dj",
+            r#"---toml
+title = "Contents"
+tags = ["One"]
+---
+
+{sidebar=true numbered=true}
+:table-of-content:
+
+# First
+
+## Nested
+"#,
+        )?;
+
+        let post = test_site.output_content("blog/2023/01/31/toc/index.html")?;
+        assert!(post.contains(r#"<aside class="toc toc-sidebar">"#));
+        assert!(post.contains(r#"<span class="toc-number">1.1</span>"#));
+        assert!(!post.contains("table-of-content"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_site_symbols() -> Result<()> {
+        let mut test_site = TestSiteBuilder {
+            include_drafts: false,
+        }
+        .build()?;
+
+        test_site.create_file(
+            "standalone/overview.dj",
+            r#"---toml
+title = "Overview"
+---
+
+{count="2"}
+:recent-posts:
+
+:tag-cloud:
+
+:series-list:
+
+{caption="Words"}
+:post-stats-graph:
+"#,
+        )?;
+
+        let overview = test_site.output_content("overview/index.html")?;
+        assert!(!overview.contains("SITE_SYMBOL"));
+        assert!(overview.contains(r#"<ul class="recent-posts">"#));
+        assert!(overview.contains(r#"<a href="/blog/tags/one">One</a>"#));
+        assert!(overview.contains(r#"<ul class="series-list">"#));
+        assert!(overview.contains("<svg"));
+        assert!(overview.contains("<figcaption>Words</figcaption>"));
+
+        test_site.create_file(
+            "posts/2023-01-31-fresh_post.dj",
+            r#"---toml
+title = "Fresh post"
+tags = ["One"]
+---
+
+Content
+"#,
+        )?;
+        let overview = test_site.output_content("overview/index.html")?;
+        assert!(overview.contains("Fresh post"));
+        assert!(!overview.contains("Post &amp; Title"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_draft_previews() -> Result<()> {
+        let mut test_site = TestSiteBuilder {
+            include_drafts: true,
+        }
+        .build()?;
+
+        let previews = test_site.site.render_draft_previews("secret")?;
+        assert_eq!(previews.len(), 1);
+        let (path, url) = &previews[0];
+        assert!(path.as_str().ends_with("drafts/a_draft.markdown"));
+        assert!(url.href().starts_with("/preview/"));
+        assert_eq!(
+            url,
+            &draft_preview_url(&RelPath("drafts/a_draft.markdown".into()), "secret")?
+        );
+        assert_ne!(
+            url,
+            &draft_preview_url(&RelPath("drafts/a_draft.markdown".into()), "other")?
+        );
+
+        let draft = test_site
+            .output_content(&format!("{}index.html", url.path().trim_start_matches('/')))?;
+        assert!(draft.contains(r#"<meta name="robots" content="noindex" />"#));
+
+        Ok(())
+    }
+
     fn check_files<'a>(
         files: &'a ParsedFiles,
         output_dir: &Utf8Path,
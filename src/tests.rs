@@ -3,6 +3,7 @@
 
 use crate::content::PostItem;
 use crate::content::SeriesItem;
+use crate::git::SiteRepository;
 use crate::paths::AbsPath;
 use crate::site::{Site, SiteOptions};
 use crate::site_url::{HrefUrl, ImgUrl};
@@ -12,6 +13,7 @@ use crate::util::{ParsedFile, ParsedFiles};
 use camino::Utf8Path;
 use camino::Utf8PathBuf;
 use eyre::Result;
+use git2::{Repository, Signature};
 use hotwatch::notify::event::AccessKind;
 use hotwatch::notify::event::AccessMode;
 use hotwatch::notify::event::ModifyKind;
@@ -21,7 +23,7 @@ use hotwatch::{Event, EventKind};
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tempfile::TempDir;
 use tera::Tera;
 use thiserror::Error;
@@ -177,11 +179,37 @@ impl TestSiteBuilder {
 
 pub fn templates() -> &'static Tera {
     lazy_static! {
-        static ref TEMPLATES: Tera = load_templates("templates/*.html").unwrap();
+        static ref TEMPLATES: Tera = load_templates("templates/**/*.html").unwrap();
     }
     &TEMPLATES
 }
 
+/// A git repository in a temporary directory with `files` added in a single commit.
+pub fn git_fixture(files: &[(&str, &str)]) -> Result<(TempDir, Repository)> {
+    let dir = TempDir::new()?;
+    let repo = Repository::init(dir.path())?;
+    {
+        let mut index = repo.index()?;
+        for (path, content) in files {
+            util::write_to_file(&dir.path().join(path), content)?;
+            index.add_path(Path::new(path))?;
+        }
+        index.write()?;
+        let tree = repo.find_tree(index.write_tree()?)?;
+        let signature = Signature::now("Test", "test@example.com")?;
+        repo.commit(Some("HEAD"), &signature, &signature, "Fixture", &tree, &[])?;
+    }
+    Ok((dir, repo))
+}
+
+pub fn repository() -> &'static SiteRepository {
+    lazy_static! {
+        static ref REPOSITORY: SiteRepository =
+            SiteRepository::open(".", Some(Utf8Path::new("test-site/"))).unwrap();
+    }
+    &REPOSITORY
+}
+
 #[derive(Error, Debug)]
 pub enum GeneratedFileError<'a> {
     #[error("missing doctype")]
@@ -22,12 +22,16 @@ use crate::paths::FilePath;
 pub struct SyncOpts<'a> {
     pub dir: &'a AbsPath,
     pub bucket: Bucket,
+    /// Only consider remote objects with this prefix.
+    pub remote_prefix: &'a str,
+    /// Never delete remote objects with this prefix.
+    pub keep_remote: Option<&'a str>,
     pub delete: bool,
     pub print_urls: bool,
 }
 
 pub async fn sync(opts: SyncOpts<'_>) -> Result<()> {
-    let plan = calculate_sync(opts.dir, &opts.bucket).await?;
+    let plan = calculate_sync(opts.dir, &opts.bucket, opts.remote_prefix, opts.keep_remote).await?;
 
     let mut futures = Vec::new();
     for x in plan.upload.into_values() {
@@ -52,16 +56,22 @@ pub async fn sync(opts: SyncOpts<'_>) -> Result<()> {
     Ok(())
 }
 
-async fn calculate_sync(dir: &AbsPath, bucket: &Bucket) -> Result<SyncPlan> {
+async fn calculate_sync(
+    dir: &AbsPath,
+    bucket: &Bucket,
+    remote_prefix: &str,
+    keep_remote: Option<&str>,
+) -> Result<SyncPlan> {
     let local = paths::list_files(dir.clone());
 
     let remote: HashMap<String, Object> = bucket
-        .list("".to_string(), None)
+        .list(remote_prefix.to_string(), None)
         .await?
         .into_par_iter()
         .flat_map(|res| {
             res.contents
                 .into_par_iter()
+                .filter(|obj| !keep_remote.is_some_and(|keep| obj.key.starts_with(keep)))
                 .map(|obj| (obj.key.clone(), obj))
         })
         .collect();
@@ -1,5 +1,5 @@
 use camino::{Utf8Path, Utf8PathBuf};
-use chrono::{DateTime, NaiveDateTime};
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
 use eyre::eyre;
 use eyre::Result;
 use glob::glob;
@@ -28,6 +28,16 @@ where
     .naive_local())
 }
 
+/// Parse a timestamp written as `2024-03-05 15:30`, with optional seconds or without time.
+pub fn parse_date_time(s: &str) -> Result<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M"))
+        .or_else(|_| {
+            NaiveDate::parse_from_str(s, "%Y-%m-%d").map(|date| date.and_hms_opt(0, 0, 0).unwrap())
+        })
+        .map_err(|err| eyre!("Failed to parse date `{s}`: {err}"))
+}
+
 pub fn create_file<P>(path: P) -> Result<File>
 where
     P: AsRef<Path>,